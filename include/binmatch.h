@@ -0,0 +1,61 @@
+/*
+ * C interface of binmatch, see src/ffi.rs
+ *
+ * Build the shared library with
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ */
+#ifndef BINMATCH_H
+#define BINMATCH_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes, the positive ones mirror binmatch::BinmatchError */
+enum BinmatchStatus {
+    BINMATCH_OK = 0,
+    BINMATCH_NULL_POINTER = -1,
+    BINMATCH_PANIC = -2,
+    BINMATCH_INVALID_UTF8 = -3,
+    BINMATCH_PATTERN_PARSE_ERROR = 1,
+    BINMATCH_PATTERN_LENGTH_ERROR = 2,
+    BINMATCH_STRIDE_ERROR = 3,
+    BINMATCH_CAPTURE_BUFFER_ERROR = 4,
+    BINMATCH_REPLACEMENT_LENGTH_ERROR = 5,
+    BINMATCH_PATCH_ARROW_ERROR = 6,
+    BINMATCH_LENGTH_MISMATCH_ERROR = 7,
+    BINMATCH_MIXED_TOKEN = 8,
+};
+
+typedef struct BinmatchPattern BinmatchPattern;
+
+typedef struct BinmatchMatch {
+    size_t start;
+    size_t len;
+    /* The values captured by the placeholders, NULL if there are none */
+    uint8_t *captures;
+    size_t capture_count;
+} BinmatchMatch;
+
+/* Parses `pattern`, `*out` has to be freed with binmatch_pattern_free */
+int binmatch_pattern_new(const char *pattern, BinmatchPattern **out);
+
+/* Frees a Pattern, NULL is ignored */
+void binmatch_pattern_free(BinmatchPattern *pattern);
+
+/* Finds every match in `len` bytes at `data`, `*out` is NULL if nothing matched and has to be
+ * freed with binmatch_matches_free otherwise */
+int binmatch_find(const BinmatchPattern *pattern, const uint8_t *data, size_t len,
+                  BinmatchMatch **out, size_t *count);
+
+/* Frees the matches returned by binmatch_find, NULL is ignored */
+void binmatch_matches_free(BinmatchMatch *matches, size_t count);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BINMATCH_H */
@@ -0,0 +1,86 @@
+use crate::{Match, MatchOptions, Pattern};
+use thiserror::Error;
+
+/// Why [Pattern::find_matches_in_base64] couldn't decode its input
+///
+/// Positions are byte offsets into the base64 string
+#[derive(Error, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Base64Error {
+    #[error("Invalid character {character:?} at position {position}")]
+    InvalidCharacter { character: char, position: usize },
+    #[error("Padding at position {position} is followed by data or too long")]
+    InvalidPadding { position: usize },
+    #[error("The single character at position {position} doesn't encode a full byte")]
+    InvalidLength { position: usize },
+}
+
+impl Pattern {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [Pattern::find_with] with default options, for a haystack given as base64, e.g. a
+    /// blob field of a JSON log
+    ///
+    /// Both the standard and the URL-safe alphabet are accepted, the `=` padding is optional  
+    /// The offsets of the matches refer to the decoded bytes
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The string contains a character of neither alphabet, including whitespace
+    /// - Padding is followed by data or longer than needed
+    /// - The last group has a single character, which can't encode a byte
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("4D 5A ?? 00").unwrap();
+    /// // base64 of 00 4D 5A 90 00
+    /// assert_eq!(pattern.find_matches_in_base64("AE1akAA=").unwrap()[0].start(), 1);
+    /// assert_eq!(pattern.find_matches_in_base64("AE1akAA").unwrap()[0].start(), 1);
+    /// ```
+    pub fn find_matches_in_base64(&self, base64: &str) -> Result<Vec<Match>, Base64Error> {
+        Ok(self.find_with(&decode_base64(base64)?, &MatchOptions::new()))
+    }
+}
+
+fn decode_base64(base64: &str) -> Result<Vec<u8>, Base64Error> {
+    let data = base64.trim_end_matches('=');
+    let padding = base64.len() - data.len();
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for (position, character) in data.char_indices() {
+        let value = match character {
+            'A'..='Z' => character as u32 - 'A' as u32,
+            'a'..='z' => character as u32 - 'a' as u32 + 26,
+            '0'..='9' => character as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            '=' => return Err(Base64Error::InvalidPadding { position }),
+            _ => {
+                return Err(Base64Error::InvalidCharacter {
+                    character,
+                    position,
+                })
+            }
+        };
+        bits = bits << 6 | value;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+    // Padding only completes the last group to 4 characters
+    if padding > 0 && (padding > 2 || !(data.len() + padding).is_multiple_of(4)) {
+        return Err(Base64Error::InvalidPadding {
+            position: data.len(),
+        });
+    }
+    if data.len() % 4 == 1 {
+        return Err(Base64Error::InvalidLength {
+            position: data.len() - 1,
+        });
+    }
+    Ok(bytes)
+}
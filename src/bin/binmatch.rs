@@ -0,0 +1,189 @@
+//! Command line interface of binmatch, built with the `cli` feature
+//!
+//! Exits with 0 if anything matched, 1 if nothing did and 2 on errors, like grep
+
+use binmatch::{FileMatch, MatchOptions, Patch, PatchOptions, Pattern};
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage:
+    binmatch find <pattern> <files...> [--context <bytes>] [--json]
+    binmatch count <pattern> <files...>
+    binmatch patch '<pattern> -> <template>' <file> [--dry-run] [--max <patches>]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("binmatch: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Returns whether anything matched
+fn run(args: &[String]) -> Result<bool, String> {
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .and_then(|value| value.parse().ok())
+                .ok_or(format!("{name} needs a number\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--dry-run" => options.dry_run = true,
+            "--context" => options.context = Some(value("--context")?),
+            "--max" => options.max_patches = Some(value("--max")?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(true);
+            }
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    match positional.as_slice() {
+        ["find", pattern, files @ ..] if !files.is_empty() => find(pattern, files, &options),
+        ["count", pattern, files @ ..] if !files.is_empty() => count(pattern, files),
+        ["patch", patch, file] => patch_file(patch, file, &options),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    json: bool,
+    dry_run: bool,
+    context: Option<usize>,
+    max_patches: Option<usize>,
+}
+
+fn parse_pattern(pattern: &str) -> Result<Pattern, String> {
+    Pattern::new(pattern).map_err(|err| format!("invalid pattern `{pattern}`: {err}"))
+}
+
+fn find_in(pattern: &Pattern, file: &str) -> Result<Vec<FileMatch>, String> {
+    pattern
+        .find_in_file(file.as_ref())
+        .map_err(|err| format!("{file}: {err}"))
+}
+
+fn find(pattern: &str, files: &[&str], options: &Options) -> Result<bool, String> {
+    let pattern = parse_pattern(pattern)?;
+    let mut any = false;
+    let mut json = Vec::new();
+    for file in files {
+        let found = find_in(&pattern, file)?;
+        any |= !found.is_empty();
+        for m in found {
+            if options.json {
+                json.push(format!(
+                    r#"{{"file":{},"offset":{},"length":{},"captures":"{}"}}"#,
+                    json_string(file),
+                    m.offset(),
+                    m.len(),
+                    hex(m.captures().iter().map(|capture| capture.value))
+                ));
+                continue;
+            }
+            println!(
+                "{file}:{:#010x}: {}",
+                m.offset(),
+                hex(m.bytes().iter().copied())
+            );
+            if let Some(context) = options.context {
+                print!("{}", context_dump(&pattern, file.as_ref(), &m, context)?);
+            }
+        }
+    }
+    if options.json {
+        println!("[{}]", json.join(","));
+    }
+    Ok(any)
+}
+
+/// Hexdump of the match and `context` bytes around it, the offsets are relative to the first
+/// shown byte
+fn context_dump(
+    pattern: &Pattern,
+    path: &Path,
+    found: &FileMatch,
+    context: usize,
+) -> Result<String, String> {
+    let start = found.offset().saturating_sub(context as u64);
+    let mut window = Vec::new();
+    File::open(path)
+        .and_then(|mut file| {
+            file.seek(SeekFrom::Start(start))?;
+            file.take((found.offset() - start) + (found.len() + context) as u64)
+                .read_to_end(&mut window)
+        })
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    let relative = (found.offset() - start) as usize;
+    Ok(pattern
+        .find_with(&window, &MatchOptions::new())
+        .into_iter()
+        .find(|m| m.start() == relative)
+        .map(|m| m.hexdump(&window, context))
+        .unwrap_or_default())
+}
+
+fn count(pattern: &str, files: &[&str]) -> Result<bool, String> {
+    let pattern = parse_pattern(pattern)?;
+    let mut any = false;
+    for file in files {
+        let found = find_in(&pattern, file)?.len();
+        any |= found > 0;
+        println!("{file}: {found}");
+    }
+    Ok(any)
+}
+
+fn patch_file(patch: &str, file: &str, options: &Options) -> Result<bool, String> {
+    let patch = Patch::parse(patch).map_err(|err| format!("invalid patch `{patch}`: {err}"))?;
+    let mut patch_options = PatchOptions::new();
+    if let Some(max_patches) = options.max_patches {
+        patch_options = patch_options.max_patches(max_patches);
+    }
+    if options.dry_run {
+        patch_options = patch_options.dry_run();
+    }
+    let report = patch
+        .pattern()
+        .patch_file(file.as_ref(), patch.template(), &patch_options)
+        .map_err(|err| format!("{file}: {err}"))?;
+    print!("{report}");
+    Ok(!report.is_empty())
+}
+
+fn hex(bytes: impl Iterator<Item = u8>) -> String {
+    bytes.fold(String::new(), |mut out, byte| {
+        let _ = write!(out, "{byte:02x}");
+        out
+    })
+}
+
+fn json_string(string: &str) -> String {
+    let mut out = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
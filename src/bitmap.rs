@@ -0,0 +1,168 @@
+use crate::{vec, BinmatchError, Vec};
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
+
+/// One bit per haystack offset, set where a match starts
+///
+/// Returned by [Pattern::match_bitmap](crate::Pattern::match_bitmap)  
+/// Bitmaps can be combined with `|` and `&`, the result has the length of the left one  
+/// Use [MatchBitmap::try_or_assign] and [MatchBitmap::try_and_assign] to reject bitmaps of
+/// different lengths instead
+///
+/// # Example:
+/// ```
+/// # use binmatch::Pattern;
+/// let data = vec![0x00, 0x11, 0x00, 0x22];
+/// let zero = Pattern::new("00").unwrap().match_bitmap(&data);
+/// let two = Pattern::new("22").unwrap().match_bitmap(&data);
+/// let either = &zero | &two;
+/// assert_eq!(either.iter_ones().collect::<Vec<usize>>(), vec![0, 2, 3]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct MatchBitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl MatchBitmap {
+    pub(crate) fn new(len: usize) -> MatchBitmap {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn set(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    /// Returns whether a match starts at `index`, `false` if `index` is out of bounds
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> bool {
+        index < self.len && self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Number of set bits, i.e. the number of matches
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Iterates over the indices of all set bits in ascending order
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            core::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    /// The raw bits, bit `i % 64` of word `i / 64` corresponds to offset `i`
+    #[inline(always)]
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Same as `self |= other`, but returns an Error instead of panicking
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The bitmaps don't have the same length, `self` is unchanged
+    pub fn try_or_assign(&mut self, other: &MatchBitmap) -> Result<(), BinmatchError> {
+        self.check_len(other)?;
+        self.or_words(other);
+        Ok(())
+    }
+
+    /// Same as `self &= other`, but returns an Error instead of panicking
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The bitmaps don't have the same length, `self` is unchanged
+    pub fn try_and_assign(&mut self, other: &MatchBitmap) -> Result<(), BinmatchError> {
+        self.check_len(other)?;
+        self.and_words(other);
+        Ok(())
+    }
+
+    /// ORs the words both bitmaps have, bits of `other` past `self.len` are cleared again
+    fn or_words(&mut self, other: &MatchBitmap) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+        if let (Some(last), 1..) = (self.words.last_mut(), self.len % 64) {
+            *last &= (1 << (self.len % 64)) - 1;
+        }
+    }
+
+    /// ANDs the words both bitmaps have, words `other` doesn't have are cleared
+    fn and_words(&mut self, other: &MatchBitmap) {
+        let shared = other.words.len().min(self.words.len());
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+        self.words[shared..].fill(0);
+    }
+
+    fn check_len(&self, other: &MatchBitmap) -> Result<(), BinmatchError> {
+        match self.len == other.len {
+            true => Ok(()),
+            false => Err(BinmatchError::LengthMismatchError {
+                expected: self.len,
+                found: other.len,
+            }),
+        }
+    }
+
+    /// Number of bits, which is the length of the haystack the bitmap was created from
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl BitOrAssign<&MatchBitmap> for MatchBitmap {
+    /// Keeps the length of `self`, bits of `rhs` past it are dropped, see
+    /// [MatchBitmap::try_or_assign] to reject different lengths instead
+    fn bitor_assign(&mut self, rhs: &MatchBitmap) {
+        self.or_words(rhs);
+    }
+}
+
+impl BitAndAssign<&MatchBitmap> for MatchBitmap {
+    /// Keeps the length of `self`, bits `rhs` doesn't have count as unset, see
+    /// [MatchBitmap::try_and_assign] to reject different lengths instead
+    fn bitand_assign(&mut self, rhs: &MatchBitmap) {
+        self.and_words(rhs);
+    }
+}
+
+impl BitOr for &MatchBitmap {
+    type Output = MatchBitmap;
+
+    fn bitor(self, rhs: &MatchBitmap) -> MatchBitmap {
+        let mut result = self.clone();
+        result |= rhs;
+        result
+    }
+}
+
+impl BitAnd for &MatchBitmap {
+    type Output = MatchBitmap;
+
+    fn bitand(self, rhs: &MatchBitmap) -> MatchBitmap {
+        let mut result = self.clone();
+        result &= rhs;
+        result
+    }
+}
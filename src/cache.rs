@@ -0,0 +1,310 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{Pattern, PatternElement, PatternSet, Vec};
+use alloc::string::ToString;
+
+const MAGIC: &[u8; 4] = b"BMPS";
+// Version 1 stored lengths as u32 instead of LEB128 varints
+const FORMAT_VERSION: u8 = 2;
+
+const PATTERN_MAGIC: &[u8; 4] = b"BMPT";
+const PATTERN_FORMAT_VERSION: u8 = 1;
+
+const TAG_LITERAL: u8 = 0;
+const TAG_PLACEHOLDER: u8 = 1;
+const TAG_IGNORE: u8 = 2;
+
+/// Why [PatternSet::from_bytes] rejected its input
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum SetDecodeError {
+    #[cfg_attr(feature = "std", error("The data is not an encoded PatternSet"))]
+    BadMagic,
+    #[cfg_attr(
+        feature = "std",
+        error("The format version {0} is not supported by this version of binmatch")
+    )]
+    UnsupportedVersion(u8),
+    #[cfg_attr(feature = "std", error("The checksum doesn't match the data"))]
+    ChecksumMismatch,
+    #[cfg_attr(feature = "std", error("The data ends in the middle of the set"))]
+    Truncated,
+    #[cfg_attr(feature = "std", error("A length in the set doesn't fit into a usize"))]
+    InvalidLength,
+    #[cfg_attr(feature = "std", error("Unknown element tag {0}"))]
+    InvalidElement(u8),
+    #[cfg_attr(feature = "std", error("A name is not valid UTF-8"))]
+    InvalidName,
+}
+
+/// Why [Pattern::from_bytes] rejected its input
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum PatternDecodeError {
+    #[cfg_attr(feature = "std", error("The data is not an encoded Pattern"))]
+    BadMagic,
+    #[cfg_attr(
+        feature = "std",
+        error("The format version {0} is not supported by this version of binmatch")
+    )]
+    UnsupportedVersion(u8),
+    #[cfg_attr(feature = "std", error("The data ends in the middle of the Pattern"))]
+    Truncated,
+    #[cfg_attr(
+        feature = "std",
+        error("The length of the Pattern doesn't fit into a usize")
+    )]
+    InvalidLength,
+    #[cfg_attr(feature = "std", error("Unknown element tag {0}"))]
+    InvalidElement(u8),
+    #[cfg_attr(feature = "std", error("{0} bytes follow the encoded Pattern"))]
+    TrailingData(usize),
+}
+
+impl Pattern {
+    /// Encodes the Pattern into a compact, versioned binary format, e.g. to embed it into another
+    /// file format
+    ///
+    /// The magic bytes and format version are followed by the number of elements as a LEB128
+    /// varint and a tag byte per element, literals are followed by their byte  
+    /// Capture names and transforms are not stored
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("E8 ?? __ C3").unwrap();
+    /// let bytes = pattern.to_bytes();
+    /// assert_eq!(bytes.len(), 4 + 1 + 1 + 6);
+    /// assert_eq!(Pattern::from_bytes(&bytes).unwrap(), pattern);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(PATTERN_MAGIC);
+        out.push(PATTERN_FORMAT_VERSION);
+        write_elements(&mut out, &self.data);
+        out
+    }
+
+    /// Decodes a Pattern written by [Pattern::to_bytes]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The data doesn't start with the magic bytes or was written in another format version
+    /// - The data ends early, has bytes left over or contains an unknown element, e.g. one added
+    ///   by a later version of binmatch
+    pub fn from_bytes(bytes: &[u8]) -> Result<Pattern, PatternDecodeError> {
+        let rest = bytes
+            .strip_prefix(PATTERN_MAGIC)
+            .ok_or(PatternDecodeError::BadMagic)?;
+        let (&version, rest) = rest.split_first().ok_or(PatternDecodeError::Truncated)?;
+        if version != PATTERN_FORMAT_VERSION {
+            return Err(PatternDecodeError::UnsupportedVersion(version));
+        }
+        let mut reader = Reader { bytes: rest };
+        let pattern = Pattern::from_elements(reader.elements()?);
+        if !reader.bytes.is_empty() {
+            return Err(PatternDecodeError::TrailingData(reader.bytes.len()));
+        }
+        Ok(pattern)
+    }
+}
+
+impl PatternSet {
+    /// Encodes the set into a versioned binary format, for caching a set instead of parsing it again
+    ///
+    /// Lengths are LEB128 varints and the elements are tagged like in [Pattern::to_bytes], the
+    /// priorities are stored in little endian, followed by a CRC-32 of everything before it  
+    /// Capture names and transforms of the Patterns are not stored
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Pattern, PatternSet};
+    /// let set: PatternSet = [("ret", Pattern::new("C3").unwrap())].into_iter().collect();
+    /// let bytes = set.to_bytes();
+    /// assert_eq!(PatternSet::from_bytes(&bytes).unwrap(), set);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+        write_len(&mut out, self.len());
+        for (index, pattern) in self.patterns().iter().enumerate() {
+            let name = self.name(index).unwrap_or_default();
+            write_len(&mut out, name.len());
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&self.priority(index).unwrap_or_default().to_le_bytes());
+            write_elements(&mut out, &pattern.data);
+        }
+        let checksum = crc32(&out);
+        out.extend_from_slice(&checksum.to_le_bytes());
+        out
+    }
+
+    /// Decodes a set written by [PatternSet::to_bytes]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The data doesn't start with the magic bytes or was written in another format version
+    /// - The checksum doesn't match, e.g. because the data was truncated or modified
+    /// - The data is malformed despite a valid checksum
+    pub fn from_bytes(bytes: &[u8]) -> Result<PatternSet, SetDecodeError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(SetDecodeError::BadMagic);
+        }
+        let version = *bytes.get(MAGIC.len()).ok_or(SetDecodeError::Truncated)?;
+        if version != FORMAT_VERSION {
+            return Err(SetDecodeError::UnsupportedVersion(version));
+        }
+        let Some(body_len) = bytes.len().checked_sub(4) else {
+            return Err(SetDecodeError::Truncated);
+        };
+        let (body, checksum) = bytes.split_at(body_len);
+        if body.len() <= MAGIC.len() {
+            return Err(SetDecodeError::Truncated);
+        }
+        if crc32(body).to_le_bytes() != checksum {
+            return Err(SetDecodeError::ChecksumMismatch);
+        }
+
+        let mut reader = Reader {
+            bytes: &body[MAGIC.len() + 1..],
+        };
+        let mut names = Vec::new();
+        let mut priorities = Vec::new();
+        let mut patterns = Vec::new();
+        for _ in 0..reader.len()? {
+            let name_len = reader.len()?;
+            let name = core::str::from_utf8(reader.take(name_len)?)
+                .map_err(|_| SetDecodeError::InvalidName)?;
+            names.push(name.to_string());
+            priorities.push(i32::from_le_bytes(reader.array()?));
+            patterns.push(Pattern::from_elements(reader.elements()?));
+        }
+        if !reader.bytes.is_empty() {
+            return Err(SetDecodeError::Truncated);
+        }
+        Ok(PatternSet::from_parts(patterns, names, priorities))
+    }
+}
+
+/// Writes `len` as a LEB128 varint
+fn write_len(out: &mut Vec<u8>, mut len: usize) {
+    loop {
+        let byte = (len & 0x7F) as u8;
+        len >>= 7;
+        if len == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Writes the number of elements and a tag byte per element, literals are followed by their byte
+fn write_elements(out: &mut Vec<u8>, data: &[PatternElement]) {
+    write_len(out, data.len());
+    for element in data {
+        match element {
+            PatternElement::Literal(byte) => out.extend_from_slice(&[TAG_LITERAL, *byte]),
+            PatternElement::Placeholder => out.push(TAG_PLACEHOLDER),
+            PatternElement::Ignore => out.push(TAG_IGNORE),
+        }
+    }
+}
+
+/// The ways the parts shared by both formats can be malformed, converted into the error of the
+/// format being decoded
+enum ReadError {
+    Truncated,
+    InvalidLength,
+    InvalidElement(u8),
+}
+
+impl From<ReadError> for PatternDecodeError {
+    fn from(err: ReadError) -> PatternDecodeError {
+        match err {
+            ReadError::Truncated => PatternDecodeError::Truncated,
+            ReadError::InvalidLength => PatternDecodeError::InvalidLength,
+            ReadError::InvalidElement(tag) => PatternDecodeError::InvalidElement(tag),
+        }
+    }
+}
+
+impl From<ReadError> for SetDecodeError {
+    fn from(err: ReadError) -> SetDecodeError {
+        match err {
+            ReadError::Truncated => SetDecodeError::Truncated,
+            ReadError::InvalidLength => SetDecodeError::InvalidLength,
+            ReadError::InvalidElement(tag) => SetDecodeError::InvalidElement(tag),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ReadError> {
+        if self.bytes.len() < len {
+            return Err(ReadError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReadError> {
+        let (taken, rest) = self.bytes.split_first_chunk().ok_or(ReadError::Truncated)?;
+        self.bytes = rest;
+        Ok(*taken)
+    }
+
+    /// Reads a LEB128 varint written by [write_len]
+    fn len(&mut self) -> Result<usize, ReadError> {
+        let mut len = 0usize;
+        for shift in (0..).step_by(7) {
+            let [byte] = self.array()?;
+            let bits = usize::from(byte & 0x7F);
+            if shift >= usize::BITS || (bits << shift) >> shift != bits {
+                return Err(ReadError::InvalidLength);
+            }
+            len |= bits << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        Ok(len)
+    }
+
+    /// Reads the elements written by [write_elements]
+    fn elements(&mut self) -> Result<Vec<PatternElement>, ReadError> {
+        // Not preallocated, the length may be corrupted
+        let mut data = Vec::new();
+        for _ in 0..self.len()? {
+            data.push(match self.array()? {
+                [TAG_LITERAL] => {
+                    let [byte] = self.array()?;
+                    PatternElement::Literal(byte)
+                }
+                [TAG_PLACEHOLDER] => PatternElement::Placeholder,
+                [TAG_IGNORE] => PatternElement::Ignore,
+                [tag] => return Err(ReadError::InvalidElement(tag)),
+            });
+        }
+        Ok(data)
+    }
+}
+
+/// CRC-32 (IEEE), computed bitwise since it only runs once per encoded set
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
@@ -0,0 +1,247 @@
+use crate::{Arc, BinmatchError, Pattern, String, Vec};
+use core::slice;
+
+/// A single byte captured by a placeholder (`??`)
+///
+/// For a default search `haystack[haystack_offset] == value` and `haystack_offset == match.start() + pattern_index` always hold  
+/// Searches with a view option (see [MatchOptions](crate::MatchOptions)) keep the first one, while
+/// [Pattern::map_capture](crate::Pattern::map_capture) only keeps the second one
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Capture {
+    /// The captured byte
+    pub value: u8,
+    /// Index of the placeholder inside the Pattern
+    pub pattern_index: usize,
+    /// Absolute index of the captured byte inside the haystack
+    pub haystack_offset: usize,
+}
+
+/// The captures of a [Match](crate::Match), in pattern order
+///
+/// Captures are addressed by their ordinal: 0 is the first `??` in the Pattern, 1 the second and so on  
+/// Captures need the `alloc` feature, without it [FixedPattern::capture_array](crate::FixedPattern::capture_array)
+/// and [FixedPattern::read_captures_into](crate::FixedPattern::read_captures_into) read the same bytes
+///
+/// # Example:
+/// ```
+/// # use binmatch::{MatchOptions, Pattern};
+/// let pattern = Pattern::new("AA ?? __ ??").unwrap();
+/// let data = vec![0x00, 0xAA, 0x11, 0x22, 0x33];
+/// let found = pattern.find_with(&data, &MatchOptions::new());
+/// let second = found[0].captures().get(1).unwrap();
+/// assert_eq!(second.value, 0x33);
+/// assert_eq!(second.pattern_index, 3);
+/// assert_eq!(second.haystack_offset, 4);
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Captures {
+    captures: Vec<Capture>,
+    names: Option<Arc<[String]>>, // Shared with the Pattern, indexed by ordinal
+}
+
+impl Captures {
+    pub(crate) fn new(captures: Vec<Capture>, names: Option<Arc<[String]>>) -> Captures {
+        Self { captures, names }
+    }
+
+    pub(crate) fn shift(&mut self, delta: usize) {
+        for capture in self.captures.iter_mut() {
+            capture.haystack_offset += delta;
+        }
+    }
+
+    pub(crate) fn unshift(&mut self, delta: usize) {
+        for capture in self.captures.iter_mut() {
+            capture.haystack_offset -= delta;
+        }
+    }
+
+    /// The capture with the given ordinal, `None` if the Pattern has fewer placeholders
+    #[inline(always)]
+    pub fn get(&self, ordinal: usize) -> Option<Capture> {
+        self.captures.get(ordinal).copied()
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> slice::Iter<'_, Capture> {
+        self.captures.iter()
+    }
+
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[Capture] {
+        &self.captures
+    }
+
+    /// Borrows the bytes of `len` consecutive captures starting at `start_ordinal` directly from `haystack`
+    ///
+    /// `None` if there aren't enough captures, `len` is 0, or the captures aren't a contiguous run of placeholders  
+    /// `haystack` has to be the searched haystack
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("4C 6F ?? ?? ?? ?? 00").unwrap();
+    /// let data = b"Lo1234\0".to_vec();
+    /// let found = pattern.find_with(&data, &MatchOptions::new());
+    /// assert_eq!(found[0].captures().run(&data, 0, 4), Some(&b"1234"[..]));
+    /// assert_eq!(found[0].captures().run(&data, 2, 3), None);
+    /// ```
+    pub fn run<'h>(
+        &self,
+        haystack: &'h [u8],
+        start_ordinal: usize,
+        len: usize,
+    ) -> Option<&'h [u8]> {
+        let run = self
+            .captures
+            .get(start_ordinal..start_ordinal.checked_add(len)?)?;
+        let first = run.first()?;
+        let contiguous = run.iter().enumerate().all(|(i, capture)| {
+            capture.pattern_index == first.pattern_index + i
+                && capture.haystack_offset == first.haystack_offset + i
+        });
+        if !contiguous {
+            return None;
+        }
+        haystack.get(first.haystack_offset..first.haystack_offset + len)
+    }
+
+    /// Copies the captured bytes in pattern order into an array, without allocating
+    ///
+    /// `None` unless there are exactly `N` captures
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("?? AA ??").unwrap();
+    /// let found = pattern.find_with(&[0x01, 0xAA, 0x02], &MatchOptions::new());
+    /// assert_eq!(found[0].captures().to_array::<2>(), Some([0x01, 0x02]));
+    /// assert_eq!(found[0].captures().to_array::<3>(), None);
+    /// ```
+    pub fn to_array<const N: usize>(&self) -> Option<[u8; N]> {
+        if self.captures.len() != N {
+            return None;
+        }
+        let mut array = [0; N];
+        for (byte, capture) in array.iter_mut().zip(&self.captures) {
+            *byte = capture.value;
+        }
+        Some(array)
+    }
+
+    /// Copies the captured bytes in pattern order to the start of `buf`, returning how many were written
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `buf` is shorter than the number of captures, nothing is written in that case
+    pub fn read_into(&self, buf: &mut [u8]) -> Result<usize, BinmatchError> {
+        if buf.len() < self.captures.len() {
+            return Err(BinmatchError::CaptureBufferError {
+                needed: self.captures.len(),
+                available: buf.len(),
+            });
+        }
+        for (byte, capture) in buf.iter_mut().zip(&self.captures) {
+            *byte = capture.value;
+        }
+        Ok(self.captures.len())
+    }
+
+    /// The capture of the placeholder named `name`, see [Pattern::with_capture_names]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("4C 45 ?? ??").unwrap().with_capture_names(&["len", "flags"]);
+    /// let data = vec![0x4C, 0x45, 0x10, 0x01];
+    /// let found = pattern.find_with(&data, &MatchOptions::new());
+    /// assert_eq!(found[0].captures().name("flags").unwrap().value, 0x01);
+    /// assert_eq!(found[0].captures().name("missing"), None);
+    /// ```
+    pub fn name(&self, name: &str) -> Option<Capture> {
+        if name.is_empty() {
+            return None;
+        }
+        let ordinal = self.names.as_ref()?.iter().position(|n| n == name)?;
+        self.get(ordinal)
+    }
+
+    /// Iterates over all named captures as `(name, capture)` pairs, in pattern order
+    pub fn named(&self) -> impl Iterator<Item = (&str, Capture)> + '_ {
+        self.names
+            .iter()
+            .flat_map(|names| names.iter())
+            .zip(self.captures.iter())
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, capture)| (name.as_str(), *capture))
+    }
+
+    /// Converts to the `(value, haystack offset)` tuples older versions returned
+    pub fn to_tuples(&self) -> Vec<(u8, usize)> {
+        self.captures
+            .iter()
+            .map(|capture| (capture.value, capture.haystack_offset))
+            .collect()
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.captures.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.captures.is_empty()
+    }
+}
+
+impl Pattern {
+    /// Names the placeholders of the Pattern by ordinal: `names[0]` names the first `??`, `names[1]` the second one
+    ///
+    /// The names are stored once in the Pattern and shared by all matches, see [Captures::name]  
+    /// An empty name leaves a placeholder unnamed, if a name is used twice the first placeholder wins
+    pub fn with_capture_names(mut self, names: &[&str]) -> Pattern {
+        self.capture_names = Some(names.iter().map(|name| String::from(*name)).collect());
+        self
+    }
+
+    /// The capture names set by [Pattern::with_capture_names], by ordinal
+    pub fn capture_names(&self) -> &[String] {
+        self.capture_names.as_deref().unwrap_or_default()
+    }
+}
+
+impl<'a> IntoIterator for &'a Captures {
+    type Item = &'a Capture;
+    type IntoIter = slice::Iter<'a, Capture>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.captures.iter()
+    }
+}
+
+impl From<&Captures> for Vec<(u8, usize)> {
+    fn from(captures: &Captures) -> Self {
+        captures.to_tuples()
+    }
+}
+
+/// Compares against `(value, haystack offset)` tuples, the shape older versions returned
+impl PartialEq<[(u8, usize)]> for Captures {
+    fn eq(&self, other: &[(u8, usize)]) -> bool {
+        self.captures.len() == other.len()
+            && self
+                .captures
+                .iter()
+                .zip(other)
+                .all(|(capture, (value, offset))| {
+                    capture.value == *value && capture.haystack_offset == *offset
+                })
+    }
+}
+
+impl<const N: usize> PartialEq<[(u8, usize); N]> for Captures {
+    fn eq(&self, other: &[(u8, usize); N]) -> bool {
+        *self == other[..]
+    }
+}
@@ -0,0 +1,141 @@
+use crate::file::DEFAULT_CHUNK_SIZE;
+use crate::{FileMatch, Match, Pattern, PatternSet};
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// Number of bytes a thread of [PatternSet::par_scan_to_channel] scans before checking whether the
+/// scan was cancelled
+const CHUNK_LEN: usize = 64 * 1024;
+
+impl Pattern {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Sends every match in `haystack` to `tx` as soon as it is found, instead of collecting them,
+    /// e.g. to hand them to consumer threads while the scan is still running
+    ///
+    /// Returns the number of matches sent, the matches are the same as those of [Pattern::find_with]
+    /// with default options and are sent in ascending order  
+    /// Dropping the receiver cancels the scan: the match that couldn't be sent is discarded and
+    /// the scan returns right away
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Match, Pattern};
+    /// # use std::sync::mpsc;
+    /// let pattern = Pattern::new("CC").unwrap();
+    /// let (tx, rx) = mpsc::channel::<Match>();
+    /// let consumer = std::thread::spawn(move || rx.iter().map(|m| m.start()).collect::<Vec<_>>());
+    /// assert_eq!(pattern.scan_to_channel(&[0xCC, 0x00, 0xCC], tx), 2);
+    /// assert_eq!(consumer.join().unwrap(), vec![0, 2]);
+    /// ```
+    pub fn scan_to_channel(&self, haystack: &[u8], tx: Sender<Match>) -> usize {
+        let mut sent = 0;
+        let Some(last) = haystack.len().checked_sub(self.len) else {
+            return sent;
+        };
+        for start in 0..=last {
+            if let Some(m) = self.match_at_unchecked(haystack, start) {
+                if tx.send(m).is_err() {
+                    break;
+                }
+                sent += 1;
+            }
+        }
+        sent
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [Pattern::scan_to_channel] for a stream, see [Pattern::find_in_reader]
+    ///
+    /// Dropping the receiver cancels the scan, nothing is read afterwards
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - Reading from `reader` fails, the matches found before were already sent
+    pub fn scan_reader_to_channel(
+        &self,
+        reader: impl Read,
+        tx: Sender<FileMatch>,
+    ) -> io::Result<usize> {
+        let mut sent = 0;
+        self.scan_reader(reader, DEFAULT_CHUNK_SIZE, 0, |found| {
+            let delivered = tx.send(found).is_ok();
+            sent += usize::from(delivered);
+            delivered
+        })
+        .map_err(|(_, err)| err)?;
+        Ok(sent)
+    }
+}
+
+impl PatternSet {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [PatternSet::par_find_matches], but every match is sent to `tx` together with the
+    /// index of its Pattern as soon as it is found
+    ///
+    /// Returns the number of matches sent  
+    /// The threads scan the haystack chunk by chunk, so the matches arrive in ascending order per
+    /// chunk but not overall  
+    /// Dropping the receiver cancels the scan: every thread stops at its next match or chunk
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::PatternSet;
+    /// # use std::sync::mpsc;
+    /// let set: PatternSet = "ret: C3\nint3: CC".parse().unwrap();
+    /// let (tx, rx) = mpsc::channel();
+    /// assert_eq!(set.par_scan_to_channel(&[0xCC, 0xC3, 0xCC], tx), 3);
+    /// let mut found: Vec<(usize, usize)> = rx.iter().map(|(i, m)| (i, m.start())).collect();
+    /// found.sort();
+    /// assert_eq!(found, vec![(0, 1), (1, 0), (1, 2)]);
+    /// ```
+    pub fn par_scan_to_channel(&self, haystack: &[u8], tx: Sender<(usize, Match)>) -> usize {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        self.par_scan_to_channel_with(haystack, tx, threads, CHUNK_LEN)
+    }
+
+    /// Threads take turns scanning chunks of `chunk_len` bytes
+    pub(crate) fn par_scan_to_channel_with(
+        &self,
+        haystack: &[u8],
+        tx: Sender<(usize, Match)>,
+        threads: usize,
+        chunk_len: usize,
+    ) -> usize {
+        let chunk_len = chunk_len.max(1);
+        let chunks = haystack.len().div_ceil(chunk_len);
+        let threads = threads.clamp(1, chunks.max(1));
+        let overlap = self.patterns().iter().map(|p| p.len).max().unwrap_or(0);
+        let cancelled = AtomicBool::new(false);
+        let sent = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for first in 0..threads {
+                let (tx, cancelled, sent) = (tx.clone(), &cancelled, &sent);
+                scope.spawn(move || {
+                    for chunk in (first..chunks).step_by(threads) {
+                        if cancelled.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let start = chunk * chunk_len;
+                        let end = (start + chunk_len).min(haystack.len());
+                        let window = &haystack[start..(end + overlap).min(haystack.len())];
+                        // Starts in the overlap belong to the next chunk
+                        let found = self.find_matches(window).into_iter();
+                        for (index, m) in found.filter(|(_, m)| m.start() < end - start) {
+                            if tx.send((index, m.shifted(start))).is_err() {
+                                cancelled.store(true, Ordering::Relaxed);
+                                return;
+                            }
+                            sent.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+        sent.into_inner()
+    }
+}
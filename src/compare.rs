@@ -0,0 +1,95 @@
+use crate::{Pattern, PatternElement};
+
+impl Pattern {
+    /// Whether a byte sequence exists that both Patterns match at the same offset
+    ///
+    /// Two literals are compatible if they are equal, placeholders (`??`) and ignored bytes (`__`)
+    /// are compatible with anything  
+    /// Patterns of different lengths are compared at offset 0 over the length of the shorter one,
+    /// since the longer one only adds bytes the shorter one doesn't look at
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let call = Pattern::new("E8 ?? ?? ?? ??").unwrap();
+    /// assert!(call.is_compatible_with(&Pattern::new("?? 10 00 00 00").unwrap()));
+    /// assert!(call.is_compatible_with(&Pattern::new("E8 10").unwrap()));
+    /// assert!(!call.is_compatible_with(&Pattern::new("E9 ?? ?? ?? ??").unwrap()));
+    /// ```
+    pub fn is_compatible_with(&self, other: &Pattern) -> bool {
+        self.data
+            .iter()
+            .zip(&other.data)
+            .all(|(a, b)| intersect(*a, *b).is_some())
+    }
+
+    /// The most specific Pattern matching exactly the windows both Patterns match
+    ///
+    /// A literal wins over a wildcard, a placeholder wins over an ignored byte, so nothing either
+    /// Pattern captures is lost. Capture names and transforms are not kept  
+    /// `None` if the Patterns have different lengths or aren't
+    /// [compatible](Pattern::is_compatible_with)
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let a = Pattern::new("48 ?? __ __").unwrap();
+    /// let b = Pattern::new("__ 8B ?? __").unwrap();
+    /// assert_eq!(a.intersection(&b).unwrap().to_string(), "48 8B ?? __");
+    /// assert!(a.intersection(&Pattern::new("49 __ __ __").unwrap()).is_none());
+    /// ```
+    pub fn intersection(&self, other: &Pattern) -> Option<Pattern> {
+        if self.len != other.len {
+            return None;
+        }
+        self.data
+            .iter()
+            .zip(&other.data)
+            .map(|(a, b)| intersect(*a, *b))
+            .collect::<Option<_>>()
+            .map(Pattern::from_elements)
+    }
+
+    /// Whether every window matched by this Pattern is also matched by `other`, i.e. `other` is
+    /// the same or more general
+    ///
+    /// A literal is contained in the same literal, anything is contained in a placeholder (`??`) or
+    /// ignored byte (`__`), which match the same bytes. Captures aren't compared  
+    /// `false` if the Patterns have different lengths
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let specific = Pattern::new("E8 10 00 00 00").unwrap();
+    /// let general = Pattern::new("E8 ?? ?? ?? ??").unwrap();
+    /// assert!(specific.is_subset_of(&general));
+    /// assert!(!general.is_subset_of(&specific));
+    /// ```
+    pub fn is_subset_of(&self, other: &Pattern) -> bool {
+        self.len == other.len
+            && self
+                .data
+                .iter()
+                .zip(&other.data)
+                .all(|(element, other)| match (element, other) {
+                    (_, PatternElement::Placeholder | PatternElement::Ignore) => true,
+                    (element, other) => element == other,
+                })
+    }
+}
+
+/// The element matching the bytes both elements match, `None` if there are none
+fn intersect(a: PatternElement, b: PatternElement) -> Option<PatternElement> {
+    match (a, b) {
+        (PatternElement::Literal(a), PatternElement::Literal(b)) => {
+            (a == b).then_some(PatternElement::Literal(a))
+        }
+        (PatternElement::Literal(literal), _) | (_, PatternElement::Literal(literal)) => {
+            Some(PatternElement::Literal(literal))
+        }
+        (PatternElement::Placeholder, _) | (_, PatternElement::Placeholder) => {
+            Some(PatternElement::Placeholder)
+        }
+        (PatternElement::Ignore, PatternElement::Ignore) => Some(PatternElement::Ignore),
+    }
+}
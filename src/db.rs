@@ -0,0 +1,211 @@
+use crate::{BTreeMap, BTreeSet, Match, Pattern, PatternSet, String, Vec};
+
+/// A [Pattern] of a [PatternDb] together with its name, tags and metadata
+///
+/// # Example:
+/// ```
+/// # use binmatch::{DbEntry, Pattern};
+/// let entry = DbEntry::new("upx", Pattern::new("55 50 58 21").unwrap())
+///     .tag("packer")
+///     .metadata("severity", "low");
+/// assert!(entry.has_tag("packer"));
+/// assert_eq!(entry.get_metadata("severity"), Some("low"));
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct DbEntry {
+    name: String,
+    pattern: Pattern,
+    tags: BTreeSet<String>,
+    metadata: BTreeMap<String, String>,
+}
+
+impl DbEntry {
+    pub fn new(name: impl Into<String>, pattern: Pattern) -> DbEntry {
+        Self {
+            name: name.into(),
+            pattern,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a tag, adding the same tag twice has no effect
+    pub fn tag(mut self, tag: impl Into<String>) -> DbEntry {
+        self.tags.insert(tag.into());
+        self
+    }
+
+    /// Sets the metadata `key` to `value`, replacing an earlier value
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> DbEntry {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline(always)]
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    /// The tags in ascending order
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(String::as_str)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+}
+
+/// A collection of tagged [DbEntry]s, scanned with a [PatternSet] and reported grouped by tag and by name
+///
+/// Names should be unique, the matches of entries sharing a name are reported together
+///
+/// # Example:
+/// ```
+/// # use binmatch::{DbEntry, Pattern, PatternDb};
+/// let mut db = PatternDb::new();
+/// db.insert(DbEntry::new("sha256 k0", Pattern::new("98 2F 8A 42").unwrap()).tag("crypto"));
+/// db.insert(DbEntry::new("msvc prologue", Pattern::new("8B FF 55").unwrap()).tag("compiler"));
+/// let data = vec![0x8B, 0xFF, 0x55, 0x98, 0x2F, 0x8A, 0x42];
+/// let report = db.scan(&data);
+/// assert_eq!(report.total(), 2);
+/// assert_eq!(report.tag("crypto"), ["sha256 k0"]);
+/// assert_eq!(db.scan_tagged(&data, &["compiler"]).total(), 1);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PatternDb {
+    entries: Vec<DbEntry>,
+    set: PatternSet,
+}
+
+impl PatternDb {
+    pub fn new() -> PatternDb {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, entry: DbEntry) -> &mut PatternDb {
+        self.set.insert(entry.name.clone(), entry.pattern.clone());
+        self.entries.push(entry);
+        self
+    }
+
+    /// The entries in insertion order
+    #[inline(always)]
+    pub fn entries(&self) -> &[DbEntry] {
+        &self.entries
+    }
+
+    /// Scans `haystack` for all entries
+    pub fn scan(&self, haystack: &[u8]) -> ScanReport {
+        self.report(&self.set, (0..self.entries.len()).collect(), haystack)
+    }
+
+    /// Scans `haystack` only for entries with at least one of `tags`
+    ///
+    /// A [PatternSet] is built for just these entries, the others aren't looked at during the scan
+    pub fn scan_tagged(&self, haystack: &[u8], tags: &[&str]) -> ScanReport {
+        let selected: Vec<usize> = (0..self.entries.len())
+            .filter(|&index| tags.iter().any(|tag| self.entries[index].has_tag(tag)))
+            .collect();
+        let set = PatternSet::new(
+            selected
+                .iter()
+                .map(|&index| self.entries[index].pattern.clone())
+                .collect(),
+        );
+        self.report(&set, selected, haystack)
+    }
+
+    /// `selected` maps the indices of `set` to entries
+    fn report(&self, set: &PatternSet, selected: Vec<usize>, haystack: &[u8]) -> ScanReport {
+        let mut report = ScanReport::default();
+        for (index, m) in set.find_matches(haystack) {
+            let entry = &self.entries[selected[index]];
+            report.total += 1;
+            report
+                .patterns
+                .entry(entry.name.clone())
+                .or_default()
+                .push(m);
+            for tag in &entry.tags {
+                let names = report.tags.entry(tag.clone()).or_default();
+                if !names.contains(&entry.name) {
+                    names.push(entry.name.clone());
+                }
+            }
+        }
+        for names in report.tags.values_mut() {
+            names.sort();
+        }
+        report
+    }
+}
+
+/// The result of [PatternDb::scan], only entries that matched at least once are part of it
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct ScanReport {
+    patterns: BTreeMap<String, Vec<Match>>,
+    tags: BTreeMap<String, Vec<String>>,
+    total: usize,
+}
+
+impl ScanReport {
+    /// Number of matches of all entries
+    #[inline(always)]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The matches of every entry by name, ordered by their start
+    #[inline(always)]
+    pub fn by_pattern(&self) -> &BTreeMap<String, Vec<Match>> {
+        &self.patterns
+    }
+
+    /// The names of the matching entries by tag, in ascending order
+    #[inline(always)]
+    pub fn by_tag(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.tags
+    }
+
+    /// The matches of the entry called `name`, empty if it didn't match
+    pub fn matches(&self, name: &str) -> &[Match] {
+        self.patterns
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The names of the matching entries with `tag`, empty if none matched
+    pub fn tag(&self, tag: &str) -> &[String] {
+        self.tags.get(tag).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Number of matches of all entries with `tag`
+    pub fn tag_total(&self, tag: &str) -> usize {
+        self.tag(tag)
+            .iter()
+            .map(|name| self.matches(name).len())
+            .sum()
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Converts the matches into a [Report](crate::Report) to write them as CSV or JSON, ordered by name
+    #[cfg(feature = "std")]
+    pub fn to_report(&self) -> crate::Report {
+        let mut report = crate::Report::new();
+        for (name, matches) in &self.patterns {
+            report.add(name.clone(), matches.clone());
+        }
+        report
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{vec, BTreeMap, Pattern, PatternElement, Vec};
+
+/// A deterministic automaton for a [Pattern], see [Pattern::compile_dfa]
+///
+/// Every byte costs one lookup of its byte class and one table lookup, no matter how long the
+/// Pattern is  
+/// The table has one row per state and one column per byte class, where every distinct literal of
+/// the Pattern is a class and all other bytes share one
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Dfa {
+    classes: [u8; 256],
+    class_count: usize,
+    transitions: Vec<u32>,
+    accepting: Vec<bool>,
+    len: usize,
+}
+
+/// The position of a [Dfa] in a stream, created by [Dfa::start]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DfaState {
+    state: u32,
+    position: u64,
+}
+
+impl DfaState {
+    /// Stream position of the next byte, wraps around after `u64::MAX` bytes
+    #[inline(always)]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+/// A match reported by [Dfa::advance] when its last byte arrives
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct MatchEnd {
+    start: u64,
+    end: u64,
+}
+
+impl MatchEnd {
+    /// Stream position of the first byte covered by the match
+    #[inline(always)]
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Stream position one past the last byte covered by the match
+    #[inline(always)]
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+}
+
+impl Pattern {
+    /// Compiles the Pattern into a [Dfa], which matches a stream in constant time per byte
+    ///
+    /// The Dfa reports the same starts as [Pattern::find_match_starts], but no captures  
+    /// An empty Pattern never matches
+    ///
+    /// Placeholders and ignored bytes following a literal make the automaton remember where that
+    /// literal was seen, so the number of states can grow exponentially for Patterns like
+    /// `00 ?? ?? ?? ?? 01`. Use [Pattern::compile_dfa_with_limit] for untrusted Patterns  
+    /// `None` if the automaton needs more than `u32::MAX` states
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let dfa = Pattern::new("7E ?? 7E").unwrap().compile_dfa().unwrap();
+    /// let mut state = dfa.start();
+    /// let ends: Vec<u64> = [0x7E, 0x01, 0x7E, 0x02, 0x7E]
+    ///     .iter()
+    ///     .filter_map(|&byte| dfa.advance(&mut state, byte))
+    ///     .map(|found| found.start())
+    ///     .collect();
+    /// assert_eq!(ends, vec![0, 2]);
+    /// ```
+    pub fn compile_dfa(&self) -> Option<Dfa> {
+        self.compile_dfa_with_limit(u32::MAX as usize)
+    }
+
+    /// Same as [Pattern::compile_dfa], but gives up once the automaton needs more than
+    /// `max_states` states
+    pub fn compile_dfa_with_limit(&self, max_states: usize) -> Option<Dfa> {
+        let mut classes = [0; 256];
+        let mut representatives = Vec::new();
+        let mut literals: Vec<u8> = self
+            .data
+            .iter()
+            .filter_map(|element| match element {
+                PatternElement::Literal(byte) => Some(*byte),
+                _ => None,
+            })
+            .collect();
+        literals.sort_unstable();
+        literals.dedup();
+        // Class 0 holds every byte that isn't a literal, unless all of them are
+        if let Some(other) = (0..=255).find(|byte| literals.binary_search(byte).is_err()) {
+            representatives.push(other);
+        }
+        for &literal in &literals {
+            classes[literal as usize] = representatives.len() as u8;
+            representatives.push(literal);
+        }
+
+        // A state is the set of prefix lengths that end at the current byte
+        let start = vec![0];
+        let mut ids = BTreeMap::from([(start.clone(), 0)]);
+        let mut sets = vec![start];
+        let mut transitions = Vec::new();
+        let mut accepting = Vec::new();
+        let mut current = 0;
+        while let Some(set) = sets.get(current) {
+            accepting.push(self.len > 0 && set.last() == Some(&self.len));
+            let set = set.clone();
+            for &byte in &representatives {
+                let mut next = vec![0];
+                next.extend(
+                    set.iter()
+                        .filter(|&&prefix| match self.data.get(prefix) {
+                            Some(PatternElement::Literal(literal)) => *literal == byte,
+                            Some(_) => true,
+                            None => false,
+                        })
+                        .map(|prefix| prefix + 1)
+                        // The empty prefix is always there
+                        .filter(|&prefix| prefix > 0),
+                );
+                next.dedup();
+                let id = match ids.get(&next) {
+                    Some(&id) => id,
+                    None => {
+                        if sets.len() >= max_states {
+                            return None;
+                        }
+                        let id = sets.len() as u32;
+                        ids.insert(next.clone(), id);
+                        sets.push(next);
+                        id
+                    }
+                };
+                transitions.push(id);
+            }
+            current += 1;
+        }
+
+        Some(Dfa {
+            classes,
+            class_count: representatives.len(),
+            transitions,
+            accepting,
+            len: self.len,
+        })
+    }
+}
+
+impl Dfa {
+    /// The state before the first byte of a stream, at position 0
+    pub fn start(&self) -> DfaState {
+        DfaState {
+            state: 0,
+            position: 0,
+        }
+    }
+
+    /// Feeds the next byte of the stream
+    ///
+    /// Returns the match that ends with `byte`, if there is one
+    #[inline]
+    pub fn advance(&self, state: &mut DfaState, byte: u8) -> Option<MatchEnd> {
+        let class = self.classes[byte as usize] as usize;
+        state.state = self.transitions[state.state as usize * self.class_count + class];
+        state.position = state.position.wrapping_add(1);
+        self.accepting[state.state as usize].then(|| MatchEnd {
+            start: state.position.wrapping_sub(self.len as u64),
+            end: state.position,
+        })
+    }
+
+    /// Runs the automaton over `haystack`, returning the start of every match
+    pub fn find_match_starts(&self, haystack: &[u8]) -> Vec<usize> {
+        let mut state = self.start();
+        haystack
+            .iter()
+            .filter_map(|&byte| self.advance(&mut state, byte))
+            .map(|found| found.start() as usize)
+            .collect()
+    }
+
+    #[inline(always)]
+    pub fn state_count(&self) -> usize {
+        self.accepting.len()
+    }
+
+    /// Number of columns of the transition table
+    #[inline(always)]
+    pub fn class_count(&self) -> usize {
+        self.class_count
+    }
+}
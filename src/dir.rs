@@ -0,0 +1,191 @@
+use crate::{Match, PatternSet};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::panic::resume_unwind;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// The result of scanning one file, or the error of a file or directory that couldn't be read
+type FileResult = (PathBuf, io::Result<Vec<(usize, Match)>>);
+
+/// Options for [PatternSet::scan_dir]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DirScanOptions {
+    pub(crate) max_file_size: Option<u64>,
+    pub(crate) follow_symlinks: bool,
+    pub(crate) glob: Option<String>,
+    pub(crate) threads: usize,
+}
+
+impl Default for DirScanOptions {
+    fn default() -> DirScanOptions {
+        Self {
+            max_file_size: None,
+            follow_symlinks: false,
+            glob: None,
+            threads: 1,
+        }
+    }
+}
+
+impl DirScanOptions {
+    pub fn new() -> DirScanOptions {
+        Self::default()
+    }
+
+    /// Skips files larger than `max_file_size` bytes, they are not part of the result
+    pub fn max_file_size(mut self, max_file_size: u64) -> DirScanOptions {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+
+    /// Follows symbolic links to files and directories, every directory is still visited only once
+    pub fn follow_symlinks(mut self) -> DirScanOptions {
+        self.follow_symlinks = true;
+        self
+    }
+
+    /// Only scans files whose name matches `glob`, where `*` matches any number of characters and
+    /// `?` a single one, e.g. `*.dll`
+    pub fn glob(mut self, glob: impl Into<String>) -> DirScanOptions {
+        self.glob = Some(glob.into());
+        self
+    }
+
+    /// Scans files on `threads` threads, 0 uses all available ones
+    pub fn threads(mut self, threads: usize) -> DirScanOptions {
+        self.threads = threads;
+        self
+    }
+}
+
+impl PatternSet {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Scans every file below `root` with [PatternSet::find_matches]
+    ///
+    /// The result is sorted by path and contains an entry for every scanned file  
+    /// Errors don't abort the walk: a file that can't be read, e.g. because it vanished, is reported
+    /// with its error, as is a directory that can't be listed
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use binmatch::{DirScanOptions, PatternSet};
+    /// let set: PatternSet = "upx: 55 50 58 21".parse().unwrap();
+    /// let options = DirScanOptions::new().glob("*.exe").max_file_size(64 << 20).threads(0);
+    /// for (path, result) in set.scan_dir("C:\\Program Files".as_ref(), &options) {
+    ///     match result {
+    ///         Ok(matches) if !matches.is_empty() => println!("{}: {}", path.display(), matches.len()),
+    ///         Ok(_) => {}
+    ///         Err(err) => eprintln!("{}: {err}", path.display()),
+    ///     }
+    /// }
+    /// ```
+    pub fn scan_dir(&self, root: &Path, options: &DirScanOptions) -> Vec<FileResult> {
+        let mut files = Vec::new();
+        let mut results = Vec::new();
+        let mut visited = BTreeSet::new();
+        walk(root, options, &mut visited, &mut files, &mut results);
+
+        let threads = match options.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            threads => threads,
+        }
+        .clamp(1, files.len().max(1));
+        let scan = |path: &PathBuf| fs::read(path).map(|data| self.find_matches(&data));
+        if threads == 1 {
+            results.extend(files.iter().map(|path| (path.clone(), scan(path))));
+        } else {
+            thread::scope(|scope| {
+                let workers: Vec<_> = (0..threads)
+                    .map(|first| {
+                        let (files, scan) = (&files, &scan);
+                        scope.spawn(move || {
+                            files
+                                .iter()
+                                .skip(first)
+                                .step_by(threads)
+                                .map(|path| (path.clone(), scan(path)))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                for worker in workers {
+                    results.extend(worker.join().unwrap_or_else(|panic| resume_unwind(panic)));
+                }
+            });
+        }
+        results.sort_by(|(a, _), (b, _)| a.cmp(b));
+        results
+    }
+}
+
+/// Collects the files to scan below `dir`, errors are pushed to `results` directly
+fn walk(
+    dir: &Path,
+    options: &DirScanOptions,
+    visited: &mut BTreeSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+    results: &mut Vec<FileResult>,
+) {
+    // Guards against symlink loops
+    if let Ok(canonical) = dir.canonicalize() {
+        if !visited.insert(canonical) {
+            return;
+        }
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => return results.push((dir.to_path_buf(), Err(err))),
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(entry) => paths.push(entry.path()),
+            Err(err) => results.push((dir.to_path_buf(), Err(err))),
+        }
+    }
+    paths.sort();
+
+    for path in paths {
+        let metadata = match options.follow_symlinks {
+            true => fs::metadata(&path),
+            false => fs::symlink_metadata(&path),
+        };
+        match metadata {
+            Ok(metadata) if metadata.is_dir() => walk(&path, options, visited, files, results),
+            Ok(metadata) if metadata.is_file() => {
+                let too_large = options
+                    .max_file_size
+                    .is_some_and(|max_file_size| metadata.len() > max_file_size);
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let wanted = options
+                    .glob
+                    .as_deref()
+                    .is_none_or(|glob| glob_matches(glob.as_bytes(), name.as_bytes()));
+                if wanted && !too_large {
+                    files.push(path);
+                }
+            }
+            // Symlinks that aren't followed, sockets, devices, ...
+            Ok(_) => {}
+            Err(err) => results.push((path, Err(err))),
+        }
+    }
+}
+
+/// Matches `name` against a glob of `*` and `?` wildcards
+fn glob_matches(glob: &[u8], name: &[u8]) -> bool {
+    match (glob.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob_matches(&glob[1..], name) || (!name.is_empty() && glob_matches(glob, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_matches(&glob[1..], &name[1..]),
+        (Some(expected), Some(actual)) if expected == actual => {
+            glob_matches(&glob[1..], &name[1..])
+        }
+        _ => false,
+    }
+}
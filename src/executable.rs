@@ -0,0 +1,368 @@
+use crate::{Match, MatchOptions, Pattern, String, Vec};
+
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHT_NOBITS: u32 = 8;
+
+const IMAGE_SCN_CNT_CODE: u32 = 0x20;
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+/// The container format detected by [Pattern::find_in_executable]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum ExecutableFormat {
+    Elf,
+    Pe,
+    /// Not a well-formed ELF or PE file, the data was scanned as a whole
+    #[default]
+    Raw,
+}
+
+/// A section of an executable, as passed to the filter of [Pattern::find_in_executable_with]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Section {
+    name: String,
+    file_offset: usize,
+    file_size: usize,
+    virtual_address: u64,
+    executable: bool,
+    alloc: bool,
+}
+
+impl Section {
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Offset of the section data inside the file
+    #[inline(always)]
+    pub fn file_offset(&self) -> usize {
+        self.file_offset
+    }
+
+    /// Number of bytes stored in the file, 0 for sections without file data like `.bss`
+    #[inline(always)]
+    pub fn file_size(&self) -> usize {
+        self.file_size
+    }
+
+    /// The address the section is mapped at, including the image base of PE files
+    #[inline(always)]
+    pub fn virtual_address(&self) -> u64 {
+        self.virtual_address
+    }
+
+    /// Whether the section contains code
+    #[inline(always)]
+    pub fn is_executable(&self) -> bool {
+        self.executable
+    }
+
+    /// Whether the section is mapped into memory, always the case for PE sections
+    #[inline(always)]
+    pub fn is_alloc(&self) -> bool {
+        self.alloc
+    }
+}
+
+/// A match inside a section of an executable, see [Pattern::find_in_executable]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct SectionMatch {
+    section: String,
+    virtual_address: Option<u64>,
+    matched: Match,
+}
+
+impl SectionMatch {
+    /// Name of the section containing the match, empty after a raw scan
+    #[inline(always)]
+    pub fn section(&self) -> &str {
+        &self.section
+    }
+
+    /// Offset of the match inside the file
+    #[inline(always)]
+    pub fn file_offset(&self) -> usize {
+        self.matched.start()
+    }
+
+    /// The address of the match once mapped  
+    /// `None` for sections that aren't mapped and after a raw scan
+    #[inline(always)]
+    pub fn virtual_address(&self) -> Option<u64> {
+        self.virtual_address
+    }
+
+    /// The match with offsets into the whole file
+    #[inline(always)]
+    pub fn as_match(&self) -> &Match {
+        &self.matched
+    }
+}
+
+/// The result of [Pattern::find_in_executable]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct ExecutableScan {
+    format: ExecutableFormat,
+    matches: Vec<SectionMatch>,
+}
+
+impl ExecutableScan {
+    #[inline(always)]
+    pub fn format(&self) -> ExecutableFormat {
+        self.format
+    }
+
+    /// Whether the data wasn't a well-formed executable and was scanned as a whole instead
+    #[inline(always)]
+    pub fn is_fallback(&self) -> bool {
+        self.format == ExecutableFormat::Raw
+    }
+
+    /// The matches ordered by section, then by offset
+    #[inline(always)]
+    pub fn matches(&self) -> &[SectionMatch] {
+        &self.matches
+    }
+}
+
+impl Pattern {
+    /// Scans only the executable sections of an ELF or PE file, reporting file offsets and virtual
+    /// addresses, see [Pattern::find_in_executable_with] for choosing other sections
+    ///
+    /// Data that isn't a well-formed ELF or PE file is scanned as a whole, which is flagged by
+    /// [ExecutableScan::is_fallback]  
+    /// Mach-O files are not supported and are scanned as a whole as well
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{ExecutableFormat, Pattern};
+    /// let pattern = Pattern::new("C3").unwrap();
+    /// let scan = pattern.find_in_executable(&[0x90, 0xC3]);
+    /// assert_eq!(scan.format(), ExecutableFormat::Raw);
+    /// assert_eq!(scan.matches()[0].file_offset(), 1);
+    /// ```
+    pub fn find_in_executable(&self, data: &[u8]) -> ExecutableScan {
+        self.find_in_executable_with(data, Section::is_executable)
+    }
+
+    /// Same as [Pattern::find_in_executable], but scans the sections for which `filter` returns `true`
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use binmatch::Pattern;
+    /// # let pattern = Pattern::new("C3").unwrap();
+    /// # let data = vec![];
+    /// // Code and data, but no debug info
+    /// let scan = pattern.find_in_executable_with(&data, |section| section.is_alloc());
+    /// ```
+    pub fn find_in_executable_with(
+        &self,
+        data: &[u8],
+        filter: impl Fn(&Section) -> bool,
+    ) -> ExecutableScan {
+        let Some((format, sections)) = parse_elf(data).or_else(|| parse_pe(data)) else {
+            return ExecutableScan {
+                format: ExecutableFormat::Raw,
+                matches: self
+                    .find_with(data, &MatchOptions::new())
+                    .into_iter()
+                    .map(|matched| SectionMatch {
+                        matched,
+                        ..Default::default()
+                    })
+                    .collect(),
+            };
+        };
+        let mut matches = Vec::new();
+        for section in sections.iter().filter(|section| filter(section)) {
+            let start = section.file_offset;
+            let contents = &data[start..start + section.file_size];
+            for m in self.find_with(contents, &MatchOptions::new()) {
+                let virtual_address = section
+                    .alloc
+                    .then(|| section.virtual_address.wrapping_add(m.start() as u64));
+                matches.push(SectionMatch {
+                    section: section.name.clone(),
+                    virtual_address,
+                    matched: m.shifted(start),
+                });
+            }
+        }
+        ExecutableScan { format, matches }
+    }
+}
+
+/// Reads integers of either byte order, `None` past the end of the data
+struct Bytes<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl Bytes<'_> {
+    fn array<const N: usize>(&self, offset: usize) -> Option<[u8; N]> {
+        let mut bytes: [u8; N] = self
+            .data
+            .get(offset..offset.checked_add(N)?)?
+            .try_into()
+            .ok()?;
+        if self.big_endian {
+            bytes.reverse();
+        }
+        Some(bytes)
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        self.array(offset).map(u16::from_le_bytes)
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        self.array(offset).map(u32::from_le_bytes)
+    }
+
+    fn u64(&self, offset: usize) -> Option<u64> {
+        self.array(offset).map(u64::from_le_bytes)
+    }
+
+    /// A 32 or 64 bit word converted to a u64
+    fn word(&self, offset: usize, wide: bool) -> Option<u64> {
+        match wide {
+            true => self.u64(offset),
+            false => self.u32(offset).map(u64::from),
+        }
+    }
+
+    /// Checks that `len` bytes at `offset` are part of the data
+    fn range(&self, offset: u64, len: u64) -> Option<(usize, usize)> {
+        let offset = usize::try_from(offset).ok()?;
+        let len = usize::try_from(len).ok()?;
+        (offset.checked_add(len)? <= self.data.len()).then_some((offset, len))
+    }
+
+    /// The NUL terminated string at `offset`
+    fn c_str(&self, offset: usize) -> Option<String> {
+        let bytes = self.data.get(offset..)?;
+        let end = bytes.iter().position(|byte| *byte == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+}
+
+fn parse_elf(data: &[u8]) -> Option<(ExecutableFormat, Vec<Section>)> {
+    if data.get(..4)? != b"\x7FELF" {
+        return None;
+    }
+    let wide = match data.get(4)? {
+        1 => false,
+        2 => true,
+        _ => return None,
+    };
+    let big_endian = match data.get(5)? {
+        1 => false,
+        2 => true,
+        _ => return None,
+    };
+    let bytes = Bytes { data, big_endian };
+    let (shoff, shentsize, shnum, shstrndx) = match wide {
+        true => (
+            bytes.u64(0x28)?,
+            bytes.u16(0x3A)?,
+            bytes.u16(0x3C)?,
+            bytes.u16(0x3E)?,
+        ),
+        false => (
+            bytes.u32(0x20)?.into(),
+            bytes.u16(0x2E)?,
+            bytes.u16(0x30)?,
+            bytes.u16(0x32)?,
+        ),
+    };
+    if shentsize < if wide { 0x40 } else { 0x28 } {
+        return None;
+    }
+    let (shoff, _) = bytes.range(shoff, u64::from(shentsize) * u64::from(shnum))?;
+
+    // (name offset, type, flags, address, file offset, size) of every section header
+    let headers = (0..usize::from(shnum))
+        .map(|index| {
+            let header = shoff + index * usize::from(shentsize);
+            let word = |offset_32: usize, offset_64: usize| match wide {
+                true => bytes.word(header + offset_64, true),
+                false => bytes.word(header + offset_32, false),
+            };
+            Some((
+                bytes.u32(header)?,
+                bytes.u32(header + 4)?,
+                word(0x08, 0x08)?,
+                word(0x0C, 0x10)?,
+                word(0x10, 0x18)?,
+                word(0x14, 0x20)?,
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let (_, _, _, _, names_offset, names_size) = *headers.get(usize::from(shstrndx))?;
+    let (names_offset, _) = bytes.range(names_offset, names_size)?;
+
+    let mut sections = Vec::new();
+    for (name, kind, flags, address, offset, size) in headers.into_iter().skip(1) {
+        let file_size = if kind == SHT_NOBITS { 0 } else { size };
+        let (file_offset, file_size) = bytes.range(offset, file_size)?;
+        sections.push(Section {
+            name: bytes.c_str(names_offset + usize::try_from(name).ok()?)?,
+            file_offset,
+            file_size,
+            virtual_address: address,
+            executable: flags & SHF_EXECINSTR != 0,
+            alloc: flags & SHF_ALLOC != 0,
+        });
+    }
+    Some((ExecutableFormat::Elf, sections))
+}
+
+fn parse_pe(data: &[u8]) -> Option<(ExecutableFormat, Vec<Section>)> {
+    if data.get(..2)? != b"MZ" {
+        return None;
+    }
+    let bytes = Bytes {
+        data,
+        big_endian: false,
+    };
+    let pe = usize::try_from(bytes.u32(0x3C)?).ok()?;
+    if data.get(pe..pe.checked_add(4)?)? != b"PE\0\0" {
+        return None;
+    }
+    let section_count = usize::from(bytes.u16(pe + 6)?);
+    let optional_size = usize::from(bytes.u16(pe + 20)?);
+    let optional = pe + 24;
+    let image_base = match bytes.u16(optional)? {
+        0x10B => bytes.u32(optional + 28)?.into(),
+        0x20B => bytes.u64(optional + 24)?,
+        _ => return None,
+    };
+
+    let table = optional + optional_size;
+    let mut sections = Vec::new();
+    for index in 0..section_count {
+        let header = table + index * 40;
+        let name = data.get(header..header + 8)?;
+        let name = &name[..name.iter().position(|byte| *byte == 0).unwrap_or(8)];
+        let virtual_size = bytes.u32(header + 8)?;
+        let raw_size = bytes.u32(header + 16)?;
+        // The raw data is padded to the file alignment, the virtual size is exact
+        let file_size = match virtual_size {
+            0 => raw_size,
+            _ => raw_size.min(virtual_size),
+        };
+        let (file_offset, file_size) =
+            bytes.range(bytes.u32(header + 20)?.into(), file_size.into())?;
+        let characteristics = bytes.u32(header + 36)?;
+        sections.push(Section {
+            name: String::from_utf8_lossy(name).into_owned(),
+            file_offset,
+            file_size,
+            virtual_address: image_base.wrapping_add(bytes.u32(header + 12)?.into()),
+            executable: characteristics & (IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_CNT_CODE) != 0,
+            alloc: true,
+        });
+    }
+    Some((ExecutableFormat::Pe, sections))
+}
@@ -0,0 +1,93 @@
+use crate::{Match, Pattern};
+use core::fmt;
+
+/// The result of [Pattern::explain_at], telling why the Pattern did or didn't match at an offset
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MatchExplanation {
+    /// The Pattern matches, with the same [Match] that [Pattern::match_at] returns
+    Matched(Match),
+    /// The literal at `pattern_index` expected `expected` but the haystack contains `found`
+    Mismatch {
+        pattern_index: usize,
+        expected: u8,
+        found: u8,
+    },
+    /// The Pattern needs `needed` bytes starting at the offset, but the haystack only has `available`
+    OutOfBounds { needed: usize, available: usize },
+}
+
+impl MatchExplanation {
+    #[inline(always)]
+    pub fn is_match(&self) -> bool {
+        matches!(self, MatchExplanation::Matched(_))
+    }
+}
+
+impl fmt::Display for MatchExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchExplanation::Matched(m) => {
+                write!(f, "match at {:#x}, captures: [", m.start())?;
+                for (i, capture) in m.captures().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(
+                        f,
+                        "{:#04x} at {:#x}",
+                        capture.value, capture.haystack_offset
+                    )?;
+                }
+                write!(f, "]")
+            }
+            MatchExplanation::Mismatch {
+                pattern_index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "mismatch at +{}: expected {:#04X}, found {:#04X}",
+                pattern_index, expected, found
+            ),
+            MatchExplanation::OutOfBounds { needed, available } => write!(
+                f,
+                "pattern needs {} bytes but only {} are available",
+                needed, available
+            ),
+        }
+    }
+}
+
+impl Pattern {
+    /// Explains why the Pattern does or doesn't match at `offset`, for debugging signatures
+    ///
+    /// Unlike [Pattern::match_at] the reason for a failure is kept: the first mismatching element,
+    /// what it expected and what was found instead
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("48 8B ?? 8B").unwrap();
+    /// let data = vec![0x48, 0x8B, 0x05, 0x89];
+    /// let explanation = pattern.explain_at(&data, 0);
+    /// assert!(!explanation.is_match());
+    /// assert_eq!(explanation.to_string(), "mismatch at +3: expected 0x8B, found 0x89");
+    /// ```
+    pub fn explain_at(&self, haystack: &[u8], offset: usize) -> MatchExplanation {
+        let available = haystack.len().saturating_sub(offset);
+        if available < self.len {
+            return MatchExplanation::OutOfBounds {
+                needed: self.len,
+                available,
+            };
+        }
+        match self.match_or_mismatch(haystack, offset) {
+            Ok(m) => MatchExplanation::Matched(m),
+            Err((pattern_index, expected, found)) => MatchExplanation::Mismatch {
+                pattern_index,
+                expected,
+                found,
+            },
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{BinmatchError, Pattern, PatternElement, String, Vec};
+use core::fmt::Write;
+
+impl Pattern {
+    /// Writes the Pattern in the style of IDA signatures, e.g. `E8 ? ? ? ? 90`
+    ///
+    /// IDA only knows wildcards, so placeholders and ignored bytes are both written as `?` and
+    /// [Pattern::from_ida_string] reads them back as placeholders
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("E8 ?? ?? ?? ?? 90").unwrap();
+    /// assert_eq!(pattern.to_ida_string(), "E8 ? ? ? ? 90");
+    /// ```
+    pub fn to_ida_string(&self) -> String {
+        let mut out = String::new();
+        for (index, element) in self.data.iter().enumerate() {
+            if index > 0 {
+                out.push(' ');
+            }
+            match element {
+                PatternElement::Literal(byte) => {
+                    let _ = write!(out, "{byte:02X}");
+                }
+                PatternElement::Placeholder | PatternElement::Ignore => out.push('?'),
+            }
+        }
+        out
+    }
+
+    /// Parses an IDA style signature, where `?` and `??` are wildcards, e.g. `E8 ? ? ? ? 90`
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - A byte isn't two hex digits or a wildcard, the error holds the first invalid character
+    pub fn from_ida_string(signature: &str) -> Result<Pattern, BinmatchError> {
+        let data = signature
+            .split_whitespace()
+            .map(|token| match token {
+                "?" | "??" => Ok(PatternElement::Placeholder),
+                // The tokens are slices of `signature`, so their offset is the distance between them
+                token => {
+                    parse_hex_byte(token, token.as_ptr() as usize - signature.as_ptr() as usize)
+                        .map(PatternElement::Literal)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Pattern::from_elements(data))
+    }
+
+    /// Writes the Pattern as a byte string and mask, as used by code scanning for signatures,
+    /// e.g. `("\xE8\x00\x00\x00\x00\x90", "x????x")`
+    ///
+    /// Wildcards are written as `\x00` in the bytes and `?` in the mask, placeholders and ignored
+    /// bytes alike, [Pattern::from_code_style] reads them back as placeholders
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("E8 ?? __ 90").unwrap();
+    /// let (bytes, mask) = pattern.to_code_style();
+    /// assert_eq!(bytes, r"\xE8\x00\x00\x90");
+    /// assert_eq!(mask, "x??x");
+    /// ```
+    pub fn to_code_style(&self) -> (String, String) {
+        let mut bytes = String::new();
+        let mut mask = String::new();
+        for element in &self.data {
+            let (byte, kind) = match element {
+                PatternElement::Literal(byte) => (*byte, 'x'),
+                PatternElement::Placeholder | PatternElement::Ignore => (0, '?'),
+            };
+            let _ = write!(bytes, "\\x{byte:02X}");
+            mask.push(kind);
+        }
+        (bytes, mask)
+    }
+
+    /// Parses a byte string of `\xNN` escapes and a mask of `x` for literals and `?` for wildcards,
+    /// see [Pattern::to_code_style]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The bytes contain anything but `\xNN` escapes or the mask anything but `x` and `?`, the
+    ///   error holds the first invalid character
+    /// - The bytes and the mask differ in length, the error expects the number of bytes
+    pub fn from_code_style(bytes: &str, mask: &str) -> Result<Pattern, BinmatchError> {
+        let mut parsed = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let position = bytes.len() - rest.len();
+            let escape = rest
+                .strip_prefix("\\x")
+                .ok_or(BinmatchError::PatternParseError {
+                    ch: rest.chars().next().unwrap_or('\\'),
+                    position,
+                })?;
+            let digits = escape.get(..2).unwrap_or(escape);
+            parsed.push(parse_hex_byte(digits, position + 2)?);
+            rest = &escape[digits.len()..];
+        }
+        if parsed.len() != mask.chars().count() {
+            return Err(BinmatchError::LengthMismatchError {
+                expected: parsed.len(),
+                found: mask.chars().count(),
+            });
+        }
+        let data = parsed
+            .into_iter()
+            .zip(mask.char_indices())
+            .map(|(byte, (position, kind))| match kind {
+                'x' => Ok(PatternElement::Literal(byte)),
+                '?' => Ok(PatternElement::Placeholder),
+                ch => Err(BinmatchError::PatternParseError { ch, position }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Pattern::from_elements(data))
+    }
+
+    /// Writes the Pattern as a YARA hex string, e.g. `{ E8 ?? ?? ?? ?? 90 }`
+    ///
+    /// Placeholders and ignored bytes are both written as `??`, [Pattern::from_yara_hex] reads them
+    /// back as placeholders
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("E8 ?? __ 90").unwrap();
+    /// assert_eq!(pattern.to_yara_hex(), "{ E8 ?? ?? 90 }");
+    /// ```
+    pub fn to_yara_hex(&self) -> String {
+        let mut out = String::from("{ ");
+        for element in &self.data {
+            match element {
+                PatternElement::Literal(byte) => {
+                    let _ = write!(out, "{byte:02X} ");
+                }
+                PatternElement::Placeholder | PatternElement::Ignore => out.push_str("?? "),
+            }
+        }
+        out.push('}');
+        out
+    }
+
+    /// Parses a YARA hex string made of bytes and `??` wildcards, e.g. `{ E8 ?? ?? ?? ?? 90 }`
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The braces are missing
+    /// - The string uses nibble wildcards, jumps or alternatives, which have no Pattern equivalent,
+    ///   or any other invalid character, the error holds the first one
+    /// - A byte is missing its second digit
+    pub fn from_yara_hex(hex: &str) -> Result<Pattern, BinmatchError> {
+        let start = hex.len() - hex.trim_start().len();
+        let trimmed = hex.trim();
+        let last = trimmed.chars().last().unwrap_or('}');
+        let inner = trimmed
+            .strip_prefix('{')
+            .ok_or(BinmatchError::PatternParseError {
+                ch: trimmed.chars().next().unwrap_or('{'),
+                position: start,
+            })?
+            .strip_suffix('}')
+            .ok_or(BinmatchError::PatternParseError {
+                ch: last,
+                position: start + trimmed.len().saturating_sub(last.len_utf8()),
+            })?;
+        let digits: Vec<(usize, char)> = inner
+            .char_indices()
+            .filter(|(_, c)| !c.is_whitespace())
+            .map(|(position, c)| (start + 1 + position, c))
+            .collect();
+        if let Some(&(position, ch)) = digits
+            .iter()
+            .find(|(_, c)| !c.is_ascii_hexdigit() && *c != '?')
+        {
+            return Err(BinmatchError::PatternParseError { ch, position });
+        }
+        if !digits.len().is_multiple_of(2) {
+            return Err(BinmatchError::PatternLengthError { len: digits.len() });
+        }
+        let data = digits
+            .chunks(2)
+            .map(|pair| match pair {
+                [(_, '?'), (_, '?')] => Ok(PatternElement::Placeholder),
+                [(position, '?'), _] | [_, (position, '?')] => {
+                    Err(BinmatchError::PatternParseError {
+                        ch: '?',
+                        position: *position,
+                    })
+                }
+                [(position, high), (_, low)] => {
+                    parse_hex_byte(&[*high, *low].iter().collect::<String>(), *position)
+                        .map(PatternElement::Literal)
+                }
+                _ => unreachable!("The digits were checked to come in pairs"),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Pattern::from_elements(data))
+    }
+}
+
+/// Parses exactly two hex digits found at `position` of the input, the error holds the first
+/// invalid character
+fn parse_hex_byte(digits: &str, position: usize) -> Result<u8, BinmatchError> {
+    if let Some((offset, ch)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(BinmatchError::PatternParseError {
+            ch,
+            position: position + offset,
+        });
+    }
+    u8::from_str_radix(digits, 16)
+        .ok()
+        .filter(|_| digits.len() == 2)
+        .ok_or(BinmatchError::PatternLengthError {
+            len: digits.chars().count(),
+        })
+}
@@ -0,0 +1,208 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{Match, MatchOptions, Pattern, String, Vec};
+
+/// How a field is decoded, starting at the pattern index of its named capture
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum FieldKind {
+    U8,
+    U16Le,
+    U16Be,
+    U32Le,
+    U32Be,
+    U64Le,
+    U64Be,
+}
+
+impl FieldKind {
+    /// Number of bytes the field covers
+    pub fn width(&self) -> usize {
+        match self {
+            FieldKind::U8 => 1,
+            FieldKind::U16Le | FieldKind::U16Be => 2,
+            FieldKind::U32Le | FieldKind::U32Be => 4,
+            FieldKind::U64Le | FieldKind::U64Be => 8,
+        }
+    }
+
+    fn read(&self, m: &Match, haystack: &[u8], pattern_index: usize) -> Option<u64> {
+        Some(match self {
+            FieldKind::U8 => m.byte_at(haystack, pattern_index)? as u64,
+            FieldKind::U16Le => m.read_u16_le(haystack, pattern_index)? as u64,
+            FieldKind::U16Be => m.read_u16_be(haystack, pattern_index)? as u64,
+            FieldKind::U32Le => m.read_u32_le(haystack, pattern_index)? as u64,
+            FieldKind::U32Be => m.read_u32_be(haystack, pattern_index)? as u64,
+            FieldKind::U64Le => m.read_u64_le(haystack, pattern_index)?,
+            FieldKind::U64Be => m.read_u64_be(haystack, pattern_index)?,
+        })
+    }
+}
+
+/// Why a single field could not be extracted
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ExtractError {
+    #[cfg_attr(feature = "std", error("The Pattern has no capture named {field}"))]
+    MissingCapture { field: String },
+    #[cfg_attr(
+        feature = "std",
+        error("The field {field} needs {width} bytes but runs past the end of the match")
+    )]
+    OutOfBounds { field: String, width: usize },
+}
+
+/// Decodes named captures of a [Match] into integer fields
+///
+/// Each field starts at the pattern index of the capture with the same name (see [Pattern::with_capture_names])
+/// and reads as many bytes as its [FieldKind] needs, which don't have to be placeholders
+///
+/// # Example:
+/// ```
+/// # use binmatch::{Extractor, Pattern};
+/// struct Record {
+///     len: u8,
+///     value: u32,
+/// }
+///
+/// // A TLV stream where tag 0x01 is followed by a length and a little endian u32
+/// let pattern = Pattern::new("01 ?? ?? ?? ?? ??").unwrap().with_capture_names(&["len", "value"]);
+/// let stream = vec![0x01, 0x04, 0x78, 0x56, 0x34, 0x12, 0x02, 0x00, 0x01, 0x04, 0x01, 0x00, 0x00, 0x00];
+/// let extractor = Extractor::new().field_u8("len").field_u32_le("value");
+/// let records: Vec<Record> = extractor
+///     .extract_all(&pattern, &stream)
+///     .unwrap()
+///     .iter()
+///     .map(|fields| Record {
+///         len: fields.u8("len").unwrap(),
+///         value: fields.u32("value").unwrap(),
+///     })
+///     .collect();
+/// assert_eq!(records.len(), 2);
+/// assert_eq!((records[0].len, records[0].value), (4, 0x12345678));
+/// assert_eq!(records[1].value, 1);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Extractor {
+    fields: Vec<(String, FieldKind)>,
+}
+
+impl Extractor {
+    pub fn new() -> Extractor {
+        Self::default()
+    }
+
+    /// Adds a field read from the capture named `name`
+    pub fn field(mut self, name: &str, kind: FieldKind) -> Extractor {
+        self.fields.push((String::from(name), kind));
+        self
+    }
+
+    pub fn field_u8(self, name: &str) -> Extractor {
+        self.field(name, FieldKind::U8)
+    }
+
+    pub fn field_u16_le(self, name: &str) -> Extractor {
+        self.field(name, FieldKind::U16Le)
+    }
+
+    pub fn field_u16_be(self, name: &str) -> Extractor {
+        self.field(name, FieldKind::U16Be)
+    }
+
+    pub fn field_u32_le(self, name: &str) -> Extractor {
+        self.field(name, FieldKind::U32Le)
+    }
+
+    pub fn field_u32_be(self, name: &str) -> Extractor {
+        self.field(name, FieldKind::U32Be)
+    }
+
+    pub fn field_u64_le(self, name: &str) -> Extractor {
+        self.field(name, FieldKind::U64Le)
+    }
+
+    pub fn field_u64_be(self, name: &str) -> Extractor {
+        self.field(name, FieldKind::U64Be)
+    }
+
+    /// Decodes every field from a single match, `haystack` has to be the searched haystack
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - A field has no capture with its name, or would run past the end of the match  
+    ///   Every failing field is reported, not just the first one
+    pub fn extract(&self, m: &Match, haystack: &[u8]) -> Result<Fields, Vec<ExtractError>> {
+        let mut values = Vec::with_capacity(self.fields.len());
+        let mut errors = Vec::new();
+        for (name, kind) in &self.fields {
+            let Some(capture) = m.captures().name(name) else {
+                errors.push(ExtractError::MissingCapture {
+                    field: name.clone(),
+                });
+                continue;
+            };
+            match kind.read(m, haystack, capture.pattern_index) {
+                Some(value) => values.push((name.clone(), value)),
+                None => errors.push(ExtractError::OutOfBounds {
+                    field: name.clone(),
+                    width: kind.width(),
+                }),
+            }
+        }
+        if errors.is_empty() {
+            Ok(Fields { values })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Finds all matches of `pattern` and decodes each of them, see [Extractor::extract]
+    pub fn extract_all(
+        &self,
+        pattern: &Pattern,
+        haystack: &[u8],
+    ) -> Result<Vec<Fields>, Vec<ExtractError>> {
+        pattern
+            .find_with(haystack, &MatchOptions::default())
+            .iter()
+            .map(|m| self.extract(m, haystack))
+            .collect()
+    }
+}
+
+/// The decoded fields of one match, returned by [Extractor::extract]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Fields {
+    values: Vec<(String, u64)>,
+}
+
+impl Fields {
+    /// The value of the field `name`, widened to a `u64`
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.values
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// The value of the field `name`, `None` if it doesn't exist or doesn't fit into a `u8`
+    pub fn u8(&self, name: &str) -> Option<u8> {
+        self.get(name)?.try_into().ok()
+    }
+
+    /// The value of the field `name`, `None` if it doesn't exist or doesn't fit into a `u16`
+    pub fn u16(&self, name: &str) -> Option<u16> {
+        self.get(name)?.try_into().ok()
+    }
+
+    /// The value of the field `name`, `None` if it doesn't exist or doesn't fit into a `u32`
+    pub fn u32(&self, name: &str) -> Option<u32> {
+        self.get(name)?.try_into().ok()
+    }
+
+    /// Same as [Fields::get]
+    pub fn u64(&self, name: &str) -> Option<u64> {
+        self.get(name)
+    }
+}
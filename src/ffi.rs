@@ -0,0 +1,184 @@
+//! C interface of binmatch, built with the `ffi` feature
+//!
+//! The declarations are in `include/binmatch.h`, a shared library is built with
+//! `cargo rustc --release --features ffi --crate-type cdylib`
+//!
+//! Every function returns a [BinmatchStatus] code, accepts null pointers without crashing and
+//! never unwinds into the caller: a panic is reported as [BinmatchStatus::Panic]
+
+use crate::{BinmatchError, MatchOptions, Pattern};
+use std::ffi::{c_char, c_int, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// The status codes returned by the C functions, the positive ones mirror [BinmatchError]
+#[repr(C)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum BinmatchStatus {
+    Ok = 0,
+    /// A required pointer was null
+    NullPointer = -1,
+    /// The function panicked, the out parameters are unchanged
+    Panic = -2,
+    /// The pattern string is not valid UTF-8
+    InvalidUtf8 = -3,
+    PatternParseError = 1,
+    PatternLengthError = 2,
+    StrideError = 3,
+    CaptureBufferError = 4,
+    ReplacementLengthError = 5,
+    PatchArrowError = 6,
+    LengthMismatchError = 7,
+    MixedToken = 8,
+}
+
+impl From<&BinmatchError> for BinmatchStatus {
+    fn from(err: &BinmatchError) -> BinmatchStatus {
+        match err {
+            BinmatchError::PatternParseError { .. } => BinmatchStatus::PatternParseError,
+            BinmatchError::PatternLengthError { .. } => BinmatchStatus::PatternLengthError,
+            BinmatchError::StrideError => BinmatchStatus::StrideError,
+            BinmatchError::CaptureBufferError { .. } => BinmatchStatus::CaptureBufferError,
+            BinmatchError::ReplacementLengthError { .. } => BinmatchStatus::ReplacementLengthError,
+            BinmatchError::PatchArrowError => BinmatchStatus::PatchArrowError,
+            BinmatchError::LengthMismatchError { .. } => BinmatchStatus::LengthMismatchError,
+            BinmatchError::MixedToken { .. } => BinmatchStatus::MixedToken,
+        }
+    }
+}
+
+/// A parsed [Pattern], opaque to C
+#[derive(Debug)]
+pub struct BinmatchPattern(Pattern);
+
+/// A match as seen from C, owned by the array returned by [binmatch_find]
+#[repr(C)]
+#[derive(Debug)]
+pub struct BinmatchMatch {
+    pub start: usize,
+    pub len: usize,
+    /// The values captured by the placeholders in pattern order, null if there are none
+    pub captures: *mut u8,
+    pub capture_count: usize,
+}
+
+/// Runs `f`, turning a panic into [BinmatchStatus::Panic]
+fn guarded(f: impl FnOnce() -> BinmatchStatus) -> c_int {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(BinmatchStatus::Panic) as c_int
+}
+
+/// Parses the NUL terminated `pattern` and stores the result in `*out`, which has to be freed with
+/// [binmatch_pattern_free]
+///
+/// `*out` is only written on success
+///
+/// # Safety
+///
+/// `pattern` has to be null or a NUL terminated string, `out` null or valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn binmatch_pattern_new(
+    pattern: *const c_char,
+    out: *mut *mut BinmatchPattern,
+) -> c_int {
+    guarded(|| {
+        if pattern.is_null() || out.is_null() {
+            return BinmatchStatus::NullPointer;
+        }
+        let Ok(pattern) = CStr::from_ptr(pattern).to_str() else {
+            return BinmatchStatus::InvalidUtf8;
+        };
+        match Pattern::try_new(pattern) {
+            Ok(pattern) => {
+                *out = Box::into_raw(Box::new(BinmatchPattern(pattern)));
+                BinmatchStatus::Ok
+            }
+            Err(err) => BinmatchStatus::from(&err),
+        }
+    })
+}
+
+/// Frees a Pattern created by [binmatch_pattern_new], null is ignored
+///
+/// # Safety
+///
+/// `pattern` has to be null or a Pattern that wasn't freed yet
+#[no_mangle]
+pub unsafe extern "C" fn binmatch_pattern_free(pattern: *mut BinmatchPattern) {
+    if !pattern.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(pattern))));
+    }
+}
+
+/// Finds every match of `pattern` in the `len` bytes at `data`, see [Pattern::find_with]
+///
+/// `*out` receives an array of `*count` matches that has to be freed with [binmatch_matches_free],
+/// it is null if nothing matched  
+/// `data` may be null if `len` is 0
+///
+/// # Safety
+///
+/// `pattern` has to be null or a live Pattern, `data` valid for `len` bytes and `out` and `count`
+/// null or valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn binmatch_find(
+    pattern: *const BinmatchPattern,
+    data: *const u8,
+    len: usize,
+    out: *mut *mut BinmatchMatch,
+    count: *mut usize,
+) -> c_int {
+    guarded(|| {
+        if pattern.is_null() || out.is_null() || count.is_null() || (data.is_null() && len > 0) {
+            return BinmatchStatus::NullPointer;
+        }
+        let haystack = match data.is_null() {
+            true => &[],
+            false => std::slice::from_raw_parts(data, len),
+        };
+        let matches: Box<[BinmatchMatch]> = (*pattern)
+            .0
+            .find_with(haystack, &MatchOptions::new())
+            .iter()
+            .map(|m| {
+                let captures: Box<[u8]> = m.captures().iter().map(|c| c.value).collect();
+                BinmatchMatch {
+                    start: m.start(),
+                    len: m.len(),
+                    capture_count: captures.len(),
+                    captures: match captures.is_empty() {
+                        true => ptr::null_mut(),
+                        false => Box::into_raw(captures).cast(),
+                    },
+                }
+            })
+            .collect();
+        *count = matches.len();
+        *out = match matches.is_empty() {
+            true => ptr::null_mut(),
+            false => Box::into_raw(matches).cast(),
+        };
+        BinmatchStatus::Ok
+    })
+}
+
+/// Frees the `count` matches returned by [binmatch_find], null is ignored
+///
+/// # Safety
+///
+/// `matches` has to be null or an array returned by [binmatch_find] that wasn't freed yet, `count`
+/// its length
+#[no_mangle]
+pub unsafe extern "C" fn binmatch_matches_free(matches: *mut BinmatchMatch, count: usize) {
+    if matches.is_null() {
+        return;
+    }
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let matches = Box::from_raw(ptr::slice_from_raw_parts_mut(matches, count));
+        for m in matches.iter().filter(|m| !m.captures.is_null()) {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                m.captures,
+                m.capture_count,
+            )));
+        }
+    }));
+}
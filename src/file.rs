@@ -0,0 +1,492 @@
+use crate::patch::RevertAction;
+use crate::StreamMatcher;
+use crate::{
+    FileMatch, PatchOptions, PatchRecord, PatchReport, PatchTemplate, Pattern, RevertError,
+};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Number of bytes read at once when scanning a stream
+pub(crate) const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The payload of the `io::Error` returned by [Pattern::patch_file] when a write fails after
+/// other matches were already patched
+///
+/// # Example:
+/// ```no_run
+/// # use binmatch::{PartialPatchError, PatchOptions, PatchTemplate, Pattern};
+/// # let pattern = Pattern::new("74 ??").unwrap();
+/// # let template = PatchTemplate::new(&pattern, "EB __").unwrap();
+/// if let Err(err) = pattern.patch_file("firmware.bin".as_ref(), &template, &PatchOptions::new()) {
+///     if let Some(partial) = err.get_ref().and_then(|e| e.downcast_ref::<PartialPatchError>()) {
+///         eprintln!("{} matches were already patched", partial.report.len());
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PartialPatchError {
+    /// The matches that were completely written before the failure, including the failed one if
+    /// only its [hook](PatchOptions::after_patch_file) failed
+    pub report: PatchReport,
+    /// The offset of the match whose write or hook failed
+    pub offset: u64,
+    pub source: io::Error,
+}
+
+impl fmt::Display for PartialPatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Patching the match at {:#x} failed after {} matches were patched: {}",
+            self.offset,
+            self.report.len(),
+            self.source
+        )
+    }
+}
+
+impl std::error::Error for PartialPatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// The payload of the `io::Error` returned by [Pattern::find_in_reader] when reading fails
+///
+/// # Example:
+/// ```no_run
+/// # use binmatch::{PartialReadError, Pattern};
+/// # let pattern = Pattern::new("4D 5A").unwrap();
+/// let disk = std::fs::File::open("/dev/sdb").unwrap();
+/// if let Err(err) = pattern.find_in_reader(disk) {
+///     if let Some(partial) = err.get_ref().and_then(|e| e.downcast_ref::<PartialReadError>()) {
+///         eprintln!("reading failed at {:#x}", partial.offset);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PartialReadError {
+    /// The matches found before the failure
+    pub matches: Vec<FileMatch>,
+    /// The stream position at which reading failed
+    pub offset: u64,
+    pub source: io::Error,
+}
+
+impl fmt::Display for PartialReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Reading failed at {:#x} after {} matches were found: {}",
+            self.offset,
+            self.matches.len(),
+            self.source
+        )
+    }
+}
+
+impl std::error::Error for PartialReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl Pattern {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Searches a stream without loading it into memory, e.g. a disk image, see [FileMatch]
+    ///
+    /// The stream is read in chunks of 64 KiB, see [Pattern::find_in_reader_with]  
+    /// Matches crossing chunk boundaries are found exactly once, the result is the same as that
+    /// of [Pattern::find_with] on the whole stream with default options, in ascending order
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - Reading fails, the error carries a [PartialReadError] with the stream position and the
+    ///   matches found up to it
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("4D 5A ??").unwrap();
+    /// let image: &[u8] = &[0x00, 0x4D, 0x5A, 0x90, 0x00];
+    /// let found = pattern.find_in_reader(image).unwrap();
+    /// assert_eq!(found[0].offset(), 1);
+    /// assert_eq!(found[0].captures().get(0).unwrap().value, 0x90);
+    /// ```
+    ///
+    /// # Compressed streams:
+    ///
+    /// Any decompressing reader can be passed in, the offsets are then those in the decompressed
+    /// stream. A decompression error is returned like any other read error, together with the
+    /// matches found before it. For files of concatenated gzip members, use a decoder that
+    /// continues across members, e.g. `MultiGzDecoder` of the `flate2` crate
+    /// ```ignore
+    /// let archive = std::fs::File::open("dump.bin.gz")?;
+    /// let found = pattern.find_in_reader(flate2::read::MultiGzDecoder::new(archive))?;
+    /// ```
+    pub fn find_in_reader<R: Read>(&self, reader: R) -> io::Result<Vec<FileMatch>> {
+        self.find_in_reader_with(reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [Pattern::find_in_reader], but reads `chunk_size` bytes at once  
+    /// A `chunk_size` of 0 is treated as 1
+    pub fn find_in_reader_with<R: Read>(
+        &self,
+        reader: R,
+        chunk_size: usize,
+    ) -> io::Result<Vec<FileMatch>> {
+        self.find_in_reader_from(reader, chunk_size, 0)
+    }
+
+    /// Same as [Pattern::find_in_reader_with], but the stream starts at `position`
+    pub(crate) fn find_in_reader_from(
+        &self,
+        reader: impl Read,
+        chunk_size: usize,
+        position: u64,
+    ) -> io::Result<Vec<FileMatch>> {
+        let mut matches = Vec::new();
+        let result = self.scan_reader(reader, chunk_size, position, |found| {
+            matches.push(found);
+            true
+        });
+        match result {
+            Ok(()) => Ok(matches),
+            Err((offset, source)) => {
+                let partial = PartialReadError {
+                    matches,
+                    offset,
+                    source,
+                };
+                Err(io::Error::new(partial.source.kind(), partial))
+            }
+        }
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Searches the file at `path` like [Pattern::find_in_reader], without loading it into memory
+    ///
+    /// Files shorter than the Pattern, including empty ones, aren't read at all  
+    /// The file is read while it is searched, if another process modifies it concurrently the
+    /// matches may reflect a mix of the old and new contents, and a file truncated during the search
+    /// is only searched up to its new end
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The file can't be opened or read, see [Pattern::find_in_reader]
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use binmatch::Pattern;
+    /// let mz = Pattern::new("4D 5A").unwrap();
+    /// for found in mz.find_in_file("disk.img".as_ref()).unwrap() {
+    ///     println!("possible PE image at {:#x}", found.offset());
+    /// }
+    /// ```
+    pub fn find_in_file(&self, path: &Path) -> io::Result<Vec<FileMatch>> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() < self.len as u64 {
+            return Ok(Vec::new());
+        }
+        self.find_in_reader(file)
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [Pattern::find_in_reader], but the matches are returned one by one as soon as the
+    /// chunk containing them was read, e.g. to act on early hits in a long transfer
+    ///
+    /// Only the matches of the current chunk are buffered and they are returned in ascending order  
+    /// A read error is returned once, afterwards the iterator ends
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("4D 5A").unwrap();
+    /// let image: &[u8] = &[0x4D, 0x5A, 0x00, 0x4D, 0x5A];
+    /// let mut found = pattern.matches_in_reader(image);
+    /// assert_eq!(found.next().unwrap().unwrap().offset(), 0);
+    /// assert_eq!(found.next().unwrap().unwrap().offset(), 3);
+    /// assert!(found.next().is_none());
+    /// ```
+    pub fn matches_in_reader<R: Read>(&self, reader: R) -> ReaderMatches<'_, R> {
+        ReaderMatches {
+            reader,
+            matcher: self.stream_matcher(),
+            chunk: vec![0; DEFAULT_CHUNK_SIZE],
+            pending: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Reads `reader` in chunks of up to `chunk_size` bytes, pushes them into a [StreamMatcher]
+    /// starting at `position` and calls `found` with every match, until `found` returns `false`
+    ///
+    /// Read errors are returned together with the stream position reached, after scanning the bytes
+    /// read up to it
+    pub(crate) fn scan_reader(
+        &self,
+        mut reader: impl Read,
+        chunk_size: usize,
+        position: u64,
+        mut found: impl FnMut(FileMatch) -> bool,
+    ) -> Result<(), (u64, io::Error)> {
+        let mut matcher = self.stream_matcher_at(position);
+        let mut chunk = vec![0; chunk_size.max(1)];
+        loop {
+            let matches = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => matcher.push(&chunk[..read]),
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err((matcher.position(), err)),
+            };
+            for m in matches {
+                if !found(m) {
+                    return Ok(());
+                }
+            }
+        }
+        for m in matcher.finish() {
+            if !found(m) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Patches the file at `path` in place, writing `template` over every match
+    ///
+    /// The file is scanned in chunks, afterwards only the bytes that actually change are written  
+    /// Overlapping matches are handled like in [Pattern::replace_matches], see [PatchOptions] for
+    /// limiting the number of patches and dry runs
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The file can't be opened, read or written
+    ///   If a write fails after other matches were patched, the error carries a [PartialPatchError]
+    ///   listing them
+    /// - `template` is not exactly as long as the Pattern, with [ErrorKind::InvalidInput]
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use binmatch::{PatchOptions, PatchTemplate, Pattern};
+    /// let je = Pattern::new("74 ??").unwrap();
+    /// let jmp = PatchTemplate::new(&je, "EB __").unwrap();
+    /// let report = je.patch_file("game.exe".as_ref(), &jmp, &PatchOptions::new().max_patches(1)).unwrap();
+    /// println!("patched {} matches", report.len());
+    /// ```
+    pub fn patch_file(
+        &self,
+        path: &Path,
+        template: &PatchTemplate,
+        options: &PatchOptions,
+    ) -> io::Result<PatchReport> {
+        template.check_len(self)?;
+        let mut file = match options.dry_run {
+            true => File::open(path)?,
+            false => OpenOptions::new().read(true).write(true).open(path)?,
+        };
+
+        let max_patches = options.max_patches.unwrap_or(usize::MAX);
+        let end = file.metadata()?.len();
+        let mut records = Vec::new();
+        let mut next_allowed = 0;
+        if max_patches > 0 {
+            self.scan_reader(&file, DEFAULT_CHUNK_SIZE, 0, |found| {
+                let offset = found.offset();
+                if offset < next_allowed {
+                    return true;
+                }
+                next_allowed = offset.saturating_add((found.len() as u64).max(1));
+                let old = found.bytes().to_vec();
+                let mut new = old.clone();
+                template.apply(&mut new);
+                records.push(PatchRecord { offset, old, new });
+                records.len() < max_patches
+            })
+            .map_err(|(_, err)| err)?;
+        }
+
+        let mut report = PatchReport::default();
+        for record in records {
+            if !options.dry_run {
+                if let Err(source) = write_changes(&mut file, &record) {
+                    let offset = record.offset;
+                    let partial = PartialPatchError {
+                        report,
+                        offset,
+                        source,
+                    };
+                    return Err(io::Error::new(partial.source.kind(), partial));
+                }
+                if let Err(source) = options.hooks.run_file(&mut file, &record) {
+                    let offset = record.offset;
+                    report.push(record);
+                    let partial = PartialPatchError {
+                        report,
+                        offset,
+                        source,
+                    };
+                    return Err(io::Error::new(partial.source.kind(), partial));
+                }
+            }
+            report.push(record);
+        }
+        report.update_limit_reached(max_patches, next_allowed, self.len, end);
+        if !options.dry_run {
+            file.flush()?;
+        }
+        Ok(report)
+    }
+}
+
+impl Pattern {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [Pattern::fill_matches], but for the file at `path` like [Pattern::patch_file]
+    pub fn fill_file(
+        &self,
+        path: &Path,
+        fill: u8,
+        options: &PatchOptions,
+    ) -> io::Result<PatchReport> {
+        self.patch_file(
+            path,
+            &PatchTemplate::literal(&vec![fill; self.len]),
+            options,
+        )
+    }
+}
+
+impl PatchReport {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [PatchReport::revert], but for the file at `path`, e.g. one patched by
+    /// [Pattern::patch_file]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The file can't be opened, read or written
+    /// - [PatchReport::revert] would fail, with [ErrorKind::InvalidData] carrying the [RevertError]
+    ///   In that case nothing is written
+    pub fn revert_file(&self, path: &Path) -> io::Result<usize> {
+        self.revert_file_with(path, false)
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [PatchReport::force_revert], but for the file at `path`
+    pub fn force_revert_file(&self, path: &Path) -> io::Result<usize> {
+        self.revert_file_with(path, true)
+    }
+
+    fn revert_file_with(&self, path: &Path, force: bool) -> io::Result<usize> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let available = file.metadata()?.len();
+        let mut writes = Vec::new();
+        for record in self.records() {
+            let end = record.offset.checked_add(record.old.len() as u64);
+            if end.is_none_or(|end| end > available) {
+                let err = RevertError::OutOfBounds {
+                    offset: record.offset,
+                    len: record.old.len(),
+                    available: available.try_into().unwrap_or(usize::MAX),
+                };
+                return Err(io::Error::new(ErrorKind::InvalidData, err));
+            }
+            let mut current = vec![0; record.old.len()];
+            file.seek(SeekFrom::Start(record.offset))?;
+            file.read_exact(&mut current)?;
+            let action = record
+                .revert_action(&current, force)
+                .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+            if action == RevertAction::Write {
+                // Writing `old` over `current` only touches the bytes that differ
+                writes.push(PatchRecord {
+                    offset: record.offset,
+                    old: current,
+                    new: record.old.clone(),
+                });
+            }
+        }
+        for record in &writes {
+            write_changes(&mut file, record)?;
+        }
+        file.flush()?;
+        Ok(writes.len())
+    }
+}
+
+/// Iterator over the matches in a reader, see [Pattern::matches_in_reader]
+pub struct ReaderMatches<'p, R> {
+    reader: R,
+    matcher: StreamMatcher<'p>,
+    chunk: Vec<u8>,
+    pending: VecDeque<FileMatch>,
+    done: bool,
+}
+
+impl<R: Read> Iterator for ReaderMatches<'_, R> {
+    type Item = io::Result<FileMatch>;
+
+    fn next(&mut self) -> Option<io::Result<FileMatch>> {
+        loop {
+            if let Some(found) = self.pending.pop_front() {
+                return Some(Ok(found));
+            }
+            if self.done {
+                return None;
+            }
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => {
+                    self.done = true;
+                    self.pending.extend(self.matcher.clone().finish());
+                }
+                Ok(read) => self.pending.extend(self.matcher.push(&self.chunk[..read])),
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for ReaderMatches<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReaderMatches")
+            .field("reader", &self.reader)
+            .field("position", &self.matcher.position())
+            .field("pending", &self.pending.len())
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+/// Writes every run of bytes that differ between `record.old` and `record.new`
+fn write_changes(file: &mut File, record: &PatchRecord) -> io::Result<()> {
+    let mut index = 0;
+    while index < record.new.len() {
+        if record.old[index] == record.new[index] {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        while index < record.new.len() && record.old[index] != record.new[index] {
+            index += 1;
+        }
+        file.seek(SeekFrom::Start(record.offset + start as u64))?;
+        file.write_all(&record.new[start..index])?;
+    }
+    Ok(())
+}
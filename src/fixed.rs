@@ -0,0 +1,280 @@
+use crate::{parse_token, BinmatchError, PatternElement, ALLOWED_ALPHABET};
+
+/// A Pattern of exactly `N` bytes stored inline, usable without `alloc`
+///
+/// Supports literals, placeholders (`??`) and ignored bytes (`__`) like [Pattern](crate::Pattern),
+/// but has no captures, names or pipeline. It is meant for [StreamScanner], e.g. to detect a sync
+/// word on a device without a heap
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct FixedPattern<const N: usize> {
+    data: [PatternElement; N],
+}
+
+impl<const N: usize> FixedPattern<N> {
+    /// Parses `pattern` with the same syntax as [Pattern::new](crate::Pattern::new)
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The input `&str` contains Characters not contained in `ALLOWED_ALPHABET`
+    /// - The inputs length is not divisible by 2
+    /// - A `?` or `_` is paired with a hex digit, like `?A`
+    /// - The input doesn't describe exactly `N` bytes, or `N` is 0, as a
+    ///   [LengthMismatchError](BinmatchError::LengthMismatchError) in bytes
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{BinmatchError, FixedPattern};
+    /// let pattern = FixedPattern::<4>::new("AA 55 ?? __").unwrap();
+    /// assert_eq!(
+    ///     FixedPattern::<3>::new("AA 55 ?? __"),
+    ///     Err(BinmatchError::LengthMismatchError { expected: 3, found: 4 })
+    /// );
+    /// ```
+    pub fn new(pattern: &str) -> Result<FixedPattern<N>, BinmatchError> {
+        let mut data = [PatternElement::Ignore; N];
+        let mut len = 0;
+        let mut pending = None;
+        for (position, char) in pattern.char_indices().filter(|&(_, c)| c != ' ') {
+            let char = char.to_ascii_uppercase();
+            if !ALLOWED_ALPHABET.contains(&char) {
+                return Err(BinmatchError::PatternParseError { ch: char, position });
+            }
+            let Some(high) = pending.take() else {
+                pending = Some((position, char));
+                continue;
+            };
+            let element = parse_token(high, (position, char))?;
+            // Counted past N so a too long pattern is reported below
+            if let Some(slot) = data.get_mut(len) {
+                *slot = element;
+            }
+            len += 1;
+        }
+        if pending.is_some() {
+            return Err(BinmatchError::PatternLengthError { len: len * 2 + 1 });
+        }
+        if len != N || N == 0 {
+            return Err(BinmatchError::LengthMismatchError {
+                expected: N,
+                found: len,
+            });
+        }
+        Ok(FixedPattern { data })
+    }
+
+    /// Number of bytes covered by the Pattern, always `N`
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Always `false`, a FixedPattern can't be empty
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Whether the Pattern matches the `N` bytes of `window`
+    pub fn matches(&self, window: &[u8; N]) -> bool {
+        self.matches_bytes(window.iter())
+    }
+
+    /// Copies the bytes of `window` at the placeholders (`??`) into an array, in pattern order
+    ///
+    /// The alloc-free counterpart of [Captures::to_array](crate::Captures::to_array), which needs
+    /// `alloc` like every [Match](crate::Match)  
+    /// `None` unless the Pattern matches `window` and has exactly `M` placeholders
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::FixedPattern;
+    /// let pattern = FixedPattern::<4>::new("7E ?? __ ??").unwrap();
+    /// assert_eq!(pattern.capture_array::<2>(&[0x7E, 0x01, 0x02, 0x03]), Some([0x01, 0x03]));
+    /// assert_eq!(pattern.capture_array::<3>(&[0x7E, 0x01, 0x02, 0x03]), None);
+    /// ```
+    pub fn capture_array<const M: usize>(&self, window: &[u8; N]) -> Option<[u8; M]> {
+        if !self.matches(window) || self.placeholders().count() != M {
+            return None;
+        }
+        let mut array = [0; M];
+        for (byte, index) in array.iter_mut().zip(self.placeholders()) {
+            *byte = *window.get(index)?;
+        }
+        Some(array)
+    }
+
+    /// Copies the bytes of `window` at the placeholders (`??`) to the start of `buf`, in pattern
+    /// order, returning how many were written
+    ///
+    /// The alloc-free counterpart of [Captures::read_into](crate::Captures::read_into)  
+    /// Returns `Ok(0)` without writing if the Pattern doesn't match `window`
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `buf` is shorter than the number of placeholders, nothing is written in that case
+    pub fn read_captures_into(
+        &self,
+        window: &[u8; N],
+        buf: &mut [u8],
+    ) -> Result<usize, BinmatchError> {
+        if !self.matches(window) {
+            return Ok(0);
+        }
+        let needed = self.placeholders().count();
+        if buf.len() < needed {
+            return Err(BinmatchError::CaptureBufferError {
+                needed,
+                available: buf.len(),
+            });
+        }
+        for (byte, index) in buf.iter_mut().zip(self.placeholders()) {
+            *byte = window.get(index).copied().unwrap_or_default();
+        }
+        Ok(needed)
+    }
+
+    /// Indices of the placeholders, in pattern order
+    fn placeholders(&self) -> impl Iterator<Item = usize> + '_ {
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, element)| matches!(element, PatternElement::Placeholder))
+            .map(|(index, _)| index)
+    }
+
+    /// `bytes` yields at least `N` bytes, the rest is ignored
+    fn matches_bytes<'b>(&self, bytes: impl Iterator<Item = &'b u8>) -> bool {
+        self.data
+            .iter()
+            .zip(bytes)
+            .all(|(element, &byte)| match element {
+                PatternElement::Literal(literal) => *literal == byte,
+                PatternElement::Placeholder | PatternElement::Ignore => true,
+            })
+    }
+
+    /// Creates a [StreamScanner] for this Pattern, see [StreamScanner::new]
+    pub fn stream_scanner(&self) -> StreamScanner<N> {
+        StreamScanner::new(*self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> From<&FixedPattern<N>> for crate::Pattern {
+    fn from(pattern: &FixedPattern<N>) -> crate::Pattern {
+        crate::Pattern {
+            data: pattern.data.to_vec(),
+            len: N,
+            ..Default::default()
+        }
+    }
+}
+
+/// Searches a stream fed one byte at a time, without `alloc` and without panicking
+///
+/// Only the last `N` bytes are kept in a ring buffer, so every match is reported exactly once, as
+/// soon as its last byte arrives  
+/// Positions are counted in a `u64` that wraps around after `u64::MAX` bytes: the position after
+/// byte `u64::MAX` is 0 again and reported offsets are computed modulo 2^64
+///
+/// # Example:
+/// ```
+/// # use binmatch::{FixedPattern, StreamScanner};
+/// let pattern = FixedPattern::<2>::new("7E ??").unwrap();
+/// let mut scanner = StreamScanner::new(pattern);
+/// assert_eq!(scanner.push(0x7E), None);
+/// assert_eq!(scanner.push(0x01), Some(0));
+///
+/// let mut found = [0; 4];
+/// let mut count = 0;
+/// scanner.push_slice(&[0x00, 0x7E, 0x7E, 0x02], |offset| {
+///     found[count] = offset;
+///     count += 1;
+/// });
+/// assert_eq!(found[..count], [3, 4]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct StreamScanner<const N: usize> {
+    pattern: FixedPattern<N>,
+    buffer: [u8; N],
+    // Index of the oldest byte in buffer, and how many bytes of it are valid
+    head: usize,
+    filled: usize,
+    position: u64,
+}
+
+impl<const N: usize> StreamScanner<N> {
+    /// Scans a stream starting at position 0
+    pub fn new(pattern: FixedPattern<N>) -> StreamScanner<N> {
+        Self::with_position(pattern, 0)
+    }
+
+    /// Scans a stream whose next byte is at `position`, e.g. to continue counting after a reset of
+    /// the device
+    ///
+    /// No bytes before `position` are known, so a match needs `N` more pushes
+    pub fn with_position(pattern: FixedPattern<N>, position: u64) -> StreamScanner<N> {
+        StreamScanner {
+            pattern,
+            buffer: [0; N],
+            head: 0,
+            filled: 0,
+            position,
+        }
+    }
+
+    /// Feeds the next byte of the stream
+    ///
+    /// Returns the position of the first byte of the match if the Pattern completes at `byte`
+    pub fn push(&mut self, byte: u8) -> Option<u64> {
+        // The oldest byte is overwritten, so the slot after it becomes the oldest one
+        *self.buffer.get_mut(self.head)? = byte;
+        self.head = match self.head + 1 {
+            next if next == N => 0,
+            next => next,
+        };
+        self.filled = (self.filled + 1).min(N);
+        self.position = self.position.wrapping_add(1);
+
+        if self.filled < N {
+            return None;
+        }
+        let (newer, older) = self.buffer.split_at(self.head);
+        self.pattern
+            .matches_bytes(older.iter().chain(newer))
+            .then(|| self.position.wrapping_sub(N as u64))
+    }
+
+    /// Feeds every byte of `bytes`, calling `on_match` with the position of every match in order
+    ///
+    /// Returns the number of matches
+    pub fn push_slice(&mut self, bytes: &[u8], mut on_match: impl FnMut(u64)) -> usize {
+        let mut count = 0;
+        for &byte in bytes {
+            if let Some(offset) = self.push(byte) {
+                on_match(offset);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Stream position of the next byte that will be pushed
+    #[inline(always)]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Forgets the buffered bytes, the position keeps counting
+    ///
+    /// Useful after a gap in the stream, so no match spans bytes from before and after it
+    pub fn reset(&mut self) {
+        self.head = 0;
+        self.filled = 0;
+    }
+
+    #[inline(always)]
+    pub fn pattern(&self) -> &FixedPattern<N> {
+        &self.pattern
+    }
+}
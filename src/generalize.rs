@@ -0,0 +1,133 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{Pattern, PatternElement, Vec};
+
+/// Why [Pattern::generalize] couldn't build a Pattern
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum GeneralizeError {
+    #[cfg_attr(feature = "std", error("At least one sample is needed"))]
+    NoSamples,
+    #[cfg_attr(
+        feature = "std",
+        error("Sample {index} is {found} bytes long, but the first one is {expected} bytes long")
+    )]
+    LengthMismatch {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[cfg_attr(
+        feature = "std",
+        error("The samples don't share a single byte, trimming left nothing")
+    )]
+    NothingInCommon,
+}
+
+/// Options for [Pattern::generalize_with]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct GeneralizeOptions {
+    pub(crate) ignore_differences: bool,
+    pub(crate) trim: bool,
+}
+
+impl GeneralizeOptions {
+    pub fn new() -> GeneralizeOptions {
+        Self::default()
+    }
+
+    /// Emits ignored bytes (`__`) instead of placeholders (`??`) where the samples differ
+    pub fn ignore_differences(mut self) -> GeneralizeOptions {
+        self.ignore_differences = true;
+        self
+    }
+
+    /// Removes the positions where the samples differ from both ends of the Pattern
+    ///
+    /// The match then starts at the first shared byte instead of the start of the samples
+    pub fn trim(mut self) -> GeneralizeOptions {
+        self.trim = true;
+        self
+    }
+}
+
+impl Pattern {
+    /// Builds the Pattern matching all `samples`, with default options, see
+    /// [Pattern::generalize_with]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let v1: &[u8] = &[0x55, 0x48, 0x8B, 0x05, 0x10, 0x00];
+    /// let v2: &[u8] = &[0x55, 0x48, 0x8B, 0x0D, 0x24, 0x00];
+    /// let pattern = Pattern::generalize(&[v1, v2]).unwrap();
+    /// assert_eq!(pattern.to_string(), "55 48 8B ?? ?? 00");
+    /// ```
+    pub fn generalize(samples: &[&[u8]]) -> Result<Pattern, GeneralizeError> {
+        Self::generalize_with(samples, &GeneralizeOptions::default())
+    }
+
+    /// Builds the Pattern matching all `samples`: a literal where all samples agree and a
+    /// placeholder where they differ
+    ///
+    /// A single sample gives a Pattern of literals only
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `samples` is empty
+    /// - The samples don't all have the same length
+    /// - [GeneralizeOptions::trim] removed every position
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{GeneralizeOptions, Pattern};
+    /// let v1: &[u8] = &[0xE8, 0x10, 0x00, 0x00, 0x00, 0x90];
+    /// let v2: &[u8] = &[0xE9, 0x20, 0x00, 0x00, 0x00, 0x90];
+    /// let options = GeneralizeOptions::new().ignore_differences().trim();
+    /// let pattern = Pattern::generalize_with(&[v1, v2], &options).unwrap();
+    /// assert_eq!(pattern.to_string(), "00 00 00 90");
+    /// ```
+    pub fn generalize_with(
+        samples: &[&[u8]],
+        options: &GeneralizeOptions,
+    ) -> Result<Pattern, GeneralizeError> {
+        let (first, rest) = samples.split_first().ok_or(GeneralizeError::NoSamples)?;
+        if let Some((index, sample)) = (1..)
+            .zip(rest)
+            .find(|(_, sample)| sample.len() != first.len())
+        {
+            return Err(GeneralizeError::LengthMismatch {
+                index,
+                expected: first.len(),
+                found: sample.len(),
+            });
+        }
+
+        let wildcard = match options.ignore_differences {
+            true => PatternElement::Ignore,
+            false => PatternElement::Placeholder,
+        };
+        let mut data: Vec<PatternElement> = (first.iter().enumerate())
+            .map(
+                |(index, &byte)| match rest.iter().all(|sample| sample[index] == byte) {
+                    true => PatternElement::Literal(byte),
+                    false => wildcard,
+                },
+            )
+            .collect();
+
+        if options.trim {
+            let is_literal =
+                |element: &PatternElement| matches!(element, PatternElement::Literal(_));
+            let end = data
+                .iter()
+                .rposition(is_literal)
+                .ok_or(GeneralizeError::NothingInCommon)?;
+            data.truncate(end + 1);
+            let start = data.iter().position(is_literal).unwrap_or(0);
+            data.drain(..start);
+        }
+        Ok(Pattern::from_elements(data))
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{Match, Pattern, PatternElement, Vec};
+
+impl Pattern {
+    /// Builds `len()` bytes the Pattern matches, e.g. to fuzz a parser with valid signatures
+    ///
+    /// Literals are copied, every placeholder (`??`) and ignored byte (`__`) takes the next byte of
+    /// `next_byte`, which can be any random number generator, e.g. `|| rng.gen()`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("E8 ?? ?? __ 90").unwrap();
+    /// let mut seed = 7u8;
+    /// let data = pattern.generate_matching(|| {
+    ///     seed = seed.wrapping_mul(31).wrapping_add(1);
+    ///     seed
+    /// });
+    /// assert_eq!(data.len(), 5);
+    /// assert!(pattern.match_at(&data, 0).is_some());
+    /// ```
+    pub fn generate_matching(&self, mut next_byte: impl FnMut() -> u8) -> Vec<u8> {
+        self.data
+            .iter()
+            .map(|element| match element {
+                PatternElement::Literal(byte) => *byte,
+                PatternElement::Placeholder | PatternElement::Ignore => next_byte(),
+            })
+            .collect()
+    }
+
+    /// Same as [Pattern::generate_matching], but writes the bytes into `buf` at `at`, so a match is
+    /// planted in existing data
+    ///
+    /// Returns the planted match, `None` if the Pattern doesn't fit into `buf` at `at`, `buf` is
+    /// unchanged then
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("7F 45 4C 46 ??").unwrap();
+    /// let mut buf = vec![0; 16];
+    /// let planted = pattern.embed_match_in(&mut buf, 4, || 0x02).unwrap();
+    /// assert_eq!(planted.start(), 4);
+    /// assert_eq!(&buf[4..9], &[0x7F, 0x45, 0x4C, 0x46, 0x02]);
+    /// assert!(pattern.embed_match_in(&mut buf, 12, || 0x02).is_none());
+    /// ```
+    pub fn embed_match_in(
+        &self,
+        buf: &mut [u8],
+        at: usize,
+        mut next_byte: impl FnMut() -> u8,
+    ) -> Option<Match> {
+        let window = buf.get_mut(at..at.checked_add(self.len)?)?;
+        for (byte, element) in window.iter_mut().zip(&self.data) {
+            *byte = match element {
+                PatternElement::Literal(literal) => *literal,
+                PatternElement::Placeholder | PatternElement::Ignore => next_byte(),
+            };
+        }
+        self.match_at_unchecked(buf, at)
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{Match, MatchOptions, Pattern, Vec};
+
+/// Why [Pattern::find_matches_in_hex] couldn't decode its input
+///
+/// Positions are byte offsets into the hex string
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum HexError {
+    #[cfg_attr(
+        feature = "std",
+        error("Invalid character {character:?} at position {position}")
+    )]
+    InvalidCharacter { character: char, position: usize },
+    #[cfg_attr(
+        feature = "std",
+        error("The digit at position {position} is missing its second digit")
+    )]
+    MissingDigit { position: usize },
+}
+
+impl Pattern {
+    /// Same as [Pattern::find_with] with default options, for a haystack given as a hex string, e.g.
+    /// the output of `xxd -p` or bytes copied from a debugger
+    ///
+    /// Whitespace between bytes and `0x` prefixes are skipped, digits may be upper or lower case  
+    /// The offsets of the matches refer to the decoded bytes
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The string contains a character that is neither a hex digit, whitespace nor part of a
+    ///   `0x` prefix
+    /// - A byte is missing its second digit, e.g. because whitespace splits it
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let dump = "\
+    /// 7f454c4602010100000000000000000003003e00010000004010000000000000
+    /// 4000000000000000e8ffffffff90c3";
+    /// let pattern = Pattern::new("E8 ?? ?? ?? ?? 90").unwrap();
+    /// let found = pattern.find_matches_in_hex(dump).unwrap();
+    /// assert_eq!(found[0].start(), 40);
+    ///
+    /// let found = pattern.find_matches_in_hex("0xE8 0x01 0x02 0x03 0x04 0x90").unwrap();
+    /// assert_eq!(found[0].start(), 0);
+    /// ```
+    pub fn find_matches_in_hex(&self, hex: &str) -> Result<Vec<Match>, HexError> {
+        Ok(self.find_with(&decode_hex(hex)?, &MatchOptions::new()))
+    }
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, HexError> {
+    let mut bytes = Vec::with_capacity(hex.len() / 2);
+    // The first digit of the current byte and its position
+    let mut high: Option<(u8, usize)> = None;
+    let mut chars = hex.char_indices().peekable();
+    while let Some((position, character)) = chars.next() {
+        let digit = match character.to_digit(16) {
+            // `0x` at the start of a byte
+            Some(0)
+                if high.is_none() && chars.next_if(|(_, c)| matches!(c, 'x' | 'X')).is_some() =>
+            {
+                continue;
+            }
+            Some(digit) => digit as u8,
+            None if character.is_whitespace() => match high {
+                Some((_, position)) => return Err(HexError::MissingDigit { position }),
+                None => continue,
+            },
+            None => {
+                return Err(HexError::InvalidCharacter {
+                    character,
+                    position,
+                })
+            }
+        };
+        match high.take() {
+            Some((high, _)) => bytes.push(high << 4 | digit),
+            None => high = Some((digit, position)),
+        }
+    }
+    match high {
+        Some((_, position)) => Err(HexError::MissingDigit { position }),
+        None => Ok(bytes),
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{Match, String};
+use core::fmt;
+use core::ops::Range;
+
+const BYTES_PER_LINE: usize = 16;
+
+impl Match {
+    /// Writes a hexdump of the match and up to `context` bytes around it to `out`
+    ///
+    /// Every line shows the offset, 16 bytes in hex and their ASCII representation  
+    /// Lines containing matched bytes are followed by a marker line with `^^` under matched bytes
+    /// and `??` under captured ones
+    pub fn fmt_hexdump<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        haystack: &[u8],
+        context: usize,
+    ) -> fmt::Result {
+        let shown = self.start().saturating_sub(context)..self.end().saturating_add(context);
+        fmt_hexdump_lines(out, haystack, shown, Some(self))
+    }
+
+    /// Same as [Match::fmt_hexdump], but returns the hexdump as a `String`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("47 ?? 54").unwrap();
+    /// let data = b"GET /".to_vec();
+    /// let found = pattern.find_with(&data, &MatchOptions::new());
+    /// assert_eq!(
+    ///     found[0].hexdump(&data, 0),
+    ///     "00000000  47 45 54 20 2f                                   |GET /|\n\
+    ///      \x20         ^^ ?? ^^\n"
+    /// );
+    /// ```
+    pub fn hexdump(&self, haystack: &[u8], context: usize) -> String {
+        let mut out = String::new();
+        // Writing to a String can't fail
+        let _ = self.fmt_hexdump(&mut out, haystack, context);
+        out
+    }
+}
+
+/// Writes the hexdump lines of `haystack` covering `shown`, with marker lines under `marked`
+pub(crate) fn fmt_hexdump_lines<W: fmt::Write>(
+    out: &mut W,
+    haystack: &[u8],
+    shown: Range<usize>,
+    marked: Option<&Match>,
+) -> fmt::Result {
+    let first_line = shown.start / BYTES_PER_LINE;
+    let end = shown.end.min(haystack.len());
+    let last_line = end.div_ceil(BYTES_PER_LINE).max(first_line + 1);
+    for line in first_line..last_line {
+        let offset = line * BYTES_PER_LINE;
+        let bytes = haystack
+            .get(offset..(offset + BYTES_PER_LINE).min(haystack.len()))
+            .unwrap_or_default();
+        write!(out, "{:08x}  ", offset)?;
+        for column in 0..BYTES_PER_LINE {
+            if column == BYTES_PER_LINE / 2 {
+                out.write_char(' ')?;
+            }
+            match bytes.get(column) {
+                Some(byte) => write!(out, "{:02x} ", byte)?,
+                None => out.write_str("   ")?,
+            }
+        }
+        out.write_char('|')?;
+        for byte in bytes {
+            let shown = if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            };
+            out.write_char(shown)?;
+        }
+        out.write_str("|\n")?;
+
+        let Some(marked) = marked else {
+            continue;
+        };
+        if offset >= marked.end() || offset + BYTES_PER_LINE <= marked.start() {
+            continue;
+        }
+        let mut marker = String::from("          ");
+        for column in 0..BYTES_PER_LINE {
+            if column == BYTES_PER_LINE / 2 {
+                marker.push(' ');
+            }
+            let index = offset + column;
+            if marked.captures().iter().any(|c| c.haystack_offset == index) {
+                marker.push_str("?? ");
+            } else if marked.range().contains(&index) {
+                marker.push_str("^^ ");
+            } else {
+                marker.push_str("   ");
+            }
+        }
+        writeln!(out, "{}", marker.trim_end())?;
+    }
+    Ok(())
+}
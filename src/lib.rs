@@ -12,20 +12,207 @@
 //!
 //! All needed functions can be found in [Pattern]
 //!
+//! Parsing fails with a [BinmatchError], which can be matched on to tell the user what's wrong:
+//! ```
+//! # use binmatch::{BinmatchError, Pattern};
+//! let message = match Pattern::new("48 8B 0D ?0") {
+//!     Ok(pattern) => format!("{pattern}"),
+//!     Err(BinmatchError::PatternParseError { ch, position }) => {
+//!         format!("remove the {ch:?} at {position}")
+//!     }
+//!     Err(BinmatchError::MixedToken { token: [high, low], position }) => {
+//!         format!("replace {high}{low} at {position} with ?? or two digits")
+//!     }
+//!     Err(BinmatchError::PatternLengthError { len }) => format!("{len} digits aren't whole bytes"),
+//!     Err(other) => other.to_string(),
+//! };
+//! assert_eq!(message, "replace ?0 at 9 with ?? or two digits");
+//! ```
+//!
 //! # Usage with `#![no_std]`
-//! First off, disable the default feature `std`  
-//! `cargo add binmatch --no-default-features`  
-//! The normal [Pattern::new] is no longer accesible, because it needs `std` to function  
-//! Every time you wish to create a new [Pattern] you now have to use [Pattern::new_unchecked]  
+//! First off, disable the default feature `std` and enable `alloc`, the matching needs a heap  
+//! `cargo add binmatch --no-default-features --features alloc`  
+//! [Pattern::new] works the same, only the file, reader and parallel APIs need `std`  
+//! Without `alloc`, a [FixedPattern] can still be searched for in a stream with [StreamScanner]  
 //!
 
-#[cfg(not(feature = "std"))]
-include!("no_std_include.rs");
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// `clippy::indexing_slicing` isn't denied: the scans index windows whose bounds were checked once
+// up front, e.g. with `haystack.len().checked_sub(self.len)`, and `get` on every byte would cost
+// time in the hot loops. Indices taken from callers, like the haystack passed to `Match::window`
+// or the offsets of a `PatchRecord`, are checked with `get` instead
+#![cfg_attr(
+    not(test),
+    deny(clippy::panic, clippy::unwrap_used, clippy::expect_used)
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
 #[cfg(feature = "std")]
 use thiserror::Error;
 
-#[cfg(test)]
+#[cfg(feature = "std")]
+mod base64;
+#[cfg(feature = "alloc")]
+mod bitmap;
+#[cfg(feature = "alloc")]
+mod cache;
+#[cfg(feature = "alloc")]
+mod captures;
+#[cfg(feature = "std")]
+mod channel;
+#[cfg(feature = "alloc")]
+mod compare;
+#[cfg(feature = "alloc")]
+mod db;
+#[cfg(feature = "alloc")]
+mod dfa;
+#[cfg(feature = "std")]
+mod dir;
+#[cfg(feature = "alloc")]
+mod executable;
+#[cfg(feature = "alloc")]
+mod explain;
+#[cfg(feature = "alloc")]
+mod export;
+#[cfg(feature = "alloc")]
+mod extract;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod file;
+mod fixed;
+#[cfg(feature = "alloc")]
+mod generalize;
+#[cfg(feature = "alloc")]
+mod generate;
+#[cfg(feature = "alloc")]
+mod hex;
+#[cfg(feature = "alloc")]
+mod hexdump;
+#[cfg(feature = "alloc")]
+mod lint;
+#[cfg(feature = "alloc")]
+mod matches;
+#[cfg(feature = "alloc")]
+mod memory;
+#[cfg(feature = "alloc")]
+mod options;
+#[cfg(feature = "std")]
+mod parallel;
+#[cfg(feature = "alloc")]
+mod parse;
+#[cfg(feature = "alloc")]
+mod patch;
+#[cfg(feature = "alloc")]
+mod pipeline;
+#[cfg(feature = "alloc")]
+mod prefilter;
+#[cfg(all(feature = "process", target_os = "linux"))]
+mod process;
+#[cfg(feature = "alloc")]
+mod regex;
+#[cfg(feature = "std")]
+mod report;
+#[cfg(feature = "alloc")]
+mod set;
+#[cfg(feature = "alloc")]
+mod siggen;
+#[cfg(feature = "std")]
+mod signature;
+#[cfg(feature = "alloc")]
+mod source;
+#[cfg(feature = "alloc")]
+mod stats;
+#[cfg(feature = "alloc")]
+mod stream;
+#[cfg(feature = "test-util")]
+#[doc(hidden)]
+pub mod test_util;
+#[cfg(all(test, feature = "alloc"))]
 mod tests;
+#[cfg(feature = "std")]
+mod writer;
+
+#[cfg(feature = "std")]
+pub use base64::Base64Error;
+#[cfg(feature = "alloc")]
+pub use bitmap::MatchBitmap;
+#[cfg(feature = "alloc")]
+pub use cache::{PatternDecodeError, SetDecodeError};
+#[cfg(feature = "alloc")]
+pub use captures::{Capture, Captures};
+#[cfg(feature = "alloc")]
+pub use db::{DbEntry, PatternDb, ScanReport};
+#[cfg(feature = "alloc")]
+pub use dfa::{Dfa, DfaState, MatchEnd};
+#[cfg(feature = "std")]
+pub use dir::DirScanOptions;
+#[cfg(feature = "alloc")]
+pub use executable::{ExecutableFormat, ExecutableScan, Section, SectionMatch};
+#[cfg(feature = "alloc")]
+pub use explain::MatchExplanation;
+#[cfg(feature = "alloc")]
+pub use extract::{ExtractError, Extractor, FieldKind, Fields};
+#[cfg(feature = "std")]
+pub use file::{PartialPatchError, PartialReadError, ReaderMatches};
+pub use fixed::{FixedPattern, StreamScanner};
+#[cfg(feature = "alloc")]
+pub use generalize::{GeneralizeError, GeneralizeOptions};
+#[cfg(feature = "alloc")]
+pub use hex::HexError;
+#[cfg(feature = "alloc")]
+pub use lint::LintWarning;
+#[cfg(feature = "alloc")]
+pub use matches::{ContextMatch, Match, ScanSummary};
+#[cfg(feature = "std")]
+pub use memory::ReaderSource;
+#[cfg(feature = "alloc")]
+pub use memory::{MemError, MemoryRegion, MemorySource, Permissions, RegionMatch, SliceSource};
+#[cfg(feature = "alloc")]
+pub use options::MatchOptions;
+#[cfg(feature = "alloc")]
+pub use parse::ParseError;
+#[cfg(feature = "alloc")]
+pub use patch::{
+    Patch, PatchOptions, PatchRecord, PatchReport, PatchTemplate, RevertError, VerifyError,
+};
+#[cfg(all(feature = "process", target_os = "linux"))]
+pub use process::ProcessScanError;
+#[cfg(feature = "alloc")]
+pub use regex::RegexImportError;
+#[cfg(feature = "std")]
+pub use report::Report;
+#[cfg(feature = "alloc")]
+pub use set::{MatchPolicy, PatternSet};
+#[cfg(feature = "alloc")]
+pub use siggen::{SigGenError, SigGenOptions};
+#[cfg(feature = "std")]
+pub use signature::SignatureFileError;
+#[cfg(feature = "alloc")]
+pub use stats::ScanStats;
+#[cfg(feature = "alloc")]
+pub use stream::{FileMatch, ScanState, StreamMatcher};
+#[cfg(feature = "std")]
+pub use writer::ScanWriter;
+
+#[cfg(feature = "alloc")]
+use pipeline::CapturePipeline;
+#[cfg(feature = "alloc")]
+use source::PatternSource;
+
+#[cfg(feature = "alloc")]
+use core::ops::Range;
 
 pub const ALLOWED_ALPHABET: [char; 18] = [
     '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F',
@@ -33,19 +220,137 @@ pub const ALLOWED_ALPHABET: [char; 18] = [
     '_', // _ is used to indicate a character to ignore
 ];
 
-#[cfg(feature = "std")]
-#[derive(Error, Debug)]
+/// Turns two uppercased characters with their positions into the element they stand for
+///
+/// Every parser of the syntax of [Pattern::new] goes through here, so they agree on what a token is
+pub(crate) fn parse_token(
+    (position, high): (usize, char),
+    (low_position, low): (usize, char),
+) -> Result<PatternElement, BinmatchError> {
+    for (position, ch) in [(position, high), (low_position, low)] {
+        if !ALLOWED_ALPHABET.contains(&ch) {
+            return Err(BinmatchError::PatternParseError { ch, position });
+        }
+    }
+    match (high, low) {
+        ('?', '?') => Ok(PatternElement::Placeholder),
+        ('_', '_') => Ok(PatternElement::Ignore),
+        // Every character is in the alphabet, so only a mix like `?0` isn't two digits
+        _ => match (high.to_digit(16), low.to_digit(16)) {
+            (Some(high), Some(low)) => Ok(PatternElement::Literal((high * 16 + low) as u8)),
+            _ => Err(BinmatchError::MixedToken {
+                token: [high, low],
+                position,
+            }),
+        },
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum BinmatchError {
-    #[error("Invalid Character passed to binmatch::pattern::new [{0}]")]
-    PatternParseError(char),
-    #[error("Patterns should always be an even number of characters long")]
-    PatternLengthError,
+    #[cfg_attr(
+        feature = "std",
+        error("invalid character {ch:?} at position {position}")
+    )]
+    PatternParseError { ch: char, position: usize },
+    #[cfg_attr(
+        feature = "std",
+        error("Patterns should always be an even number of characters long, found {len}")
+    )]
+    PatternLengthError { len: usize },
+    #[cfg_attr(feature = "std", error("The stride of a search must be at least 1"))]
+    StrideError,
+    #[cfg_attr(
+        feature = "std",
+        error("{needed} captures don't fit into a buffer of {available} bytes")
+    )]
+    CaptureBufferError { needed: usize, available: usize },
+    #[cfg_attr(
+        feature = "std",
+        error("The replacement is {found} bytes long, but the Pattern is {expected} bytes long")
+    )]
+    ReplacementLengthError { expected: usize, found: usize },
+    #[cfg_attr(
+        feature = "std",
+        error("A patch needs exactly one `->` between the Pattern and the replacement")
+    )]
+    PatchArrowError,
+    #[cfg_attr(
+        feature = "std",
+        error("Expected a length of {expected}, but found a length of {found}")
+    )]
+    LengthMismatchError { expected: usize, found: usize },
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "mixed token \"{}{}\" at position {position}, `?` and `_` can only be paired with themselves",
+            .token[0],
+            .token[1]
+        )
+    )]
+    MixedToken { token: [char; 2], position: usize },
+}
+
+/// Without `std` the messages are written by hand, with the same wording as the derived ones
+#[cfg(not(feature = "std"))]
+impl core::fmt::Display for BinmatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BinmatchError::PatternParseError { ch, position } => {
+                write!(f, "invalid character {ch:?} at position {position}")
+            }
+            BinmatchError::PatternLengthError { len } => write!(
+                f,
+                "Patterns should always be an even number of characters long, found {len}"
+            ),
+            BinmatchError::StrideError => f.write_str("The stride of a search must be at least 1"),
+            BinmatchError::CaptureBufferError { needed, available } => write!(
+                f,
+                "{needed} captures don't fit into a buffer of {available} bytes"
+            ),
+            BinmatchError::ReplacementLengthError { expected, found } => write!(
+                f,
+                "The replacement is {found} bytes long, but the Pattern is {expected} bytes long"
+            ),
+            BinmatchError::PatchArrowError => f.write_str(
+                "A patch needs exactly one `->` between the Pattern and the replacement",
+            ),
+            BinmatchError::LengthMismatchError { expected, found } => write!(
+                f,
+                "Expected a length of {expected}, but found a length of {found}"
+            ),
+            BinmatchError::MixedToken {
+                token: [high, low],
+                position,
+            } => write!(
+                f,
+                "mixed token \"{high}{low}\" at position {position}, `?` and `_` can only be paired with themselves"
+            ),
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl core::error::Error for BinmatchError {}
+
+/// Lets `?` turn a BinmatchError into an [std::io::Error] of kind
+/// [InvalidInput](std::io::ErrorKind::InvalidInput), e.g. in functions that also read files
+#[cfg(feature = "std")]
+impl From<BinmatchError> for std::io::Error {
+    fn from(err: BinmatchError) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg(feature = "alloc")]
 pub struct Pattern {
     data: Vec<PatternElement>,
     len: usize,
+    capture_names: Option<Arc<[String]>>,
+    capture_pipeline: CapturePipeline,
+    source: PatternSource,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -55,9 +360,8 @@ enum PatternElement {
     Ignore,
 }
 
+#[cfg(feature = "alloc")]
 impl Pattern {
-    /// <div class="warning"> Only available using the <code>std</code> feature </div>
-    ///
     /// Create a new `Pattern`  
     ///
     ///
@@ -65,71 +369,82 @@ impl Pattern {
     ///
     /// - The input `&str` contains Characters not contained in `ALLOWED_ALPHABET`
     /// - The inputs length is not divisible by 2
+    /// - A `?` or `_` is paired with a hex digit, like `?A`
     ///
     /// # Example:
     /// ```
-    /// # use binmatch::Pattern;
+    /// # use binmatch::{BinmatchError, Pattern};
     /// let pattern = Pattern::new("00 __ 00 ??").unwrap();
+    /// assert_eq!(
+    ///     Pattern::new("00 __ 0"),
+    ///     Err(BinmatchError::PatternLengthError { len: 5 })
+    /// );
+    /// ```
+    pub fn new(pattern: &str) -> Result<Pattern, BinmatchError> {
+        Self::try_new(pattern)
+    }
+
+    /// Same as [Pattern::new]
+    ///
+    /// Kept from when [Pattern::new] returned a `Box<dyn Error>` and needed `std`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{BinmatchError, Pattern};
+    /// let pattern = Pattern::try_new("00 __ 00 ??").unwrap();
+    /// assert!(matches!(Pattern::try_new("0"), Err(BinmatchError::PatternLengthError { len: 1 })));
     /// ```
-    #[cfg(feature = "std")]
-    pub fn new(pattern: &str) -> Result<Pattern, Box<dyn std::error::Error>> {
-        let string = pattern.replace(' ', "").to_uppercase();
-        if string.len() % 2 != 0 {
-            return Err(Box::new(BinmatchError::PatternLengthError));
+    pub fn try_new(pattern: &str) -> Result<Pattern, BinmatchError> {
+        // Positions are kept for the spans, so the spaces aren't removed from a copy
+        let chars: Vec<(usize, char)> = pattern
+            .char_indices()
+            .filter(|(_, char)| *char != ' ')
+            .map(|(position, char)| (position, char.to_ascii_uppercase()))
+            .collect();
+        if !chars.len().is_multiple_of(2) {
+            return Err(BinmatchError::PatternLengthError { len: chars.len() });
         }
-        for char in string.chars() {
-            if !ALLOWED_ALPHABET.contains(&char) {
-                return Err(Box::new(BinmatchError::PatternParseError(char)));
-            }
+        if let Some(&(position, ch)) = chars.iter().find(|(_, c)| !ALLOWED_ALPHABET.contains(c)) {
+            return Err(BinmatchError::PatternParseError { ch, position });
         }
 
         let mut data: Vec<PatternElement> = Vec::new();
-        for hex in string.chars().collect::<Vec<char>>().chunks(2) {
-            let hex = String::from_utf8(hex.to_vec().iter().map(|&c| c as u8).collect())?;
-            match hex.as_str() {
-                "??" => data.push(PatternElement::Placeholder),
-                "__" => data.push(PatternElement::Ignore),
-                v => data.push(PatternElement::Literal(u8::from_str_radix(v, 16)?)),
-            }
+        let mut spans = Vec::new();
+        for pair in chars.chunks(2) {
+            let [high, low] = *pair else {
+                return Err(BinmatchError::PatternLengthError { len: chars.len() });
+            };
+            data.push(parse_token(high, low)?);
+            spans.push(high.0..low.0 + low.1.len_utf8());
         }
-        let len = data.len();
 
-        Ok(Self { data, len })
+        Ok(Self {
+            len: data.len(),
+            data,
+            source: PatternSource::new(pattern, spans),
+            ..Default::default()
+        })
     }
 
     /// Create a new `Pattern`  
     ///
+    /// Deprecated, because panicking isn't acceptable on many `no_std` targets, use
+    /// [Pattern::try_new] instead
+    ///
     /// # Panics when:
     /// - The input `&str` contains Characters not contained in `ALLOWED_ALPHABET`
     /// - The inputs length is not divisible by 2
+    /// - A `?` or `_` is paired with a hex digit, like `?A`
     ///
     /// # Example:
     /// ```
     /// # use binmatch::Pattern;
     /// let pattern = Pattern::new_unchecked("00 __ 00 ??");
     /// ```
+    #[deprecated(note = "use Pattern::try_new, which doesn't panic")]
+    #[allow(clippy::expect_used)]
     pub fn new_unchecked(pattern: &str) -> Pattern {
-        let string = pattern.replace(' ', "").to_uppercase();
-        assert!(string.len() % 2 == 0);
-        for char in string.chars() {
-            assert!(ALLOWED_ALPHABET.contains(&char));
-        }
-
-        let mut data: Vec<PatternElement> = Vec::new();
-        for hex in string.chars().collect::<Vec<char>>().chunks(2) {
-            let hex = String::from_utf8(hex.to_vec().iter().map(|&c| c as u8).collect())
-                .expect("Could not parse a chunk to a String");
-            match hex.as_str() {
-                "??" => data.push(PatternElement::Placeholder),
-                "__" => data.push(PatternElement::Ignore),
-                v => data.push(PatternElement::Literal(
-                    u8::from_str_radix(v, 16).expect("Could not parse the string to a u8"),
-                )), // It shouldn't be possible to panic from this line
-            }
-        }
-        let len = data.len();
-
-        Self { data, len }
+        Self::try_new(pattern).expect("Invalid pattern passed to Pattern::new_unchecked")
     }
 
     /// Finds all matches in the `haystack`
@@ -145,17 +460,10 @@ impl Pattern {
     /// assert_eq!(matches, vec![(0x42, 5)]);
     /// ```
     pub fn find_matches_with_index(&self, haystack: Vec<u8>) -> Vec<(u8, usize)> {
-        let mut matches = Vec::new();
-        for (i, sub) in haystack.windows(self.len).enumerate() {
-            matches.extend(
-                self.match_chunk(sub.to_vec())
-                    .0
-                    .iter()
-                    .map(|m| (m.0, m.1 + i))
-                    .collect::<Vec<(u8, usize)>>(),
-            );
-        }
-        matches
+        self.find_with(&haystack, &MatchOptions::default())
+            .iter()
+            .flat_map(|m| m.captures().to_tuples())
+            .collect()
     }
 
     /// Convenience Method for cases where the index is not needed
@@ -183,12 +491,92 @@ impl Pattern {
     /// assert_eq!(pattern.has_match(data), true);
     /// ```
     pub fn has_match(&self, haystack: Vec<u8>) -> bool {
-        for sub in haystack.windows(self.len) {
-            if self.match_chunk(sub.to_vec()).1 {
-                return true;
+        !self
+            .find_with(&haystack, &MatchOptions::new().max_matches(1))
+            .is_empty()
+    }
+
+    /// Maps the absolute index of every captured byte to its value
+    ///
+    /// Overlapping matches can capture the same index through different placeholders,
+    /// the index is then only stored once (first match wins, the value is the same haystack byte either way)
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ??").unwrap();
+    /// let data = vec![0x00, 0x42, 0x00, 0x43];
+    /// let map = pattern.find_matches_as_map(&data);
+    /// assert_eq!(map.get(&3), Some(&0x43));
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn find_matches_as_map(&self, haystack: &[u8]) -> BTreeMap<usize, u8> {
+        let mut map = BTreeMap::new();
+        for m in self.find_with(haystack, &MatchOptions::default()) {
+            for capture in m.captures() {
+                map.entry(capture.haystack_offset).or_insert(capture.value);
+            }
+        }
+        map
+    }
+
+    /// Collects the distinct values captured by any placeholder across all matches, sorted ascending
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("31 ??").unwrap();
+    /// let data = vec![0x31, 0x07, 0x31, 0x03, 0x31, 0x07];
+    /// assert_eq!(pattern.captured_value_set(&data), vec![0x03, 0x07]);
+    /// ```
+    pub fn captured_value_set(&self, haystack: &[u8]) -> Vec<u8> {
+        let mut seen = [false; 256];
+        for histogram in self.captured_value_histogram(haystack) {
+            for (value, count) in histogram.iter().enumerate() {
+                seen[value] |= *count > 0;
+            }
+        }
+        (0..=255u8).filter(|value| seen[*value as usize]).collect()
+    }
+
+    /// Counts how often each value was captured, with one histogram per placeholder in pattern order
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("31 ?? ??").unwrap();
+    /// let data = vec![0x31, 0x07, 0x00, 0x31, 0x07, 0x01];
+    /// let histograms = pattern.captured_value_histogram(&data);
+    /// assert_eq!(histograms.len(), 2);
+    /// assert_eq!(histograms[0][0x07], 2);
+    /// assert_eq!((histograms[1][0x00], histograms[1][0x01]), (1, 1));
+    /// ```
+    pub fn captured_value_histogram(&self, haystack: &[u8]) -> Vec<[u32; 256]> {
+        let placeholders = self
+            .data
+            .iter()
+            .filter(|element| **element == PatternElement::Placeholder)
+            .count();
+        let mut histograms = vec![[0u32; 256]; placeholders];
+        if placeholders == 0 {
+            return histograms;
+        }
+        for window in haystack.windows(self.len) {
+            if !self.matches_window(window) {
+                continue;
+            }
+            let captured = window
+                .iter()
+                .zip(self.data.iter())
+                .filter(|(_, element)| **element == PatternElement::Placeholder);
+            for (ordinal, (histogram, (value, _))) in
+                histograms.iter_mut().zip(captured).enumerate()
+            {
+                let value = self.capture_pipeline.apply(ordinal, *value) as usize;
+                histogram[value] = histogram[value].saturating_add(1);
             }
         }
-        false
+        histograms
     }
 
     /// Finds a match in a chunk  
@@ -213,21 +601,397 @@ impl Pattern {
     /// let (matches, _) = pattern.match_chunk(vec![0x00, 0x32, 0x42, 0x00, 0x00]);
     /// unreachable!();
     /// ```
+    #[deprecated(note = "use Pattern::try_match_chunk, which doesn't panic")]
+    #[allow(clippy::expect_used)]
     pub fn match_chunk(&self, chunk: Vec<u8>) -> (Vec<(u8, usize)>, bool) {
-        assert_eq!(self.len, chunk.len());
+        self.try_match_chunk(&chunk)
+            .expect("The chunk has to have the length of the Pattern")
+    }
+
+    /// Same as [Pattern::match_chunk], but returns an Error instead of panicking
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `chunk` doesn't have the length of the Pattern
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 __ 00 ??").unwrap();
+    /// let (matches, _) = pattern.try_match_chunk(&[0x00, 0x32, 0x00, 0x42]).unwrap();
+    /// assert_eq!(matches, vec![(0x42, 3)]);
+    /// assert!(pattern.try_match_chunk(&[0x00, 0x32]).is_err());
+    /// ```
+    pub fn try_match_chunk(&self, chunk: &[u8]) -> Result<(Vec<(u8, usize)>, bool), BinmatchError> {
+        if chunk.len() != self.len {
+            return Err(BinmatchError::LengthMismatchError {
+                expected: self.len,
+                found: chunk.len(),
+            });
+        }
         let mut matches = Vec::new();
         for (index, (actual, expected)) in chunk.iter().zip(self.data.clone()).enumerate() {
             match expected {
                 PatternElement::Literal(expected) => {
                     if expected != *actual {
-                        return (Vec::new(), false); // Discard all matches
+                        return Ok((Vec::new(), false)); // Discard all matches
                     }
                 }
-                PatternElement::Placeholder => matches.push((*actual, index)),
+                PatternElement::Placeholder => {
+                    matches.push((self.capture_pipeline.apply(matches.len(), *actual), index))
+                }
                 PatternElement::Ignore => (),
             }
         }
-        (matches, true)
+        Ok((matches, true))
+    }
+
+    /// Only tests the offsets `phase + k * stride`, for data laid out in fixed size records  
+    /// Offsets in between are never looked at, which makes this a lot faster than filtering a full scan
+    ///
+    /// A `stride` of 1 behaves exactly like a normal scan  
+    /// A `phase` larger than or equal to `stride` is reduced modulo `stride`
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `stride` is 0
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("AA ??").unwrap();
+    /// let data = vec![0xAA, 0x01, 0xAA, 0x02, 0xAA, 0x03, 0xAA, 0x04];
+    /// let matches = pattern.find_matches_strided(&data, 4, 2).unwrap();
+    /// assert_eq!(matches.len(), 2);
+    /// assert_eq!(matches[0].start(), 2);
+    /// assert_eq!(matches[1].captures(), &[(0x04, 7)]);
+    /// ```
+    pub fn find_matches_strided(
+        &self,
+        haystack: &[u8],
+        stride: usize,
+        phase: usize,
+    ) -> Result<Vec<Match>, BinmatchError> {
+        if stride == 0 {
+            return Err(BinmatchError::StrideError);
+        }
+        let mut matches = Vec::new();
+        if haystack.len() < self.len {
+            return Ok(matches);
+        }
+        let last = haystack.len() - self.len;
+        for start in (phase % stride..=last).step_by(stride) {
+            if let Some(m) = self.match_at_unchecked(haystack, start) {
+                matches.push(m);
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Checks whether the Pattern matches `haystack` starting exactly at `offset`
+    ///
+    /// Returns `None` if it doesn't match or if the Pattern would run past the end of `haystack`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ??").unwrap();
+    /// let data = vec![0x12, 0x00, 0x42];
+    /// assert_eq!(pattern.match_at(&data, 1).unwrap().captures(), &[(0x42, 2)]);
+    /// assert!(pattern.match_at(&data, 0).is_none());
+    /// assert!(pattern.match_at(&data, 2).is_none());
+    /// ```
+    pub fn match_at(&self, haystack: &[u8], offset: usize) -> Option<Match> {
+        match offset.checked_add(self.len) {
+            Some(end) if end <= haystack.len() => self.match_at_unchecked(haystack, offset),
+            _ => None,
+        }
+    }
+
+    /// Checks the Pattern at every offset in `offsets`, e.g. candidates from an index or a previous coarse scan
+    ///
+    /// The result has one entry per offset, in the same order  
+    /// Offsets where the Pattern doesn't match or doesn't fit into `haystack` are `None`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ??").unwrap();
+    /// let data = vec![0x00, 0x01, 0x00, 0x02];
+    /// let results = pattern.match_at_many(&data, &[2, 1, 0, 3, usize::MAX]);
+    /// let starts: Vec<Option<usize>> = results.iter().map(|m| m.as_ref().map(|m| m.start())).collect();
+    /// assert_eq!(starts, vec![Some(2), None, Some(0), None, None]);
+    /// ```
+    pub fn match_at_many(&self, haystack: &[u8], offsets: &[usize]) -> Vec<Option<Match>> {
+        let last = haystack.len().checked_sub(self.len);
+        offsets
+            .iter()
+            .map(|&offset| match last {
+                Some(last) if offset <= last => self.match_at_unchecked(haystack, offset),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Finds the offsets at which the Pattern matches, without collecting any captures
+    ///
+    /// An empty Pattern never matches, like in [Pattern::compile_dfa]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ??").unwrap();
+    /// let data = vec![0x00, 0x00, 0x12, 0x00];
+    /// assert_eq!(pattern.find_match_starts(&data), vec![0, 1]);
+    /// ```
+    pub fn find_match_starts(&self, haystack: &[u8]) -> Vec<usize> {
+        if self.len == 0 {
+            return Vec::new();
+        }
+        haystack
+            .windows(self.len)
+            .enumerate()
+            .filter(|(_, window)| self.matches_window(window))
+            .map(|(start, _)| start)
+            .collect()
+    }
+
+    /// Same as [Pattern::find_match_starts], but stores the starts as one bit per haystack offset  
+    /// Takes `haystack.len() / 8` bytes no matter how many matches there are
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ??").unwrap();
+    /// let data = vec![0x00, 0x00, 0x12, 0x00];
+    /// let bitmap = pattern.match_bitmap(&data);
+    /// assert!(bitmap.get(0) && bitmap.get(1) && !bitmap.get(2) && !bitmap.get(3));
+    /// ```
+    pub fn match_bitmap(&self, haystack: &[u8]) -> MatchBitmap {
+        let mut bitmap = MatchBitmap::new(haystack.len());
+        if self.len == 0 {
+            return bitmap;
+        }
+        for (start, window) in haystack.windows(self.len).enumerate() {
+            if self.matches_window(window) {
+                bitmap.set(start);
+            }
+        }
+        bitmap
+    }
+
+    /// Merges the extents of all matches into maximal ranges of covered bytes  
+    /// Matches that overlap or touch (`next.start <= previous.end`) end up in the same range
+    ///
+    /// Use [Pattern::find_match_regions_with_gap] to also merge matches that are a few bytes apart
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ??").unwrap();
+    /// let data = vec![0x00, 0x00, 0x00, 0x12, 0x34, 0x00, 0x56];
+    /// assert_eq!(pattern.find_match_regions(&data), vec![0..4, 5..7]);
+    /// ```
+    pub fn find_match_regions(&self, haystack: &[u8]) -> Vec<Range<usize>> {
+        self.find_match_regions_with_gap(haystack, 0)
+    }
+
+    /// Same as [Pattern::find_match_regions], but also merges matches separated by at most `gap` bytes
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ??").unwrap();
+    /// let data = vec![0x00, 0x00, 0x00, 0x12, 0x34, 0x00, 0x56];
+    /// assert_eq!(pattern.find_match_regions_with_gap(&data, 1), vec![0..7]);
+    /// ```
+    pub fn find_match_regions_with_gap(&self, haystack: &[u8], gap: usize) -> Vec<Range<usize>> {
+        let mut regions: Vec<Range<usize>> = Vec::new();
+        for start in self.find_match_starts(haystack) {
+            let end = start + self.len;
+            match regions.last_mut() {
+                Some(last) if start <= last.end.saturating_add(gap) => last.end = last.end.max(end),
+                _ => regions.push(start..end),
+            }
+        }
+        regions
+    }
+
+    /// Finds all matches together with up to `before` bytes preceding and `after` bytes following each of them
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("AA").unwrap();
+    /// let data = vec![0x01, 0x02, 0xAA, 0x03];
+    /// let found = pattern.find_with_context(&data, 4, 4);
+    /// assert_eq!(&found[0].pre[..], &[0x01, 0x02]);
+    /// assert_eq!(&found[0].post[..], &[0x03]);
+    /// ```
+    pub fn find_with_context<'h>(
+        &self,
+        haystack: &'h [u8],
+        before: usize,
+        after: usize,
+    ) -> Vec<ContextMatch<'h>> {
+        self.find_with(haystack, &MatchOptions::default())
+            .into_iter()
+            .map(|matched| ContextMatch {
+                pre: Cow::Borrowed(
+                    &haystack[matched.start().saturating_sub(before)..matched.start()],
+                ),
+                post: Cow::Borrowed(
+                    &haystack
+                        [matched.end()..matched.end().saturating_add(after).min(haystack.len())],
+                ),
+                matched,
+            })
+            .collect()
+    }
+
+    /// Finds all matches walking from the end of `haystack` towards the start, the last match comes first
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("AA ??").unwrap();
+    /// let data = vec![0xAA, 0x01, 0xAA, 0x02];
+    /// let matches = pattern.rfind_matches(&data);
+    /// assert_eq!(matches[0].start(), 2);
+    /// assert_eq!(matches[1].start(), 0);
+    /// ```
+    pub fn rfind_matches(&self, haystack: &[u8]) -> Vec<Match> {
+        self.rfind_iter(haystack).collect()
+    }
+
+    /// Finds the match starting closest to the end of `haystack`  
+    /// Stops at the first match found walking backwards, so trailers of big files are found quickly
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("AA ??").unwrap();
+    /// let data = vec![0xAA, 0x01, 0xAA, 0x02, 0x00];
+    /// assert_eq!(pattern.find_last(&data).unwrap().captures(), &[(0x02, 3)]);
+    /// ```
+    pub fn find_last(&self, haystack: &[u8]) -> Option<Match> {
+        self.rfind_iter(haystack).next()
+    }
+
+    fn rfind_iter<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = Match> + 'a {
+        let starts = match haystack.len().checked_sub(self.len) {
+            Some(last) => 0..last + 1,
+            None => 0..0,
+        };
+        starts
+            .rev()
+            .filter_map(move |start| self.match_at_unchecked(haystack, start))
+    }
+
+    /// Searches a ring buffer whose oldest byte is at `head`, including matches that wrap around the physical end
+    ///
+    /// All offsets in the returned matches are **logical**, i.e. relative to `head`  
+    /// The physical index of a logical offset `i` is `(head + i) % buf.len()`  
+    /// A `head` larger than `buf.len()` is reduced modulo `buf.len()`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("AA BB ??").unwrap();
+    /// let ring = vec![0xBB, 0x42, 0x00, 0x00, 0xAA]; // Logically AA BB 42 00 00
+    /// let matches = pattern.find_in_ring(&ring, 4);
+    /// assert_eq!(matches[0].start(), 0);
+    /// assert_eq!(matches[0].captures(), &[(0x42, 2)]);
+    /// ```
+    pub fn find_in_ring(&self, buf: &[u8], head: usize) -> Vec<Match> {
+        if buf.is_empty() {
+            return Vec::new();
+        }
+        let options = MatchOptions::default();
+        let (second, first) = buf.split_at(head % buf.len());
+        let mut matches = self.find_with(first, &options);
+
+        // Only the last `len() - 1` bytes before and the first `len() - 1` bytes after the seam can form a wrapping match
+        let carry = self.len.saturating_sub(1);
+        let before = &first[first.len().saturating_sub(carry)..];
+        let after = &second[..carry.min(second.len())];
+        let mut stitch = Vec::with_capacity(before.len() + after.len());
+        stitch.extend_from_slice(before);
+        stitch.extend_from_slice(after);
+        matches.extend(
+            self.find_with(&stitch, &options)
+                .into_iter()
+                .filter(|m| m.start() < before.len() && m.end() > before.len())
+                .map(|m| m.shifted(first.len() - before.len())),
+        );
+
+        matches.extend(
+            self.find_with(second, &options)
+                .into_iter()
+                .map(|m| m.shifted(first.len())),
+        );
+        matches
+    }
+
+    /// Checks only the literals of a window that has exactly the Pattern's length
+    ///
+    /// An empty Pattern never matches, so every search agrees with [Pattern::find_with]
+    #[inline(always)]
+    fn matches_window(&self, window: &[u8]) -> bool {
+        self.len > 0
+            && window
+                .iter()
+                .zip(self.data.iter())
+                .all(|(actual, expected)| match expected {
+                    PatternElement::Literal(expected) => expected == actual,
+                    PatternElement::Placeholder | PatternElement::Ignore => true,
+                })
+    }
+
+    /// Verifies the Pattern at `start`, the caller has to make sure the window fits into `haystack`
+    ///
+    /// An empty Pattern never matches, see [Pattern::find_with]
+    fn match_at_unchecked(&self, haystack: &[u8], start: usize) -> Option<Match> {
+        if self.len == 0 {
+            return None;
+        }
+        self.match_or_mismatch(haystack, start).ok()
+    }
+
+    /// Same as [Pattern::match_at_unchecked], but the error tells which literal didn't match: its
+    /// index in the Pattern, the expected and the found byte
+    fn match_or_mismatch(&self, haystack: &[u8], start: usize) -> Result<Match, (usize, u8, u8)> {
+        let window = &haystack[start..start + self.len];
+        let mut captures = Vec::new();
+        for (index, (actual, expected)) in window.iter().zip(self.data.iter()).enumerate() {
+            match expected {
+                PatternElement::Literal(expected) => {
+                    if expected != actual {
+                        return Err((index, *expected, *actual));
+                    }
+                }
+                PatternElement::Placeholder => captures.push(Capture {
+                    value: self.capture_pipeline.apply(captures.len(), *actual),
+                    pattern_index: index,
+                    haystack_offset: start + index,
+                }),
+                PatternElement::Ignore => (),
+            }
+        }
+        Ok(Match::new(
+            start,
+            start + self.len,
+            Captures::new(captures, self.capture_names.clone()),
+        ))
+    }
+
+    /// A Pattern without capture names or transforms
+    fn from_elements(data: Vec<PatternElement>) -> Pattern {
+        Pattern {
+            len: data.len(),
+            data,
+            ..Default::default()
+        }
     }
 
     #[inline(always)]
@@ -240,3 +1004,42 @@ impl Pattern {
         self.len == 0
     }
 }
+
+/// <div class="warning"> Only available using the <code>alloc</code> feature </div>
+///
+/// Parses the syntax of [Pattern::new], so Patterns can be read with anything built on `FromStr`
+///
+/// # Example:
+/// ```
+/// # use binmatch::Pattern;
+/// let pattern: Pattern = "48 8b ?? __".parse().unwrap();
+/// assert_eq!(pattern.to_string(), "48 8B ?? __");
+/// ```
+#[cfg(feature = "alloc")]
+impl core::str::FromStr for Pattern {
+    type Err = BinmatchError;
+
+    fn from_str(pattern: &str) -> Result<Pattern, BinmatchError> {
+        Pattern::try_new(pattern)
+    }
+}
+
+/// Writes the Pattern in the canonical syntax accepted by [Pattern::new], e.g. `48 8B ?? __`
+///
+/// Parsing the output again gives an equal Pattern, apart from capture names and transforms
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (index, element) in self.data.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            match element {
+                PatternElement::Literal(byte) => write!(f, "{:02X}", byte)?,
+                PatternElement::Placeholder => f.write_str("??")?,
+                PatternElement::Ignore => f.write_str("__")?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{Pattern, PatternElement, Vec};
+use core::fmt;
+
+/// A likely mistake in a Pattern, found by [Pattern::lint]
+///
+/// The Display messages are meant to be shown to whoever wrote the Pattern
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
+pub enum LintWarning {
+    /// Every element is a placeholder or ignored, so every window matches
+    AllWildcards,
+    /// The Pattern starts with this many ignored bytes, which only shift the match start
+    LeadingIgnores(usize),
+    /// The Pattern ends with this many ignored bytes, which only make the match longer
+    TrailingIgnores(usize),
+    /// The Pattern is shorter than [LintWarning::MIN_LEN]
+    TrivialLength,
+    /// The Pattern is less specific than [LintWarning::MIN_SPECIFICITY_BITS], see
+    /// [Pattern::specificity]
+    LowSpecificity { bits: f64 },
+    /// No run of consecutive literals reaches [LintWarning::MIN_LITERAL_RUN], so scans can't skip
+    /// ahead to candidate windows
+    ShortLiteralRuns { longest: usize },
+    /// The Pattern is longer than the data it is meant for, see [Pattern::lint_for_len]
+    LongerThanHaystack { len: usize, haystack_len: usize },
+}
+
+impl LintWarning {
+    /// Patterns with fewer elements are reported as [LintWarning::TrivialLength]
+    pub const MIN_LEN: usize = 2;
+    /// Patterns with fewer bits are reported as [LintWarning::LowSpecificity]  
+    /// 32 bits mean one random hit per 4 GiB of uniformly random data
+    pub const MIN_SPECIFICITY_BITS: f64 = 32.0;
+    /// Patterns whose longest literal run is shorter are reported as
+    /// [LintWarning::ShortLiteralRuns]
+    pub const MIN_LITERAL_RUN: usize = 2;
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintWarning::AllWildcards => {
+                f.write_str("The pattern only contains wildcards and matches anything")
+            }
+            LintWarning::LeadingIgnores(count) => write!(
+                f,
+                "The pattern starts with {count} ignored bytes, which only shift the match offset"
+            ),
+            LintWarning::TrailingIgnores(count) => write!(
+                f,
+                "The pattern ends with {count} ignored bytes, which only make the match longer"
+            ),
+            LintWarning::TrivialLength => write!(
+                f,
+                "The pattern is shorter than {} bytes and will match almost everywhere",
+                LintWarning::MIN_LEN
+            ),
+            LintWarning::LowSpecificity { bits } => write!(
+                f,
+                "The pattern only has {bits} bits of specificity, at least {} are recommended",
+                LintWarning::MIN_SPECIFICITY_BITS
+            ),
+            LintWarning::ShortLiteralRuns { longest } => write!(
+                f,
+                "The longest run of literal bytes is {longest} bytes long, at least {} make scans faster",
+                LintWarning::MIN_LITERAL_RUN
+            ),
+            LintWarning::LongerThanHaystack { len, haystack_len } => write!(
+                f,
+                "The pattern is {len} bytes long and can't match {haystack_len} bytes of data"
+            ),
+        }
+    }
+}
+
+impl Pattern {
+    /// Checks the Pattern for likely mistakes, empty if there are none
+    ///
+    /// The thresholds are constants of [LintWarning]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{LintWarning, Pattern};
+    /// let pattern = Pattern::new("__ __ 48 8B ?? ?? ?? ??").unwrap();
+    /// let warnings = pattern.lint();
+    /// assert_eq!(warnings[0], LintWarning::LeadingIgnores(2));
+    /// assert_eq!(
+    ///     warnings[0].to_string(),
+    ///     "The pattern starts with 2 ignored bytes, which only shift the match offset"
+    /// );
+    /// ```
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+        let is_ignore = |element: &&PatternElement| **element == PatternElement::Ignore;
+        let leading = self.data.iter().take_while(is_ignore).count();
+        let literals = self
+            .data
+            .iter()
+            .filter(|element| matches!(element, PatternElement::Literal(_)))
+            .count();
+
+        if self.len < LintWarning::MIN_LEN {
+            warnings.push(LintWarning::TrivialLength);
+        }
+        if self.len > 0 && literals == 0 {
+            warnings.push(LintWarning::AllWildcards);
+            return warnings;
+        }
+        if leading > 0 {
+            warnings.push(LintWarning::LeadingIgnores(leading));
+        }
+        let trailing = self.data.iter().rev().take_while(is_ignore).count();
+        if trailing > 0 {
+            warnings.push(LintWarning::TrailingIgnores(trailing));
+        }
+        let bits = self.specificity();
+        if literals > 0 && bits < LintWarning::MIN_SPECIFICITY_BITS {
+            warnings.push(LintWarning::LowSpecificity { bits });
+        }
+        let longest = self
+            .data
+            .split(|element| !matches!(element, PatternElement::Literal(_)))
+            .map(|run| run.len())
+            .max()
+            .unwrap_or(0);
+        if literals > 0 && longest < LintWarning::MIN_LITERAL_RUN {
+            warnings.push(LintWarning::ShortLiteralRuns { longest });
+        }
+        warnings
+    }
+
+    /// Same as [Pattern::lint], but also warns if the Pattern can't fit into `haystack_len` bytes,
+    /// e.g. the size of the files it will be used on
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{LintWarning, Pattern};
+    /// let pattern = Pattern::new("4D 5A 90 00 03 00").unwrap();
+    /// assert!(pattern.lint_for_len(64).is_empty());
+    /// assert_eq!(
+    ///     pattern.lint_for_len(4),
+    ///     vec![LintWarning::LongerThanHaystack { len: 6, haystack_len: 4 }]
+    /// );
+    /// ```
+    pub fn lint_for_len(&self, haystack_len: usize) -> Vec<LintWarning> {
+        let mut warnings = self.lint();
+        if self.len > haystack_len {
+            warnings.push(LintWarning::LongerThanHaystack {
+                len: self.len,
+                haystack_len,
+            });
+        }
+        warnings
+    }
+}
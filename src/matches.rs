@@ -0,0 +1,402 @@
+use crate::{Captures, Cow, Vec};
+use core::fmt;
+use core::ops::Range;
+
+/// A single occurrence of a [Pattern](crate::Pattern) inside a haystack
+///
+/// All offsets are absolute indices into the searched haystack
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Match {
+    start: usize,
+    end: usize,
+    captures: Captures,
+    window: Option<Vec<u8>>,
+}
+
+impl Match {
+    pub(crate) fn new(start: usize, end: usize, captures: Captures) -> Match {
+        Self {
+            start,
+            end,
+            captures,
+            window: None,
+        }
+    }
+
+    /// Stores a copy of the matched bytes, see [MatchOptions::copy_window](crate::MatchOptions::copy_window)
+    pub(crate) fn with_window(mut self, haystack: &[u8]) -> Match {
+        self.window = Some(haystack[self.range()].to_vec());
+        self
+    }
+
+    /// Moves the match by `delta` bytes, for results of a search on a sub slice
+    pub(crate) fn shifted(mut self, delta: usize) -> Match {
+        self.start += delta;
+        self.end += delta;
+        self.captures.shift(delta);
+        self
+    }
+
+    /// Index of the first byte covered by the match
+    #[inline(always)]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Index one past the last byte covered by the match
+    #[inline(always)]
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The covered bytes as a `Range`, usable to index the haystack directly
+    #[inline(always)]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// The values matched by placeholders (`??`), see [Captures]
+    #[inline(always)]
+    pub fn captures(&self) -> &Captures {
+        &self.captures
+    }
+
+    /// Every byte covered by the match, including the ones at ignored (`__`) positions
+    ///
+    /// Uses the copy stored by [MatchOptions::copy_window](crate::MatchOptions::copy_window) if there is one,
+    /// otherwise the bytes are borrowed from `haystack`, which has to be the searched haystack  
+    /// `None` if there is no copy and `haystack` is too short to contain the match
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA __ ??").unwrap();
+    /// let data = vec![0x00, 0xAA, 0x12, 0x34];
+    /// let found = pattern.find_with(&data, &MatchOptions::new());
+    /// assert_eq!(found[0].window(&data), Some(&[0xAA, 0x12, 0x34][..]));
+    /// assert_eq!(found[0].byte_at(&data, 1), Some(0x12));
+    /// assert_eq!(found[0].window(&data[..2]), None);
+    /// ```
+    pub fn window<'a>(&'a self, haystack: &'a [u8]) -> Option<&'a [u8]> {
+        match &self.window {
+            Some(window) => Some(window),
+            None => haystack.get(self.range()),
+        }
+    }
+
+    /// The bytes covered by the match, borrowed from `haystack` which has to be the searched haystack
+    ///
+    /// Always exactly [Match::len] bytes, unlike [Match::window] this never uses a stored copy  
+    /// `None` if `haystack` is too short to contain the match
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("DE AD __ EF").unwrap();
+    /// let data = vec![0x00, 0xDE, 0xAD, 0xBE, 0xEF, 0x00];
+    /// let found = pattern.find_with(&data, &MatchOptions::new());
+    /// // 64 bit FNV-1a of the matched bytes
+    /// let digest = found[0]
+    ///     .as_bytes(&data)
+    ///     .unwrap()
+    ///     .iter()
+    ///     .fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3));
+    /// assert_eq!(digest, 0x277045760cdd0993);
+    /// ```
+    #[inline(always)]
+    pub fn as_bytes<'h>(&self, haystack: &'h [u8]) -> Option<&'h [u8]> {
+        haystack.get(self.range())
+    }
+
+    /// The copy of the matched bytes, only present when searching with [MatchOptions::copy_window](crate::MatchOptions::copy_window)
+    #[inline(always)]
+    pub fn stored_window(&self) -> Option<&[u8]> {
+        self.window.as_deref()
+    }
+
+    /// The byte at `pattern_index` of the [window](Match::window), `None` if the index is past the end of the match
+    /// or there is no window
+    #[inline(always)]
+    pub fn byte_at(&self, haystack: &[u8], pattern_index: usize) -> Option<u8> {
+        self.window(haystack)?.get(pattern_index).copied()
+    }
+
+    /// Copies `N` bytes starting at `pattern_index` of the [window](Match::window)  
+    /// `None` if they would run past the end of the match or there is no window
+    ///
+    /// The `read_*` methods interpret these bytes as integers and floats, they can start at any position of the
+    /// Pattern and aren't limited to placeholders
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// // A RIFF "data" chunk header followed by its little endian size
+    /// let pattern = Pattern::new("64 61 74 61 ?? ?? ?? ??").unwrap();
+    /// let data = b"WAVEdata\x10\x27\x00\x00".to_vec();
+    /// let header = &pattern.find_with(&data, &MatchOptions::new())[0];
+    /// assert_eq!(header.read_u32_le(&data, 4), Some(0x10 + 0x27 * 256));
+    /// assert_eq!(header.read_u32_le(&data, 5), None);
+    /// assert_eq!(header.read_array::<2>(&data, 0), Some(*b"da"));
+    /// ```
+    pub fn read_array<const N: usize>(
+        &self,
+        haystack: &[u8],
+        pattern_index: usize,
+    ) -> Option<[u8; N]> {
+        let window = self.window(haystack)?;
+        let bytes = window.get(pattern_index..pattern_index.checked_add(N)?)?;
+        bytes.try_into().ok()
+    }
+
+    /// Reads a little endian `u16` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_u16_le(&self, haystack: &[u8], pattern_index: usize) -> Option<u16> {
+        self.read_array(haystack, pattern_index)
+            .map(u16::from_le_bytes)
+    }
+
+    /// Reads a big endian `u16` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_u16_be(&self, haystack: &[u8], pattern_index: usize) -> Option<u16> {
+        self.read_array(haystack, pattern_index)
+            .map(u16::from_be_bytes)
+    }
+
+    /// Reads a little endian `u32` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_u32_le(&self, haystack: &[u8], pattern_index: usize) -> Option<u32> {
+        self.read_array(haystack, pattern_index)
+            .map(u32::from_le_bytes)
+    }
+
+    /// Reads a big endian `u32` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_u32_be(&self, haystack: &[u8], pattern_index: usize) -> Option<u32> {
+        self.read_array(haystack, pattern_index)
+            .map(u32::from_be_bytes)
+    }
+
+    /// Reads a little endian `u64` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_u64_le(&self, haystack: &[u8], pattern_index: usize) -> Option<u64> {
+        self.read_array(haystack, pattern_index)
+            .map(u64::from_le_bytes)
+    }
+
+    /// Reads a big endian `u64` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_u64_be(&self, haystack: &[u8], pattern_index: usize) -> Option<u64> {
+        self.read_array(haystack, pattern_index)
+            .map(u64::from_be_bytes)
+    }
+
+    /// Reads a little endian `i16` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_i16_le(&self, haystack: &[u8], pattern_index: usize) -> Option<i16> {
+        self.read_array(haystack, pattern_index)
+            .map(i16::from_le_bytes)
+    }
+
+    /// Reads a big endian `i16` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_i16_be(&self, haystack: &[u8], pattern_index: usize) -> Option<i16> {
+        self.read_array(haystack, pattern_index)
+            .map(i16::from_be_bytes)
+    }
+
+    /// Reads a little endian `i32` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_i32_le(&self, haystack: &[u8], pattern_index: usize) -> Option<i32> {
+        self.read_array(haystack, pattern_index)
+            .map(i32::from_le_bytes)
+    }
+
+    /// Reads a big endian `i32` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_i32_be(&self, haystack: &[u8], pattern_index: usize) -> Option<i32> {
+        self.read_array(haystack, pattern_index)
+            .map(i32::from_be_bytes)
+    }
+
+    /// Reads a little endian `i64` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_i64_le(&self, haystack: &[u8], pattern_index: usize) -> Option<i64> {
+        self.read_array(haystack, pattern_index)
+            .map(i64::from_le_bytes)
+    }
+
+    /// Reads a big endian `i64` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_i64_be(&self, haystack: &[u8], pattern_index: usize) -> Option<i64> {
+        self.read_array(haystack, pattern_index)
+            .map(i64::from_be_bytes)
+    }
+
+    /// Reads a little endian `f32` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_f32_le(&self, haystack: &[u8], pattern_index: usize) -> Option<f32> {
+        self.read_array(haystack, pattern_index)
+            .map(f32::from_le_bytes)
+    }
+
+    /// Reads a big endian `f32` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_f32_be(&self, haystack: &[u8], pattern_index: usize) -> Option<f32> {
+        self.read_array(haystack, pattern_index)
+            .map(f32::from_be_bytes)
+    }
+
+    /// Reads a little endian `f64` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_f64_le(&self, haystack: &[u8], pattern_index: usize) -> Option<f64> {
+        self.read_array(haystack, pattern_index)
+            .map(f64::from_le_bytes)
+    }
+
+    /// Reads a big endian `f64` starting at `pattern_index` of the [window](Match::window)
+    pub fn read_f64_be(&self, haystack: &[u8], pattern_index: usize) -> Option<f64> {
+        self.read_array(haystack, pattern_index)
+            .map(f64::from_be_bytes)
+    }
+
+    /// Resolves a relative 32 bit displacement, like the targets of `E8`/`E9` calls and jumps or RIP relative `8B 05` loads
+    ///
+    /// Reads a signed little endian displacement at `pattern_index` of the [window](Match::window) and returns
+    /// `start + next_instr_offset + displacement`, where `next_instr_offset` is the offset of the following instruction
+    /// relative to the start of the match  
+    /// `None` if the displacement runs past the end of the match or the target would be negative or overflow
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("E8 ?? ?? ?? ??").unwrap();
+    /// let code = vec![0x90, 0x90, 0xE8, 0xF9, 0xFF, 0xFF, 0xFF]; // call -7
+    /// let call = &pattern.find_with(&code, &MatchOptions::new())[0];
+    /// assert_eq!(call.rel32_target(&code, 1, 5), Some(0));
+    /// ```
+    pub fn rel32_target(
+        &self,
+        haystack: &[u8],
+        pattern_index: usize,
+        next_instr_offset: usize,
+    ) -> Option<u64> {
+        self.rel32_target_with_base(haystack, pattern_index, next_instr_offset, 0)
+    }
+
+    /// Same as [Match::rel32_target], but adds `image_base` to the result, e.g. to get a virtual address
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("48 8B 05 ?? ?? ?? ??").unwrap();
+    /// let code = vec![0x48, 0x8B, 0x05, 0x00, 0x10, 0x00, 0x00]; // mov rax, [rip + 0x1000]
+    /// let load = &pattern.find_with(&code, &MatchOptions::new())[0];
+    /// assert_eq!(load.rel32_target_with_base(&code, 3, 7, 0x140000000), Some(0x140001007));
+    /// ```
+    pub fn rel32_target_with_base(
+        &self,
+        haystack: &[u8],
+        pattern_index: usize,
+        next_instr_offset: usize,
+        image_base: u64,
+    ) -> Option<u64> {
+        let displacement = self.read_i32_le(haystack, pattern_index)?;
+        let next = image_base
+            .checked_add(u64::try_from(self.start).ok()?)?
+            .checked_add(u64::try_from(next_instr_offset).ok()?)?;
+        next.checked_add_signed(displacement.into())
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// A [Match] together with the bytes around it, returned by [Pattern::find_with_context](crate::Pattern::find_with_context)
+///
+/// The context is borrowed from the haystack when it is still available, and owned otherwise
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ContextMatch<'h> {
+    pub matched: Match,
+    /// Up to `before` bytes preceding the match, fewer at the start of the haystack
+    pub pre: Cow<'h, [u8]>,
+    /// Up to `after` bytes following the match, fewer at the end of the haystack
+    pub post: Cow<'h, [u8]>,
+}
+
+/// Number of hex digits used for offsets into data of `len` bytes, 8 below 4 GiB and 16 otherwise
+fn offset_width(len: usize) -> usize {
+    if (len as u64) < 1 << 32 {
+        8
+    } else {
+        16
+    }
+}
+
+/// Formats as `0x00004a10..0x00004a18 captures=[42, 7f]`
+///
+/// The match doesn't know the length of its haystack, so both offsets are padded to 8 hex digits,
+/// or to 16 if the match ends past 4 GiB
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let w = offset_width(self.end) + 2;
+        write!(f, "{:#0w$x}..{:#0w$x} captures=[", self.start, self.end)?;
+        for (i, capture) in self.captures.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{:02x}", capture.value)?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// A one line summary of a scan, like `2 matches of pattern X in 4096 bytes (first at 0x00000010, last at 0x00000800)`
+///
+/// The width of the offsets depends on the size of the haystack
+///
+/// # Example:
+/// ```
+/// # use binmatch::{MatchOptions, Pattern, ScanSummary};
+/// let pattern = Pattern::new("AA").unwrap();
+/// let data = vec![0x00, 0xAA, 0xAA];
+/// let found = pattern.find_with(&data, &MatchOptions::new());
+/// assert_eq!(
+///     ScanSummary::new("marker", &found, data.len()).to_string(),
+///     "2 matches of pattern marker in 3 bytes (first at 0x00000001, last at 0x00000002)"
+/// );
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ScanSummary<'a> {
+    name: &'a str,
+    matches: &'a [Match],
+    haystack_len: usize,
+}
+
+impl<'a> ScanSummary<'a> {
+    pub fn new(name: &'a str, matches: &'a [Match], haystack_len: usize) -> ScanSummary<'a> {
+        Self {
+            name,
+            matches,
+            haystack_len,
+        }
+    }
+}
+
+impl fmt::Display for ScanSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.matches.len();
+        write!(
+            f,
+            "{} {} of pattern {} in {} bytes",
+            count,
+            if count == 1 { "match" } else { "matches" },
+            self.name,
+            self.haystack_len
+        )?;
+        let first = self.matches.iter().map(Match::start).min();
+        let last = self.matches.iter().map(Match::start).max();
+        if let (Some(first), Some(last)) = (first, last) {
+            let w = offset_width(self.haystack_len) + 2;
+            write!(
+                f,
+                " (first at {:#0w$x}, last at {:#0w$x})",
+                first,
+                last,
+                w = w
+            )?;
+        }
+        Ok(())
+    }
+}
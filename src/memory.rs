@@ -0,0 +1,360 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{vec, Captures, FileMatch, Pattern, String, Vec};
+use core::fmt;
+use core::ops::Range;
+
+/// Number of bytes read from a [MemorySource] at once
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Why a [MemorySource] could not be read
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MemError {
+    /// The page containing `address` can't be read, e.g. a guard page, it is skipped by the scan
+    #[cfg_attr(feature = "std", error("The memory at {address:#x} can't be read"))]
+    Unreadable { address: u64 },
+    /// The source itself failed, e.g. a lost debugger connection, which aborts the scan
+    #[cfg_attr(
+        feature = "std",
+        error("Reading the memory at {address:#x} failed: {message}")
+    )]
+    Failed { address: u64, message: String },
+}
+
+/// Memory that can be searched by [Pattern::find_in_source], e.g. a debugger connection, a
+/// hypervisor introspection API or an emulator
+///
+/// # Example:
+/// ```
+/// # use binmatch::{MemError, MemoryRegion, MemorySource, Pattern};
+/// /// 16 bytes of ROM mapped at 0x8000
+/// struct Rom([u8; 16]);
+///
+/// impl MemorySource for Rom {
+///     fn read(&mut self, address: u64, buf: &mut [u8]) -> Result<usize, MemError> {
+///         let offset = address.checked_sub(0x8000).ok_or(MemError::Unreadable { address })?;
+///         let rest = self.0.get(offset as usize..).ok_or(MemError::Unreadable { address })?;
+///         let len = buf.len().min(rest.len());
+///         buf[..len].copy_from_slice(&rest[..len]);
+///         Ok(len)
+///     }
+///
+///     fn regions(&self) -> Vec<MemoryRegion> {
+///         vec![(0x8000..0x8010).into()]
+///     }
+/// }
+///
+/// let mut rom = Rom([0x00, 0x00, 0xA9, 0x42, 0x8D, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+/// let found = Pattern::new("A9 ?? 8D").unwrap().find_in_source(&mut rom).unwrap();
+/// assert_eq!(found[0].address(), 0x8002);
+/// ```
+pub trait MemorySource {
+    /// Reads up to `buf.len()` bytes at `address` and returns how many were read
+    ///
+    /// Short reads are fine, the rest is requested again. Returning 0 or [MemError::Unreadable]
+    /// skips the page containing `address`, [MemError::Failed] aborts the scan
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> Result<usize, MemError>;
+
+    /// The regions to scan, in ascending order, regions without read permission are skipped
+    fn regions(&self) -> Vec<MemoryRegion>;
+
+    /// The granularity at which unreadable memory is skipped
+    fn page_size(&self) -> u64 {
+        4096
+    }
+}
+
+/// The access rights of a [MemoryRegion]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Permissions {
+    read: bool,
+    write: bool,
+    execute: bool,
+    shared: bool,
+}
+
+impl Permissions {
+    pub const fn new(read: bool, write: bool, execute: bool, shared: bool) -> Permissions {
+        Self {
+            read,
+            write,
+            execute,
+            shared,
+        }
+    }
+
+    #[inline(always)]
+    pub fn read(&self) -> bool {
+        self.read
+    }
+
+    #[inline(always)]
+    pub fn write(&self) -> bool {
+        self.write
+    }
+
+    #[inline(always)]
+    pub fn execute(&self) -> bool {
+        self.execute
+    }
+
+    /// Whether the region is shared with other processes instead of copied on write
+    #[inline(always)]
+    pub fn shared(&self) -> bool {
+        self.shared
+    }
+}
+
+/// Formats like `/proc/<pid>/maps`, e.g. `r-xp`
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |set: bool, c: char| if set { c } else { '-' };
+        write!(
+            f,
+            "{}{}{}{}",
+            flag(self.read, 'r'),
+            flag(self.write, 'w'),
+            flag(self.execute, 'x'),
+            if self.shared { 's' } else { 'p' }
+        )
+    }
+}
+
+/// A mapped region of an address space, e.g. of a process or a [MemorySource]
+///
+/// A plain range of addresses converts into a readable region without a path
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct MemoryRegion {
+    start: u64,
+    end: u64,
+    permissions: Permissions,
+    path: Option<String>,
+}
+
+impl MemoryRegion {
+    pub fn new(range: Range<u64>, permissions: Permissions) -> MemoryRegion {
+        Self {
+            start: range.start,
+            end: range.end,
+            permissions,
+            path: None,
+        }
+    }
+
+    /// Sets the mapped file or a pseudo name like `[heap]`
+    pub fn with_path(mut self, path: impl Into<String>) -> MemoryRegion {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Address of the first byte of the region
+    #[inline(always)]
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Address one past the last byte of the region
+    #[inline(always)]
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    #[inline(always)]
+    pub fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    /// The mapped file or a pseudo name like `[heap]`, `None` for anonymous mappings
+    #[inline(always)]
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+}
+
+/// A match inside a [MemoryRegion], see [Pattern::find_in_source]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct RegionMatch {
+    region: MemoryRegion,
+    found: FileMatch,
+}
+
+impl RegionMatch {
+    /// Virtual address of the first byte covered by the match
+    #[inline(always)]
+    pub fn address(&self) -> u64 {
+        self.found.offset()
+    }
+
+    /// The region containing the match
+    #[inline(always)]
+    pub fn region(&self) -> &MemoryRegion {
+        &self.region
+    }
+
+    /// Every byte covered by the match, as read during the scan
+    #[inline(always)]
+    pub fn bytes(&self) -> &[u8] {
+        self.found.bytes()
+    }
+
+    /// The values matched by placeholders (`??`), relative to [RegionMatch::bytes]
+    #[inline(always)]
+    pub fn captures(&self) -> &Captures {
+        self.found.captures()
+    }
+}
+
+impl From<Range<u64>> for MemoryRegion {
+    fn from(range: Range<u64>) -> MemoryRegion {
+        MemoryRegion::new(range, Permissions::new(true, false, false, false))
+    }
+}
+
+/// A [MemorySource] reading from a slice mapped at `base`
+///
+/// # Example:
+/// ```
+/// # use binmatch::{Pattern, SliceSource};
+/// let data = [0x90, 0xC3];
+/// let found = Pattern::new("C3").unwrap().find_in_source(&mut SliceSource::new(0x1000, &data));
+/// assert_eq!(found.unwrap()[0].address(), 0x1001);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SliceSource<'a> {
+    base: u64,
+    data: &'a [u8],
+}
+
+impl<'a> SliceSource<'a> {
+    pub fn new(base: u64, data: &'a [u8]) -> SliceSource<'a> {
+        Self { base, data }
+    }
+}
+
+impl MemorySource for SliceSource<'_> {
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> Result<usize, MemError> {
+        let rest = address
+            .checked_sub(self.base)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .and_then(|offset| self.data.get(offset..))
+            .ok_or(MemError::Unreadable { address })?;
+        let len = buf.len().min(rest.len());
+        buf[..len].copy_from_slice(&rest[..len]);
+        Ok(len)
+    }
+
+    fn regions(&self) -> Vec<MemoryRegion> {
+        vec![(self.base..self.base + self.data.len() as u64).into()]
+    }
+}
+
+/// <div class="warning"> Only available using the <code>std</code> feature </div>
+///
+/// A [MemorySource] reading from a seekable reader, e.g. a file or a memory dump, where the
+/// address is the position in the reader
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ReaderSource<R> {
+    reader: R,
+    len: u64,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read + std::io::Seek> ReaderSource<R> {
+    /// # Returns an Error when:
+    ///
+    /// - The length of `reader` can't be determined by seeking to its end
+    pub fn new(mut reader: R) -> std::io::Result<ReaderSource<R>> {
+        let len = reader.seek(std::io::SeekFrom::End(0))?;
+        Ok(Self { reader, len })
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read + std::io::Seek> MemorySource for ReaderSource<R> {
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> Result<usize, MemError> {
+        let failed = |err: std::io::Error| MemError::Failed {
+            address,
+            message: err.to_string(),
+        };
+        self.reader
+            .seek(std::io::SeekFrom::Start(address))
+            .map_err(failed)?;
+        loop {
+            match self.reader.read(buf) {
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                result => return result.map_err(failed),
+            }
+        }
+    }
+
+    fn regions(&self) -> Vec<MemoryRegion> {
+        vec![(0..self.len).into()]
+    }
+}
+
+impl Pattern {
+    /// Searches every readable region of `source`, see [MemorySource]
+    ///
+    /// Every region is read in chunks and searched like a stream, so matches crossing chunks are
+    /// found exactly once  
+    /// Unreadable pages split a region, matches never span them
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `source` returns [MemError::Failed]
+    pub fn find_in_source<S: MemorySource>(
+        &self,
+        source: &mut S,
+    ) -> Result<Vec<RegionMatch>, MemError> {
+        self.find_in_source_with_buffer(source, &mut vec![0; CHUNK_SIZE])
+    }
+
+    /// Same as [Pattern::find_in_source], reading through `chunk`
+    pub(crate) fn find_in_source_with_buffer<S: MemorySource>(
+        &self,
+        source: &mut S,
+        chunk: &mut [u8],
+    ) -> Result<Vec<RegionMatch>, MemError> {
+        let page_size = source.page_size().max(1);
+        let mut matches = Vec::new();
+        for region in source.regions() {
+            if !region.permissions.read {
+                continue;
+            }
+            let mut found = Vec::new();
+            let mut matcher = self.stream_matcher_at(region.start);
+            let mut address = region.start;
+            while address < region.end {
+                let len = (region.end - address).min(chunk.len() as u64) as usize;
+                match source.read(address, &mut chunk[..len]) {
+                    Ok(read) if read > 0 => {
+                        let read = read.min(len);
+                        found.extend(matcher.push(&chunk[..read]));
+                        address += read as u64;
+                    }
+                    Ok(_) | Err(MemError::Unreadable { .. }) => {
+                        address = (address - address % page_size)
+                            .saturating_add(page_size)
+                            .min(region.end);
+                        found.extend(matcher.finish());
+                        matcher = self.stream_matcher_at(address);
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            found.extend(matcher.finish());
+            matches.extend(found.into_iter().map(|found| RegionMatch {
+                region: region.clone(),
+                found,
+            }));
+        }
+        Ok(matches)
+    }
+}
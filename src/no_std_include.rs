@@ -1,2 +0,0 @@
-extern crate alloc;
-use alloc::{string::String, vec::Vec};
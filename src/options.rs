@@ -0,0 +1,382 @@
+use crate::{Capture, Captures, Match, Pattern, PatternElement, Vec};
+use core::ops::Range;
+
+/// Configuration for [Pattern::find_with]
+///
+/// Options are set through chained builder methods, the default matches exactly like [Pattern::find_matches_with_index]
+///
+/// # Example:
+/// ```
+/// # use binmatch::{MatchOptions, Pattern};
+/// let pattern = Pattern::new("12 34 ??").unwrap();
+/// let data = vec![0x12, 0xFF, 0xFF, 0x34, 0xFF, 0x56];
+/// let options = MatchOptions::new().skip_haystack_bytes(&[0xFF]);
+/// let matches = pattern.find_with(&data, &options);
+/// assert_eq!(matches[0].range(), 0..6);
+/// assert_eq!(matches[0].captures(), &[(0x56, 5)]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct MatchOptions {
+    skip_bytes: [u64; 4],
+    ascii_case_insensitive: bool,
+    exclude: Vec<Range<usize>>, // Sorted, non overlapping and non adjacent
+    block_size: Option<usize>,
+    swap_width: usize,
+    non_overlapping: bool,
+    align: usize,
+    max_matches: Option<usize>,
+    range: Option<Range<usize>>,
+    copy_window: bool,
+}
+
+impl MatchOptions {
+    pub fn new() -> MatchOptions {
+        Self::default()
+    }
+
+    /// After a match, continues searching after its last byte instead of at the next offset
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA AA").unwrap();
+    /// let data = vec![0xAA, 0xAA, 0xAA, 0xAA, 0xAA];
+    /// assert_eq!(pattern.find_with(&data, &MatchOptions::new()).len(), 4);
+    /// assert_eq!(pattern.find_with(&data, &MatchOptions::new().non_overlapping()).len(), 2);
+    /// ```
+    pub fn non_overlapping(mut self) -> MatchOptions {
+        self.non_overlapping = true;
+        self
+    }
+
+    /// Only considers matches starting at a multiple of `alignment`, 0 and 1 allow every offset
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA").unwrap();
+    /// let data = vec![0x00, 0xAA, 0x00, 0x00, 0xAA];
+    /// let matches = pattern.find_with(&data, &MatchOptions::new().align(4));
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(matches[0].start(), 4);
+    /// ```
+    pub fn align(mut self, alignment: usize) -> MatchOptions {
+        self.align = alignment;
+        self
+    }
+
+    /// Stops searching once `max_matches` matches have been found
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA").unwrap();
+    /// let data = vec![0xAA; 100];
+    /// assert_eq!(pattern.find_with(&data, &MatchOptions::new().max_matches(3)).len(), 3);
+    /// ```
+    pub fn max_matches(mut self, max_matches: usize) -> MatchOptions {
+        self.max_matches = Some(max_matches);
+        self
+    }
+
+    /// Only searches the bytes in `range`, a match has to lie completely inside it  
+    /// The reported offsets are still relative to the start of the whole haystack
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA ??").unwrap();
+    /// let data = vec![0xAA, 0x01, 0xAA, 0x02, 0xAA, 0x03];
+    /// let matches = pattern.find_with(&data, &MatchOptions::new().range(1..5));
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(matches[0].captures(), &[(0x02, 3)]);
+    /// ```
+    pub fn range(mut self, range: Range<usize>) -> MatchOptions {
+        self.range = Some(range);
+        self
+    }
+
+    /// Stores a copy of every matched window in the [Match], so all of its bytes stay accessible through
+    /// [Match::window] and [Match::byte_at] after the haystack is gone
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA __").unwrap();
+    /// let found = pattern.find_with(&[0xAA, 0x42], &MatchOptions::new().copy_window());
+    /// assert_eq!(found[0].stored_window(), Some(&[0xAA, 0x42][..]));
+    /// assert_eq!(found[0].byte_at(&[], 1), Some(0x42));
+    /// ```
+    pub fn copy_window(mut self) -> MatchOptions {
+        self.copy_window = true;
+        self
+    }
+
+    /// Treats every byte in `bytes` as padding that is transparently skipped in the haystack  
+    /// The Pattern then matches the data as if the padding wasn't there, e.g. for dumps with interleaved spare bytes
+    ///
+    /// - A match always starts on a byte that isn't skipped, the reported offsets refer to the original haystack
+    /// - Skipped bytes are still matched by a literal of the same value, so a Pattern may contain the padding byte itself
+    /// - Placeholders and ignored bytes never consume a skipped byte
+    pub fn skip_haystack_bytes(mut self, bytes: &[u8]) -> MatchOptions {
+        for &byte in bytes {
+            self.skip_bytes[byte as usize / 64] |= 1 << (byte % 64);
+        }
+        self
+    }
+
+    /// Literals in the ASCII letter range match both upper and lower case  
+    /// Every other byte still has to match exactly, captures always report the byte found in the haystack
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("47 45 54 20").unwrap(); // "GET "
+    /// let options = MatchOptions::new().ascii_case_insensitive();
+    /// assert_eq!(pattern.find_with(b"get GeT gEt_", &options).len(), 2);
+    /// ```
+    pub fn ascii_case_insensitive(mut self) -> MatchOptions {
+        self.ascii_case_insensitive = true;
+        self
+    }
+
+    /// Never reports a match whose extent intersects one of `ranges`, and doesn't scan inside them at all  
+    /// Can be called multiple times, the ranges may overlap and don't need to be sorted
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA").unwrap();
+    /// let data = vec![0xAA, 0xAA, 0xAA, 0xAA];
+    /// let options = MatchOptions::new().exclude(&[2..3, 0..1]);
+    /// let starts: Vec<usize> = pattern.find_with(&data, &options).iter().map(|m| m.start()).collect();
+    /// assert_eq!(starts, vec![1, 3]);
+    /// ```
+    pub fn exclude(mut self, ranges: &[Range<usize>]) -> MatchOptions {
+        self.exclude
+            .extend(ranges.iter().filter(|r| r.start < r.end).cloned());
+        self.exclude.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(self.exclude.len());
+        for range in self.exclude.drain(..) {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        self.exclude = merged;
+        self
+    }
+
+    /// Reports only the first match starting in each `block_size` aligned block, then continues at the next block  
+    /// The matches still carry their exact offsets, a `block_size` of 0 is treated as 1
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA").unwrap();
+    /// let data = vec![0x00, 0xAA, 0xAA, 0xAA, 0xAA, 0x00, 0xAA];
+    /// let options = MatchOptions::new().one_per_block(4);
+    /// let starts: Vec<usize> = pattern.find_with(&data, &options).iter().map(|m| m.start()).collect();
+    /// assert_eq!(starts, vec![1, 4]);
+    /// ```
+    pub fn one_per_block(mut self, block_size: usize) -> MatchOptions {
+        self.block_size = Some(block_size.max(1));
+        self
+    }
+
+    /// Matches against the byte swapped interpretation of each `width` aligned word, without copying the haystack  
+    /// Useful for dumps of big endian devices when the Pattern is written in little endian order (or the other way around)
+    ///
+    /// - Match starts are positions in the swapped view, which cover the same words as the original haystack
+    /// - Captures report the index the byte was actually read from, so `haystack[index] == value` always holds
+    /// - Placeholders and ignored bytes inside a word refer to the swapped position, just like literals
+    /// - A trailing partial word is not part of the view
+    /// - [exclude](MatchOptions::exclude) ranges are haystack offsets, a match is dropped if any word it reads from overlaps one
+    /// - Widths of 0 and 1 leave the haystack as it is
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("12 34 56 ??").unwrap();
+    /// let data = vec![0x00, 0x00, 0x00, 0x00, 0x78, 0x56, 0x34, 0x12];
+    /// let matches = pattern.find_with(&data, &MatchOptions::new().swap_bytes(4));
+    /// assert_eq!(matches[0].start(), 4);
+    /// assert_eq!(matches[0].captures(), &[(0x78, 4)]);
+    /// ```
+    pub fn swap_bytes(mut self, width: usize) -> MatchOptions {
+        self.swap_width = width;
+        self
+    }
+
+    /// Whether the haystack bytes read for the view positions `view` overlap an excluded range  
+    /// When swapping bytes these are the whole words covering `view`
+    fn overlaps_excluded(&self, view: Range<usize>) -> bool {
+        let covered = if self.swap_width > 1 {
+            let start = view.start - view.start % self.swap_width;
+            start
+                ..view
+                    .end
+                    .div_ceil(self.swap_width)
+                    .saturating_mul(self.swap_width)
+        } else {
+            view
+        };
+        let first = self
+            .exclude
+            .partition_point(|range| range.end <= covered.start);
+        self.exclude
+            .get(first)
+            .is_some_and(|range| range.start < covered.end)
+    }
+
+    /// The byte seen at `position` of the (possibly byte swapped) view and its index in the haystack
+    #[inline(always)]
+    fn view_byte(&self, haystack: &[u8], position: usize) -> Option<(u8, usize)> {
+        let index = if self.swap_width > 1 {
+            let offset = position % self.swap_width;
+            let word = position - offset;
+            if word.checked_add(self.swap_width)? > haystack.len() {
+                return None;
+            }
+            word + self.swap_width - 1 - offset
+        } else {
+            position
+        };
+        haystack.get(index).map(|&byte| (byte, index))
+    }
+
+    #[inline(always)]
+    fn literal_matches(&self, expected: u8, actual: u8) -> bool {
+        if self.ascii_case_insensitive {
+            expected.eq_ignore_ascii_case(&actual)
+        } else {
+            expected == actual
+        }
+    }
+
+    /// Whether the plain fixed length verifier can be used
+    #[inline(always)]
+    fn is_plain_view(&self) -> bool {
+        !self.skips_any() && !self.ascii_case_insensitive && self.swap_width <= 1
+    }
+
+    #[inline(always)]
+    fn skips(&self, byte: u8) -> bool {
+        self.skip_bytes[byte as usize / 64] & (1 << (byte % 64)) != 0
+    }
+
+    #[inline(always)]
+    fn skips_any(&self) -> bool {
+        self.skip_bytes != [0; 4]
+    }
+}
+
+impl Pattern {
+    /// Finds all matches in the `haystack` using the given [MatchOptions]
+    ///
+    /// An empty Pattern never matches, like in [Pattern::find_match_starts]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchOptions, Pattern};
+    /// let pattern = Pattern::new("00 ??").unwrap();
+    /// let data = vec![0x00, 0x42, 0x00, 0x43];
+    /// let matches = pattern.find_with(&data, &MatchOptions::new());
+    /// assert_eq!(matches.len(), 2);
+    /// assert_eq!(matches[1].captures(), &[(0x43, 3)]);
+    /// ```
+    pub fn find_with(&self, haystack: &[u8], options: &MatchOptions) -> Vec<Match> {
+        let mut matches = Vec::new();
+        if self.len == 0 {
+            return matches;
+        }
+        let (mut start, end) = match &options.range {
+            Some(range) => (range.start, range.end.min(haystack.len())),
+            None => (0, haystack.len()),
+        };
+        let haystack = &haystack[..end];
+        let Some(last) = haystack.len().checked_sub(self.len) else {
+            return matches;
+        };
+        let max_matches = options.max_matches.unwrap_or(usize::MAX);
+        let mut excluded = options.exclude.iter().peekable();
+        while start <= last && matches.len() < max_matches {
+            while excluded.next_if(|range| range.end <= start).is_some() {}
+            let next_excluded = excluded.peek();
+            if let Some(range) = next_excluded.filter(|range| range.start <= start) {
+                start = range.end;
+                continue;
+            }
+            if options.align > 1 && start % options.align != 0 {
+                match start.div_ceil(options.align).checked_mul(options.align) {
+                    Some(aligned) => start = aligned,
+                    None => break,
+                }
+                continue;
+            }
+            let found = if options.is_plain_view() {
+                self.match_at_unchecked(haystack, start)
+            } else {
+                self.match_at_with(haystack, start, options)
+            };
+            if let Some(m) = found.filter(|m| !options.overlaps_excluded(m.range())) {
+                let next = if let Some(block_size) = options.block_size {
+                    (start / block_size + 1).saturating_mul(block_size)
+                } else if options.non_overlapping {
+                    m.end().max(start + 1)
+                } else {
+                    start + 1
+                };
+                matches.push(if options.copy_window {
+                    m.with_window(haystack)
+                } else {
+                    m
+                });
+                start = next;
+                continue;
+            }
+            start += 1;
+        }
+        matches
+    }
+
+    /// Verifies the Pattern at `start` honoring the view related options, the consumed length may exceed `self.len`
+    pub(crate) fn match_at_with(
+        &self,
+        haystack: &[u8],
+        start: usize,
+        options: &MatchOptions,
+    ) -> Option<Match> {
+        let mut position = start;
+        let mut captures = Vec::new();
+        for (index, expected) in self.data.iter().enumerate() {
+            let (mut actual, mut actual_index) = options.view_byte(haystack, position)?;
+            while options.skips(actual) && *expected != PatternElement::Literal(actual) {
+                if index == 0 {
+                    return None; // The match will be found starting at the next real byte instead
+                }
+                position += 1;
+                (actual, actual_index) = options.view_byte(haystack, position)?;
+            }
+            match expected {
+                PatternElement::Literal(expected) => {
+                    if !options.literal_matches(*expected, actual) {
+                        return None;
+                    }
+                }
+                PatternElement::Placeholder => captures.push(Capture {
+                    value: self.capture_pipeline.apply(captures.len(), actual),
+                    pattern_index: index,
+                    haystack_offset: actual_index,
+                }),
+                PatternElement::Ignore => (),
+            }
+            position += 1;
+        }
+        Some(Match::new(
+            start,
+            position,
+            Captures::new(captures, self.capture_names.clone()),
+        ))
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{Match, PatternSet};
+use std::panic::resume_unwind;
+use std::thread;
+
+/// Haystacks shorter than this per thread are split by Pattern instead of by chunk
+const MIN_CHUNK_LEN: usize = 64 * 1024;
+
+impl PatternSet {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [PatternSet::find_matches], but the scan is spread over all available threads
+    ///
+    /// Large haystacks are split into chunks that overlap by the length of the longest Pattern and
+    /// every thread scans one chunk for all Patterns, smaller haystacks are scanned completely by
+    /// every thread for a subset of the Patterns  
+    /// The result is the same as that of [PatternSet::find_matches], including the order
+    pub fn par_find_matches(&self, haystack: &[u8]) -> Vec<(usize, Match)> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        if threads > 1 && haystack.len() >= threads * MIN_CHUNK_LEN {
+            self.par_find_by_chunks(haystack, threads)
+        } else if threads > 1 && self.len() > 1 {
+            self.par_find_by_patterns(haystack, threads)
+        } else {
+            self.find_matches(haystack)
+        }
+    }
+
+    /// Every thread scans `haystack` for every `threads`-th Pattern
+    pub(crate) fn par_find_by_patterns(
+        &self,
+        haystack: &[u8],
+        threads: usize,
+    ) -> Vec<(usize, Match)> {
+        let threads = threads.clamp(1, self.len().max(1));
+        let mut matches: Vec<(usize, Match)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|first| {
+                    scope.spawn(move || {
+                        let indices: Vec<usize> = (first..self.len()).step_by(threads).collect();
+                        let subset = PatternSet::from_parts(
+                            indices
+                                .iter()
+                                .map(|&i| self.patterns()[i].clone())
+                                .collect(),
+                            indices
+                                .iter()
+                                .map(|&i| self.name(i).unwrap_or_default().to_string())
+                                .collect(),
+                            indices
+                                .iter()
+                                .map(|&i| self.priority(i).unwrap_or_default())
+                                .collect(),
+                        );
+                        subset
+                            .find_matches(haystack)
+                            .into_iter()
+                            .map(|(index, m)| (indices[index], m))
+                            .collect::<Vec<(usize, Match)>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|panic| resume_unwind(panic)))
+                .collect()
+        });
+        matches.sort_by_key(|(index, m)| (m.start(), *index));
+        matches
+    }
+
+    /// Every thread scans one of `threads` chunks of `haystack` for all Patterns
+    pub(crate) fn par_find_by_chunks(
+        &self,
+        haystack: &[u8],
+        threads: usize,
+    ) -> Vec<(usize, Match)> {
+        let chunk_len = haystack.len().div_ceil(threads.max(1)).max(1);
+        let overlap = self.patterns().iter().map(|p| p.len).max().unwrap_or(0);
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..haystack.len().div_ceil(chunk_len).max(1))
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        let start = chunk * chunk_len;
+                        let end = (start + chunk_len).min(haystack.len());
+                        let window = &haystack[start..(end + overlap).min(haystack.len())];
+                        // Starts in the overlap belong to the next chunk, except at the very end
+                        let last = end == haystack.len();
+                        self.find_matches(window)
+                            .into_iter()
+                            .filter(|(_, m)| m.start() < end - start || last)
+                            .map(|(index, m)| (index, m.shifted(start)))
+                            .collect::<Vec<(usize, Match)>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|panic| resume_unwind(panic)))
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{parse_token, BinmatchError, Pattern, PatternSource, Vec, ALLOWED_ALPHABET};
+
+/// A single problem in a pattern string, see [Pattern::parse_all_errors]
+///
+/// Positions are byte offsets into the original string, spaces included
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ParseError {
+    #[cfg_attr(
+        feature = "std",
+        error("invalid character {character:?} at position {position}")
+    )]
+    InvalidCharacter { character: char, position: usize },
+    #[cfg_attr(
+        feature = "std",
+        error("the digit at position {position} is missing its second digit")
+    )]
+    LoneNibble { position: usize },
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "mixed token \"{}{}\" at position {position}, `?` and `_` can only be paired with themselves",
+            .token[0],
+            .token[1]
+        )
+    )]
+    MixedToken { token: [char; 2], position: usize },
+}
+
+impl Pattern {
+    /// Same as [Pattern::new], but keeps going after an error and reports every problem at once
+    ///
+    /// Characters are paired up like in [Pattern::new] and every pair is checked on its own  
+    /// A missing digit is blamed on the last group of characters between spaces with an odd
+    /// length, which is then left out of the pairing so the pairs after it still line up  
+    /// The errors are ordered by position
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{ParseError, Pattern};
+    /// let errors = Pattern::parse_all_errors("48 8G ?? 0 ?5").unwrap_err();
+    /// assert_eq!(
+    ///     errors,
+    ///     vec![
+    ///         ParseError::InvalidCharacter { character: 'G', position: 4 },
+    ///         ParseError::LoneNibble { position: 9 },
+    ///         ParseError::MixedToken { token: ['?', '5'], position: 11 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn parse_all_errors(pattern: &str) -> Result<Pattern, Vec<ParseError>> {
+        let mut errors = Vec::new();
+        // Invalid characters keep their place, so a typo like `0X` doesn't shift the pairs after it
+        let mut chars = Vec::new();
+        // Start and length of the groups between spaces
+        let mut groups = Vec::new();
+        let mut group_len = 0;
+        for (position, char) in pattern.char_indices() {
+            if char == ' ' {
+                groups.push((chars.len() - group_len, group_len));
+                group_len = 0;
+                continue;
+            }
+            chars.push((position, char.to_ascii_uppercase()));
+            group_len += 1;
+        }
+        groups.push((chars.len() - group_len, group_len));
+        let is_valid = |char: &char| ALLOWED_ALPHABET.contains(char);
+        let invalid = |(position, character): (usize, char)| ParseError::InvalidCharacter {
+            character,
+            position,
+        };
+
+        // Groups like `0 0` still pair up, so only an odd total means a digit is missing
+        if !chars.len().is_multiple_of(2) {
+            if let Some(&(start, len)) = groups.iter().rev().find(|(_, len)| len % 2 == 1) {
+                let (position, char) = chars.remove(start + len - 1);
+                errors.push(match is_valid(&char) {
+                    true => ParseError::LoneNibble { position },
+                    false => invalid((position, char)),
+                });
+            }
+        }
+
+        let mut data = Vec::new();
+        let mut spans = Vec::new();
+        for pair in chars.chunks(2) {
+            let [high, low] = *pair else {
+                continue;
+            };
+            match parse_token(high, low) {
+                Ok(element) => {
+                    data.push(element);
+                    spans.push(high.0..low.0 + low.1.len_utf8());
+                }
+                Err(BinmatchError::MixedToken { token, position }) => {
+                    errors.push(ParseError::MixedToken { token, position })
+                }
+                // Both characters of the pair may be invalid, so they are reported one by one
+                Err(_) => errors.extend(
+                    pair.iter()
+                        .copied()
+                        .filter(|(_, c)| !is_valid(c))
+                        .map(invalid),
+                ),
+            }
+        }
+
+        if !errors.is_empty() {
+            errors.sort_by_key(|error| match error {
+                ParseError::InvalidCharacter { position, .. }
+                | ParseError::LoneNibble { position }
+                | ParseError::MixedToken { position, .. } => *position,
+            });
+            return Err(errors);
+        }
+        Ok(Pattern {
+            len: data.len(),
+            data,
+            source: PatternSource::new(pattern, spans),
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,1009 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{vec, Arc, BinmatchError, Cow, Match, MatchOptions, Pattern, PatternElement, Vec};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+/// The bytes written over a match, in the syntax of [Pattern::new]
+///
+/// Literals are written, `__` and `??` keep the original byte at their position
+///
+/// # Example:
+/// ```
+/// # use binmatch::{PatchTemplate, Pattern};
+/// let pattern = Pattern::new("74 ??").unwrap();
+/// let template = PatchTemplate::new(&pattern, "EB __").unwrap();
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct PatchTemplate {
+    data: Vec<PatternElement>,
+}
+
+impl PatchTemplate {
+    /// Parses a template for matches of `pattern`
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `template` can't be parsed by [Pattern::new]
+    /// - `template` and `pattern` don't have the same length
+    pub fn new(pattern: &Pattern, template: &str) -> Result<PatchTemplate, BinmatchError> {
+        // Both wildcards keep the original byte, so they are stored the same way
+        let data = Pattern::try_new(template)?
+            .data
+            .into_iter()
+            .map(|element| match element {
+                PatternElement::Placeholder => PatternElement::Ignore,
+                element => element,
+            })
+            .collect();
+        let template = PatchTemplate { data };
+        template.check_len(pattern)?;
+        Ok(template)
+    }
+
+    /// A template writing exactly `bytes`
+    pub fn literal(bytes: &[u8]) -> PatchTemplate {
+        PatchTemplate {
+            data: bytes.iter().map(|&b| PatternElement::Literal(b)).collect(),
+        }
+    }
+
+    /// Writes the literals of the template into `window`, which has to be as long as the template
+    pub(crate) fn apply(&self, window: &mut [u8]) {
+        for (byte, element) in window.iter_mut().zip(&self.data) {
+            if let PatternElement::Literal(value) = element {
+                *byte = *value;
+            }
+        }
+    }
+
+    pub(crate) fn check_len(&self, pattern: &Pattern) -> Result<(), BinmatchError> {
+        if self.data.len() != pattern.len {
+            return Err(BinmatchError::ReplacementLengthError {
+                expected: pattern.len,
+                found: self.data.len(),
+            });
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// A [Pattern] bound to the [PatchTemplate] written over its matches
+///
+/// # Example:
+/// ```
+/// # use binmatch::Patch;
+/// let patch = Patch::parse("74 ?? -> EB __").unwrap();
+/// let mut code = vec![0x85, 0xC0, 0x74, 0x07];
+/// assert_eq!(patch.apply(&mut code).len(), 1);
+/// assert_eq!(code, vec![0x85, 0xC0, 0xEB, 0x07]);
+/// assert_eq!(patch.to_string(), "74 ?? -> EB __");
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Patch {
+    pattern: Pattern,
+    template: PatchTemplate,
+}
+
+impl Patch {
+    /// Binds `template` to `pattern`
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `template` and `pattern` don't have the same length
+    pub fn new(pattern: Pattern, template: PatchTemplate) -> Result<Patch, BinmatchError> {
+        template.check_len(&pattern)?;
+        Ok(Patch { pattern, template })
+    }
+
+    /// Parses `pattern -> template`, both sides in the syntax of [Pattern::new]
+    ///
+    /// On the replacement side both `__` and `??` keep the original byte, see [PatchTemplate]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - There is no `->` or more than one
+    /// - One of the sides can't be parsed
+    /// - Both sides don't have the same length
+    pub fn parse(patch: &str) -> Result<Patch, BinmatchError> {
+        let mut sides = patch.split("->");
+        let (Some(pattern), Some(template), None) = (sides.next(), sides.next(), sides.next())
+        else {
+            return Err(BinmatchError::PatchArrowError);
+        };
+        let pattern = Pattern::try_new(pattern)?;
+        let template = PatchTemplate::new(&pattern, template)?;
+        Ok(Patch { pattern, template })
+    }
+
+    /// Patches every match in `buf`, see [Pattern::patch]
+    pub fn apply(&self, buf: &mut [u8]) -> PatchReport {
+        // The lengths were checked when binding the template
+        self.pattern
+            .patch_checked(buf, &self.template, &PatchOptions::new(), |_| true)
+    }
+
+    #[inline(always)]
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    #[inline(always)]
+    pub fn template(&self) -> &PatchTemplate {
+        &self.template
+    }
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.pattern, self.template)
+    }
+}
+
+/// Writes the template in the syntax of [Pattern::new], wildcards are written as `__`
+impl fmt::Display for PatchTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, element) in self.data.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            match element {
+                PatternElement::Literal(byte) => write!(f, "{:02X}", byte)?,
+                PatternElement::Placeholder | PatternElement::Ignore => f.write_str("__")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single patched match: its offset and the bytes before and after patching
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct PatchRecord {
+    pub offset: u64,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+/// What a patch operation wrote, one [PatchRecord] per patched match in ascending order
+///
+/// Displays as a hex diff with a `-` line for the old and a `+` line for the new bytes of every record
+///
+/// # Example:
+/// ```
+/// # use binmatch::{PatchOptions, PatchTemplate, Pattern};
+/// let pattern = Pattern::new("74 ??").unwrap();
+/// let template = PatchTemplate::new(&pattern, "EB __").unwrap();
+/// let mut code = vec![0x90, 0x74, 0x05];
+/// let report = pattern.patch(&mut code, &template, &PatchOptions::new()).unwrap();
+/// assert_eq!(report.to_string(), "@@ 0x00000001 @@\n-74 05\n+eb 05\n");
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PatchReport {
+    records: Vec<PatchRecord>,
+    skipped: usize,
+    limit_reached: bool,
+}
+
+impl PatchReport {
+    /// Rebuilds a report from its records, e.g. ones saved by an earlier run, to
+    /// [revert](PatchReport::revert) or [apply](PatchReport::apply) them
+    ///
+    /// The records are kept in the given order, nothing was skipped and no limit was reached  
+    /// Records are checked when they are used, so inconsistent ones are reported as errors then
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{PatchRecord, PatchReport};
+    /// let record = PatchRecord { offset: 1, old: vec![0x74], new: vec![0xEB] };
+    /// let report = PatchReport::from_records(vec![record]);
+    /// let mut code = vec![0x90, 0xEB];
+    /// assert_eq!(report.revert(&mut code), Ok(1));
+    /// assert_eq!(code, vec![0x90, 0x74]);
+    /// ```
+    pub fn from_records(records: Vec<PatchRecord>) -> PatchReport {
+        PatchReport {
+            records,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn push(&mut self, record: PatchRecord) {
+        self.records.push(record);
+    }
+
+    #[inline(always)]
+    pub fn records(&self) -> &[PatchRecord] {
+        &self.records
+    }
+
+    /// Number of patched matches
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Number of matches that were not patched because of the predicate of [Pattern::patch_if]
+    #[inline(always)]
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Number of matches that were looked at, i.e. the patched and the skipped ones
+    #[inline(always)]
+    pub fn candidates(&self) -> usize {
+        self.len() + self.skipped
+    }
+
+    /// Whether the scan stopped at [PatchOptions::max_patches] before reaching the end of the data,
+    /// in which case there may be further matches that were neither patched nor counted
+    #[inline(always)]
+    pub fn limit_reached(&self) -> bool {
+        self.limit_reached
+    }
+
+    /// Marks the limit as reached when `max_patches` records were made while a window of `len`
+    /// bytes still fits between the stream position `next` and `end`
+    pub(crate) fn update_limit_reached(
+        &mut self,
+        max_patches: usize,
+        next: u64,
+        len: usize,
+        end: u64,
+    ) {
+        self.limit_reached = self.len() == max_patches
+            && next
+                .checked_add(len as u64)
+                .is_some_and(|window_end| window_end <= end);
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+/// Why the bytes of a buffer don't match a [PatchReport]
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum VerifyError {
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "The record at {offset:#x} needs {len} bytes, but the buffer ends after {available}"
+        )
+    )]
+    OutOfBounds {
+        offset: u64,
+        len: usize,
+        available: usize,
+    },
+    #[cfg_attr(
+        feature = "std",
+        error("Expected {expected:#04x} at {offset:#x} (+{index} into the record), found {found:#04x}")
+    )]
+    Mismatch {
+        offset: u64,
+        index: usize,
+        expected: u8,
+        found: u8,
+    },
+    #[cfg_attr(
+        feature = "std",
+        error("The record at {offset:#x} replaces {old} bytes with {new} bytes")
+    )]
+    LengthMismatch { offset: u64, old: usize, new: usize },
+}
+
+/// Why [PatchReport::revert] refused to revert a buffer
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum RevertError {
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "The record at {offset:#x} needs {len} bytes, but the buffer ends after {available}"
+        )
+    )]
+    OutOfBounds {
+        offset: u64,
+        len: usize,
+        available: usize,
+    },
+    #[cfg_attr(
+        feature = "std",
+        error("The bytes at {offset:#x} are neither the patched nor the original ones")
+    )]
+    Modified { offset: u64 },
+    #[cfg_attr(
+        feature = "std",
+        error("The record at {offset:#x} replaces {old} bytes with {new} bytes")
+    )]
+    LengthMismatch { offset: u64, old: usize, new: usize },
+}
+
+/// What reverting a single record has to do
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum RevertAction {
+    Write,
+    AlreadyReverted,
+}
+
+impl PatchRecord {
+    /// Decides how to revert the record given the bytes `current` at its offset
+    pub(crate) fn revert_action(
+        &self,
+        current: &[u8],
+        force: bool,
+    ) -> Result<RevertAction, RevertError> {
+        if self.old.len() != self.new.len() {
+            return Err(RevertError::LengthMismatch {
+                offset: self.offset,
+                old: self.old.len(),
+                new: self.new.len(),
+            });
+        }
+        if current == self.new.as_slice() || force && current != self.old.as_slice() {
+            Ok(RevertAction::Write)
+        } else if current == self.old.as_slice() {
+            Ok(RevertAction::AlreadyReverted)
+        } else {
+            Err(RevertError::Modified {
+                offset: self.offset,
+            })
+        }
+    }
+
+    /// Checks that `buf` contains `expected` at the offset of the record
+    pub(crate) fn check(&self, buf: &[u8], expected: &[u8]) -> Result<(), VerifyError> {
+        let out_of_bounds = VerifyError::OutOfBounds {
+            offset: self.offset,
+            len: expected.len(),
+            available: buf.len(),
+        };
+        let Ok(start) = usize::try_from(self.offset) else {
+            return Err(out_of_bounds);
+        };
+        let Some(actual) = start
+            .checked_add(expected.len())
+            .and_then(|end| buf.get(start..end))
+        else {
+            return Err(out_of_bounds);
+        };
+        match actual.iter().zip(expected).position(|(a, e)| a != e) {
+            Some(index) => Err(VerifyError::Mismatch {
+                offset: self.offset + index as u64,
+                index,
+                expected: expected[index],
+                found: actual[index],
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl PatchReport {
+    /// Checks that every record's `old` bytes are still at its offset in `buf`
+    ///
+    /// Meant for a report of a dry run (see [PatchOptions::dry_run]), to make sure the data didn't
+    /// change before [PatchReport::apply] writes it
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - A record doesn't fit into `buf` or its `old` bytes differ, the first such record is reported
+    /// - The `old` and `new` bytes of a record don't have the same length
+    pub fn verify_against(&self, buf: &[u8]) -> Result<(), VerifyError> {
+        self.records.iter().try_for_each(|record| {
+            if record.old.len() != record.new.len() {
+                return Err(VerifyError::LengthMismatch {
+                    offset: record.offset,
+                    old: record.old.len(),
+                    new: record.new.len(),
+                });
+            }
+            record.check(buf, &record.old)
+        })
+    }
+
+    /// Verifies `buf` with [PatchReport::verify_against] and writes the `new` bytes of every record
+    ///
+    /// Nothing is written if verification fails
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Patch, PatchOptions};
+    /// let patch = Patch::parse("74 ?? -> EB __").unwrap();
+    /// let mut code = vec![0x74, 0x07];
+    /// let report = patch
+    ///     .pattern()
+    ///     .patch(&mut code, patch.template(), &PatchOptions::new().dry_run())
+    ///     .unwrap();
+    /// assert_eq!(code, vec![0x74, 0x07]);
+    /// report.apply(&mut code).unwrap();
+    /// assert_eq!(code, vec![0xEB, 0x07]);
+    /// ```
+    pub fn apply(&self, buf: &mut [u8]) -> Result<usize, VerifyError> {
+        self.verify_against(buf)?;
+        for record in &self.records {
+            // Verified above, so every record fits
+            let window = usize::try_from(record.offset)
+                .ok()
+                .and_then(|start| buf.get_mut(start..start.checked_add(record.new.len())?));
+            if let Some(window) = window {
+                window.copy_from_slice(&record.new);
+            }
+        }
+        Ok(self.records.len())
+    }
+}
+
+impl PatchReport {
+    /// Writes the `old` bytes of every record back into `buf` and returns the number of reverted records
+    ///
+    /// Records whose `old` bytes are already in place are skipped, so reverting twice is harmless
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - A record doesn't fit into `buf`
+    /// - The `old` and `new` bytes of a record don't have the same length
+    /// - The bytes of a record are neither its `new` nor its `old` ones, e.g. because they were
+    ///   modified after patching, see [PatchReport::force_revert]
+    ///
+    /// Nothing is written in both cases
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Patch;
+    /// let patch = Patch::parse("74 ?? -> EB __").unwrap();
+    /// let mut code = vec![0x74, 0x07];
+    /// let report = patch.apply(&mut code);
+    /// assert_eq!(report.revert(&mut code), Ok(1));
+    /// assert_eq!(code, vec![0x74, 0x07]);
+    /// assert_eq!(report.revert(&mut code), Ok(0));
+    /// ```
+    pub fn revert(&self, buf: &mut [u8]) -> Result<usize, RevertError> {
+        self.revert_with(buf, false)
+    }
+
+    /// Same as [PatchReport::revert], but records with modified bytes are overwritten as well
+    pub fn force_revert(&self, buf: &mut [u8]) -> Result<usize, RevertError> {
+        self.revert_with(buf, true)
+    }
+
+    fn revert_with(&self, buf: &mut [u8], force: bool) -> Result<usize, RevertError> {
+        let mut writes = Vec::new();
+        for record in &self.records {
+            let range = usize::try_from(record.offset)
+                .ok()
+                .and_then(|start| Some(start..start.checked_add(record.old.len())?))
+                .filter(|range| range.end <= buf.len())
+                .ok_or(RevertError::OutOfBounds {
+                    offset: record.offset,
+                    len: record.old.len(),
+                    available: buf.len(),
+                })?;
+            if record.revert_action(&buf[range.clone()], force)? == RevertAction::Write {
+                writes.push((range, &record.old));
+            }
+        }
+        for (range, old) in &writes {
+            buf[range.clone()].copy_from_slice(old);
+        }
+        Ok(writes.len())
+    }
+}
+
+/// Same as [PatchReport::from_records]
+impl FromIterator<PatchRecord> for PatchReport {
+    fn from_iter<I: IntoIterator<Item = PatchRecord>>(records: I) -> PatchReport {
+        PatchReport::from_records(records.into_iter().collect())
+    }
+}
+
+impl fmt::Display for PatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for record in &self.records {
+            writeln!(f, "@@ {:#010x} @@", record.offset)?;
+            for (sign, bytes) in [('-', &record.old), ('+', &record.new)] {
+                write!(f, "{}", sign)?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{:02x}", byte)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+type BufferHook = Arc<dyn Fn(&mut [u8], &PatchRecord) + Send + Sync>;
+#[cfg(feature = "std")]
+type FileHook = Arc<dyn Fn(&mut std::fs::File, &PatchRecord) -> std::io::Result<()> + Send + Sync>;
+
+/// The hooks of [PatchOptions], run in registration order
+#[derive(Clone, Default)]
+pub(crate) struct PatchHooks {
+    buffer: Vec<BufferHook>,
+    #[cfg(feature = "std")]
+    file: Vec<FileHook>,
+}
+
+impl PatchHooks {
+    pub(crate) fn run(&self, buf: &mut [u8], record: &PatchRecord) {
+        for hook in &self.buffer {
+            hook(buf, record);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn run_file(
+        &self,
+        file: &mut std::fs::File,
+        record: &PatchRecord,
+    ) -> std::io::Result<()> {
+        for hook in &self.file {
+            hook(file, record)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PatchHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.buffer.len();
+        #[cfg(feature = "std")]
+        let count = count + self.file.len();
+        write!(f, "{count} hooks")
+    }
+}
+
+// Closures can't be compared, so hooks are ignored when comparing or hashing options like the
+// transforms of a Pattern
+impl PartialEq for PatchHooks {
+    fn eq(&self, _: &PatchHooks) -> bool {
+        true
+    }
+}
+
+impl Eq for PatchHooks {}
+
+impl Hash for PatchHooks {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+/// Options for the patch operations, e.g. [Pattern::patch] and [Pattern::patch_file]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PatchOptions {
+    pub(crate) max_patches: Option<usize>,
+    pub(crate) dry_run: bool,
+    pub(crate) hooks: PatchHooks,
+}
+
+impl PatchOptions {
+    pub fn new() -> PatchOptions {
+        Self::default()
+    }
+
+    /// Stops after `max_patches` matches were patched, the rest of the data is not scanned
+    ///
+    /// Every patch and replace function has a variant taking [PatchOptions], e.g.
+    /// [Pattern::replace_matches_with] or [Pattern::fill_matches_with], so the limit applies to all
+    /// of them
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{PatchOptions, PatchTemplate, Pattern};
+    /// let pattern = Pattern::new("AA").unwrap();
+    /// let mut data = vec![0xAA; 5];
+    /// let report = pattern
+    ///     .patch(&mut data, &PatchTemplate::literal(&[0x00]), &PatchOptions::new().max_patches(2))
+    ///     .unwrap();
+    /// assert!(report.limit_reached());
+    /// assert_eq!(data, vec![0x00, 0x00, 0xAA, 0xAA, 0xAA]);
+    /// ```
+    pub fn max_patches(mut self, max_patches: usize) -> PatchOptions {
+        self.max_patches = Some(max_patches);
+        self
+    }
+
+    /// Finds and reports the matches, but doesn't write anything
+    pub fn dry_run(mut self) -> PatchOptions {
+        self.dry_run = true;
+        self
+    }
+
+    /// Calls `hook` with the whole buffer and the record of a patch right after that patch was
+    /// written, e.g. to fix up a checksum covering the patched region
+    ///
+    /// - The hook runs once per patch, in ascending order of the offsets, so it sees the earlier
+    ///   patches but not the later ones. A checksum covering several patches is simply recomputed
+    ///   every time, the last call leaves the final value
+    /// - The search continues on the buffer as left by the hook
+    /// - Multiple hooks run in the order they were added
+    /// - Hooks don't run during a [dry run](PatchOptions::dry_run) and not for
+    ///   [Pattern::patch_file], see [PatchOptions::after_patch_file]
+    /// - Hooks are shared by clones of the options, which may be sent to other threads, so state
+    ///   kept by a hook needs e.g. a `Mutex`. They are ignored when comparing or hashing options
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{PatchOptions, PatchTemplate, Pattern};
+    /// // The last byte holds the sum of all others
+    /// let mut data = vec![0x74, 0x05, 0x90, 0x09];
+    /// let options = PatchOptions::new().after_patch(|buf, _| {
+    ///     let (body, sum) = buf.split_at_mut(buf.len() - 1);
+    ///     sum[0] = body.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    /// });
+    /// let je = Pattern::new("74 ??").unwrap();
+    /// je.patch(&mut data, &PatchTemplate::new(&je, "EB __").unwrap(), &options).unwrap();
+    /// assert_eq!(data, vec![0xEB, 0x05, 0x90, 0x80]);
+    /// ```
+    pub fn after_patch(
+        mut self,
+        hook: impl Fn(&mut [u8], &PatchRecord) + Send + Sync + 'static,
+    ) -> PatchOptions {
+        self.hooks.buffer.push(Arc::new(hook));
+        self
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Same as [PatchOptions::after_patch], but for [Pattern::patch_file]
+    ///
+    /// The hook gets the opened file, which it may seek, read and write freely, after the changed
+    /// bytes of a patch were written  
+    /// An error of the hook stops patching, it is returned as a
+    /// [PartialPatchError](crate::PartialPatchError) whose report includes the current patch
+    #[cfg(feature = "std")]
+    pub fn after_patch_file(
+        mut self,
+        hook: impl Fn(&mut std::fs::File, &PatchRecord) -> std::io::Result<()> + Send + Sync + 'static,
+    ) -> PatchOptions {
+        self.hooks.file.push(Arc::new(hook));
+        self
+    }
+}
+
+impl Pattern {
+    /// Returns a copy of `haystack` where every match is overwritten with `replacement`
+    ///
+    /// Overlapping matches are replaced from left to right, a match overlapping an already replaced
+    /// one is skipped (see [MatchOptions::non_overlapping])  
+    /// To only replace the first matches, use [Pattern::patch] with [PatchOptions::max_patches]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `replacement` is not exactly as long as the Pattern
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("AA ??").unwrap();
+    /// let data = vec![0xAA, 0xAA, 0xAA, 0x01];
+    /// let patched = pattern.replace_matches(&data, &[0x90, 0x90]).unwrap();
+    /// assert_eq!(patched, vec![0x90, 0x90, 0x90, 0x90]);
+    /// ```
+    pub fn replace_matches(
+        &self,
+        haystack: &[u8],
+        replacement: &[u8],
+    ) -> Result<Vec<u8>, BinmatchError> {
+        Ok(self
+            .replace_matches_with(haystack, replacement, &PatchOptions::new())?
+            .0)
+    }
+
+    /// Same as [Pattern::replace_matches], but takes [PatchOptions] and also returns the report of
+    /// what was written, see [Pattern::patch]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{PatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA ??").unwrap();
+    /// let data = vec![0xAA, 0x01, 0xAA, 0x02];
+    /// let options = PatchOptions::new().max_patches(1);
+    /// let (patched, report) = pattern.replace_matches_with(&data, &[0x90, 0x90], &options).unwrap();
+    /// assert_eq!(patched, vec![0x90, 0x90, 0xAA, 0x02]);
+    /// assert_eq!(report.records()[0].old, vec![0xAA, 0x01]);
+    /// assert!(report.limit_reached());
+    /// ```
+    pub fn replace_matches_with(
+        &self,
+        haystack: &[u8],
+        replacement: &[u8],
+        options: &PatchOptions,
+    ) -> Result<(Vec<u8>, PatchReport), BinmatchError> {
+        let (patched, report) = self.replace_matches_cow_with(haystack, replacement, options)?;
+        Ok((patched.into_owned(), report))
+    }
+
+    /// Same as [Pattern::replace_matches], but `haystack` is only copied if there is a match
+    pub fn replace_matches_cow<'h>(
+        &self,
+        haystack: &'h [u8],
+        replacement: &[u8],
+    ) -> Result<Cow<'h, [u8]>, BinmatchError> {
+        Ok(self
+            .replace_matches_cow_with(haystack, replacement, &PatchOptions::new())?
+            .0)
+    }
+
+    /// Same as [Pattern::replace_matches_cow], but takes [PatchOptions] and also returns the report
+    /// of what was written, see [Pattern::patch]
+    pub fn replace_matches_cow_with<'h>(
+        &self,
+        haystack: &'h [u8],
+        replacement: &[u8],
+        options: &PatchOptions,
+    ) -> Result<(Cow<'h, [u8]>, PatchReport), BinmatchError> {
+        self.check_replacement(replacement)?;
+        let first = self.find_with(haystack, &MatchOptions::new().max_matches(1));
+        if first.is_empty() {
+            let mut report = PatchReport::default();
+            let max_patches = options.max_patches.unwrap_or(usize::MAX);
+            report.update_limit_reached(max_patches, 0, self.len, haystack.len() as u64);
+            return Ok((Cow::Borrowed(haystack), report));
+        }
+        let mut patched = haystack.to_vec();
+        let report = self.patch_checked(
+            &mut patched,
+            &PatchTemplate::literal(replacement),
+            options,
+            |_| true,
+        );
+        Ok((Cow::Owned(patched), report))
+    }
+
+    /// Overwrites every match in `buf` with `replacement` and returns the number of patched matches
+    ///
+    /// The search continues after each replaced match and only ever writes behind itself, so the
+    /// result only depends on the original contents of `buf`: a replacement can neither create nor
+    /// destroy other matches, even if it matches the Pattern itself  
+    /// Overlapping matches are handled like in [Pattern::replace_matches]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `replacement` is not exactly as long as the Pattern
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("74 ??").unwrap();
+    /// let mut code = vec![0x74, 0x05, 0x90, 0x74, 0x10];
+    /// assert_eq!(pattern.patch_in_place(&mut code, &[0x90, 0x90]).unwrap(), 2);
+    /// assert_eq!(code, vec![0x90; 5]);
+    /// ```
+    pub fn patch_in_place(
+        &self,
+        buf: &mut [u8],
+        replacement: &[u8],
+    ) -> Result<usize, BinmatchError> {
+        Ok(self
+            .patch_in_place_with(buf, replacement, &PatchOptions::new())?
+            .len())
+    }
+
+    /// Same as [Pattern::patch_in_place], but takes [PatchOptions] and returns the report of what
+    /// was written, see [Pattern::patch]
+    ///
+    /// Unlike a replacement, a hook added with [PatchOptions::after_patch] may write ahead of the
+    /// search, which then continues on the buffer as left by the hook: bytes written by a hook
+    /// can create or destroy later matches
+    pub fn patch_in_place_with(
+        &self,
+        buf: &mut [u8],
+        replacement: &[u8],
+        options: &PatchOptions,
+    ) -> Result<PatchReport, BinmatchError> {
+        self.check_replacement(replacement)?;
+        Ok(self.patch_checked(buf, &PatchTemplate::literal(replacement), options, |_| true))
+    }
+
+    /// Returns a copy of `haystack` where `template` is written over every match
+    ///
+    /// Only the literals of the template are written, the original bytes are kept where it has
+    /// wildcards  
+    /// Overlapping matches are handled like in [Pattern::replace_matches]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `template` is not exactly as long as the Pattern
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{PatchTemplate, Pattern};
+    /// // Turn every `je rel8` into a `jmp rel8` with the same displacement
+    /// let je = Pattern::new("74 ??").unwrap();
+    /// let jmp = PatchTemplate::new(&je, "EB __").unwrap();
+    /// let code = vec![0x85, 0xC0, 0x74, 0x07];
+    /// assert_eq!(je.replace_with_template(&code, &jmp).unwrap(), vec![0x85, 0xC0, 0xEB, 0x07]);
+    /// ```
+    pub fn replace_with_template(
+        &self,
+        haystack: &[u8],
+        template: &PatchTemplate,
+    ) -> Result<Vec<u8>, BinmatchError> {
+        Ok(self
+            .replace_with_template_with(haystack, template, &PatchOptions::new())?
+            .0)
+    }
+
+    /// Same as [Pattern::replace_with_template], but takes [PatchOptions] and also returns the
+    /// report of what was written, see [Pattern::patch]
+    pub fn replace_with_template_with(
+        &self,
+        haystack: &[u8],
+        template: &PatchTemplate,
+        options: &PatchOptions,
+    ) -> Result<(Vec<u8>, PatchReport), BinmatchError> {
+        self.replace_matches_if(haystack, template, options, |_| true)
+    }
+
+    /// Writes `template` over every match in `buf` and reports what was written
+    ///
+    /// Behaves like [Pattern::patch_in_place], see [PatchOptions] for limiting the number of
+    /// patches and dry runs  
+    /// The report only contains the patched matches, with the bytes they had before and after
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `template` is not exactly as long as the Pattern
+    pub fn patch(
+        &self,
+        buf: &mut [u8],
+        template: &PatchTemplate,
+        options: &PatchOptions,
+    ) -> Result<PatchReport, BinmatchError> {
+        self.patch_if(buf, template, options, |_| true)
+    }
+
+    /// Same as [Pattern::patch], but only matches for which `predicate` returns `true` are patched
+    ///
+    /// The predicate sees the absolute offsets and the captures of every match  
+    /// Skipped matches are counted in [PatchReport::skipped] and don't prevent overlapping matches
+    /// from being patched
+    pub fn patch_if(
+        &self,
+        buf: &mut [u8],
+        template: &PatchTemplate,
+        options: &PatchOptions,
+        predicate: impl Fn(&Match) -> bool,
+    ) -> Result<PatchReport, BinmatchError> {
+        template.check_len(self)?;
+        Ok(self.patch_checked(buf, template, options, predicate))
+    }
+
+    /// [Pattern::patch_if] for a `template` known to have the length of the Pattern
+    fn patch_checked(
+        &self,
+        buf: &mut [u8],
+        template: &PatchTemplate,
+        options: &PatchOptions,
+        predicate: impl Fn(&Match) -> bool,
+    ) -> PatchReport {
+        let mut report = PatchReport::default();
+        // An empty Pattern never matches, so there is nothing to patch or to run hooks for
+        if self.len == 0 {
+            return report;
+        }
+        let max_patches = options.max_patches.unwrap_or(usize::MAX);
+        let mut start = 0;
+        while start + self.len <= buf.len() && report.len() < max_patches {
+            let Some(m) = self.match_at_unchecked(buf, start) else {
+                start += 1;
+                continue;
+            };
+            if !predicate(&m) {
+                report.skipped += 1;
+                start += 1;
+                continue;
+            }
+            start = m.end().max(start + 1);
+            let window = &mut buf[m.range()];
+            let old = window.to_vec();
+            let mut new = old.clone();
+            template.apply(&mut new);
+            if !options.dry_run {
+                window.copy_from_slice(&new);
+            }
+            let record = PatchRecord {
+                offset: m.start() as u64,
+                old,
+                new,
+            };
+            if !options.dry_run {
+                options.hooks.run(buf, &record);
+            }
+            report.push(record);
+        }
+        report.update_limit_reached(max_patches, start as u64, self.len, buf.len() as u64);
+        report
+    }
+
+    /// Returns a copy of `haystack` where `template` is written over every match for which
+    /// `predicate` returns `true`, together with the report of [Pattern::patch_if]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{PatchOptions, PatchTemplate, Pattern};
+    /// // Only patch `cmp eax, 1`
+    /// let cmp = Pattern::new("83 F8 ??").unwrap();
+    /// let nop = PatchTemplate::new(&cmp, "90 90 90").unwrap();
+    /// let code = vec![0x83, 0xF8, 0x01, 0x83, 0xF8, 0x02];
+    /// let (patched, report) = cmp
+    ///     .replace_matches_if(&code, &nop, &PatchOptions::new(), |m| {
+    ///         m.captures().get(0).unwrap().value == 0x01
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(patched, vec![0x90, 0x90, 0x90, 0x83, 0xF8, 0x02]);
+    /// assert_eq!((report.len(), report.skipped()), (1, 1));
+    /// ```
+    pub fn replace_matches_if(
+        &self,
+        haystack: &[u8],
+        template: &PatchTemplate,
+        options: &PatchOptions,
+        predicate: impl Fn(&Match) -> bool,
+    ) -> Result<(Vec<u8>, PatchReport), BinmatchError> {
+        let mut patched = haystack.to_vec();
+        let report = self.patch_if(&mut patched, template, options, predicate)?;
+        Ok((patched, report))
+    }
+
+    /// Overwrites every byte of every match in `buf` with `fill` and returns the number of filled matches
+    ///
+    /// Overlapping matches are handled like in [Pattern::patch_in_place]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// // NOP out every `call rel32`
+    /// let call = Pattern::new("E8 ?? ?? ?? ??").unwrap();
+    /// let mut code = vec![0x55, 0xE8, 0x10, 0x00, 0x00, 0x00, 0xC3];
+    /// assert_eq!(call.fill_matches(&mut code, 0x90), 1);
+    /// assert_eq!(code, vec![0x55, 0x90, 0x90, 0x90, 0x90, 0x90, 0xC3]);
+    /// ```
+    pub fn fill_matches(&self, buf: &mut [u8], fill: u8) -> usize {
+        self.fill_matches_with(buf, fill, &PatchOptions::new())
+            .len()
+    }
+
+    /// Same as [Pattern::fill_matches], but takes [PatchOptions] and returns the report of what was
+    /// written, see [Pattern::patch]
+    pub fn fill_matches_with(
+        &self,
+        buf: &mut [u8],
+        fill: u8,
+        options: &PatchOptions,
+    ) -> PatchReport {
+        self.patch_checked(
+            buf,
+            &PatchTemplate::literal(&vec![fill; self.len]),
+            options,
+            |_| true,
+        )
+    }
+
+    fn check_replacement(&self, replacement: &[u8]) -> Result<(), BinmatchError> {
+        if replacement.len() != self.len {
+            return Err(BinmatchError::ReplacementLengthError {
+                expected: self.len,
+                found: replacement.len(),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{Arc, Pattern, Vec};
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+type CaptureMap = Arc<dyn Fn(u8) -> u8 + Send + Sync>;
+
+/// Transforms applied to captured values before they are reported, see [Pattern::map_capture]
+#[derive(Clone, Default)]
+pub(crate) struct CapturePipeline {
+    maps: Vec<(usize, CaptureMap)>, // Applied in insertion order
+}
+
+impl CapturePipeline {
+    #[inline(always)]
+    pub(crate) fn apply(&self, ordinal: usize, value: u8) -> u8 {
+        self.maps
+            .iter()
+            .filter(|(target, _)| *target == ordinal)
+            .fold(value, |value, (_, map)| map(value))
+    }
+}
+
+impl fmt::Debug for CapturePipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.maps.iter().map(|(ordinal, _)| ordinal))
+            .finish()
+    }
+}
+
+// Closures can't be compared, so pipelines are ignored when comparing or hashing Patterns like
+// their source
+impl PartialEq for CapturePipeline {
+    fn eq(&self, _: &CapturePipeline) -> bool {
+        true
+    }
+}
+
+impl Eq for CapturePipeline {}
+
+impl PartialOrd for CapturePipeline {
+    fn partial_cmp(&self, other: &CapturePipeline) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CapturePipeline {
+    fn cmp(&self, _: &CapturePipeline) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl Hash for CapturePipeline {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl Pattern {
+    /// Transforms the value of the capture with the given ordinal before it is reported, e.g. to mask off bits or add a base
+    ///
+    /// - Transforms only change the reported values, never whether the Pattern matches
+    /// - Calling this multiple times for the same ordinal composes the transforms in call order
+    /// - Every API reporting captured values applies them, so those values may differ from the haystack bytes
+    /// - Transforms are ignored when comparing or hashing Patterns
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("B8 ?? ??")
+    ///     .unwrap()
+    ///     .map_capture(0, |value| value & 0xF0)
+    ///     .map_capture(0, |value| value >> 4);
+    /// assert_eq!(pattern.find_matches(vec![0xB8, 0x5A, 0x42]), vec![0x05, 0x42]);
+    /// ```
+    pub fn map_capture(
+        mut self,
+        ordinal: usize,
+        map: impl Fn(u8) -> u8 + Send + Sync + 'static,
+    ) -> Pattern {
+        self.capture_pipeline.maps.push((ordinal, Arc::new(map)));
+        self
+    }
+}
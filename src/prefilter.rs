@@ -0,0 +1,164 @@
+use crate::{vec, BTreeMap, Pattern, PatternElement, Vec};
+
+/// An Aho–Corasick automaton over one literal fragment per Pattern, used by
+/// [PatternSet](crate::PatternSet) to find candidate starts without testing every position
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) struct Prefilter {
+    nodes: Vec<Node>,
+    /// Patterns without a literal, they have to be tested at every position
+    unfiltered: Vec<usize>,
+}
+
+/// An automaton without fragments still needs its root
+impl Default for Prefilter {
+    fn default() -> Prefilter {
+        Prefilter::new(&[])
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+struct Node {
+    next: BTreeMap<u8, usize>,
+    fail: usize,
+    /// Fragments ending in this node, including those of its fail chain
+    outputs: Vec<Fragment>,
+}
+
+/// The literal run of a Pattern that gets fed into the automaton
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct Fragment {
+    pattern: usize,
+    /// Index of the fragment's first element inside the Pattern
+    offset: usize,
+    len: usize,
+}
+
+impl Prefilter {
+    pub(crate) fn new(patterns: &[Pattern]) -> Prefilter {
+        let mut prefilter = Prefilter {
+            nodes: vec![Node::default()],
+            unfiltered: Vec::new(),
+        };
+        for (index, pattern) in patterns.iter().enumerate() {
+            let (offset, len) = longest_literal_run(pattern);
+            if len == 0 {
+                prefilter.unfiltered.push(index);
+                continue;
+            }
+            let mut node = 0;
+            // The run only contains literals, so nothing is skipped
+            let run =
+                pattern.data[offset..offset + len]
+                    .iter()
+                    .filter_map(|element| match element {
+                        PatternElement::Literal(byte) => Some(*byte),
+                        _ => None,
+                    });
+            for byte in run {
+                node = match prefilter.nodes[node].next.get(&byte) {
+                    Some(&next) => next,
+                    None => {
+                        prefilter.nodes.push(Node::default());
+                        let next = prefilter.nodes.len() - 1;
+                        prefilter.nodes[node].next.insert(byte, next);
+                        next
+                    }
+                };
+            }
+            prefilter.nodes[node].outputs.push(Fragment {
+                pattern: index,
+                offset,
+                len,
+            });
+        }
+        prefilter.link();
+        prefilter
+    }
+
+    /// Sets the fail links breadth first and merges the outputs along them
+    fn link(&mut self) {
+        let mut queue: Vec<usize> = self.nodes[0].next.values().copied().collect();
+        let mut head = 0;
+        while let Some(&node) = queue.get(head) {
+            head += 1;
+            let children: Vec<(u8, usize)> = self.nodes[node]
+                .next
+                .iter()
+                .map(|(&b, &n)| (b, n))
+                .collect();
+            for (byte, child) in children {
+                let mut fail = self.nodes[node].fail;
+                let target = loop {
+                    if let Some(&next) = self.nodes[fail].next.get(&byte) {
+                        break next;
+                    }
+                    if fail == 0 {
+                        break 0;
+                    }
+                    fail = self.nodes[fail].fail;
+                };
+                self.nodes[child].fail = target;
+                let inherited = self.nodes[target].outputs.clone();
+                self.nodes[child].outputs.extend(inherited);
+                queue.push(child);
+            }
+        }
+    }
+
+    /// Calls `candidate(pattern, start)` for every position a Pattern could start at
+    ///
+    /// Candidates aren't verified and come in no particular order, Patterns without a literal are
+    /// reported at every start they fit at, except for empty ones which never match
+    pub(crate) fn candidates(
+        &self,
+        patterns: &[Pattern],
+        haystack: &[u8],
+        mut candidate: impl FnMut(usize, usize),
+    ) {
+        let mut node = 0;
+        for (position, byte) in haystack.iter().enumerate() {
+            node = loop {
+                if let Some(&next) = self.nodes[node].next.get(byte) {
+                    break next;
+                }
+                if node == 0 {
+                    break 0;
+                }
+                node = self.nodes[node].fail;
+            };
+            for fragment in &self.nodes[node].outputs {
+                let Some(start) = (position + 1).checked_sub(fragment.len + fragment.offset) else {
+                    continue;
+                };
+                if start + patterns[fragment.pattern].len <= haystack.len() {
+                    candidate(fragment.pattern, start);
+                }
+            }
+        }
+        for &pattern in &self.unfiltered {
+            let len = patterns[pattern].len;
+            if len == 0 {
+                continue;
+            }
+            for start in 0..=haystack.len().saturating_sub(len) {
+                if start + len <= haystack.len() {
+                    candidate(pattern, start);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the offset and length of the longest run of literals, the first one wins ties
+fn longest_literal_run(pattern: &Pattern) -> (usize, usize) {
+    let mut best = (0, 0);
+    let mut run_start = 0;
+    for (index, element) in pattern.data.iter().enumerate() {
+        if !matches!(element, PatternElement::Literal(_)) {
+            run_start = index + 1;
+        } else if index + 1 - run_start > best.1 {
+            best = (run_start, index + 1 - run_start);
+        }
+    }
+    best
+}
@@ -0,0 +1,181 @@
+use crate::{MemError, MemoryRegion, MemorySource, Pattern, Permissions, RegionMatch};
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::fs::FileExt;
+use thiserror::Error;
+
+/// Number of bytes read at once
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Why [Pattern::find_in_process] could not scan a process
+#[derive(Error, Debug)]
+pub enum ProcessScanError {
+    #[error("Could not read the memory map of process {pid}: {source}")]
+    Maps { pid: u32, source: io::Error },
+    #[error("Line {line} of the memory map of process {pid} is malformed")]
+    MalformedMaps { pid: u32, line: usize },
+    #[error("Could not open the memory of process {pid}: {source}")]
+    Memory { pid: u32, source: io::Error },
+}
+
+impl Pattern {
+    /// <div class="warning"> Only available using the <code>process</code> feature on Linux </div>
+    ///
+    /// Scans every readable region of the process `pid`, see [Pattern::find_in_process_with]
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `/proc/<pid>/maps` can't be read or parsed, e.g. because the process doesn't exist
+    /// - `/proc/<pid>/mem` can't be opened, which needs the same permissions as attaching a
+    ///   debugger
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("48 8B 05 ?? ?? ?? ??").unwrap();
+    /// for found in pattern.find_in_process(1234).unwrap() {
+    ///     println!("{:#x} in {:?}", found.address(), found.region().path());
+    /// }
+    /// ```
+    pub fn find_in_process(&self, pid: u32) -> Result<Vec<RegionMatch>, ProcessScanError> {
+        self.find_in_process_with(pid, |_| true)
+    }
+
+    /// <div class="warning"> Only available using the <code>process</code> feature on Linux </div>
+    ///
+    /// Scans the readable regions of the process `pid` for which `filter` returns `true`
+    ///
+    /// The memory is read through `/proc/<pid>/mem` while the process keeps running, so it may
+    /// change during the scan  
+    /// Pages that can't be read, e.g. guard pages, are skipped, matches never span them  
+    /// Matches are reported with their virtual address, in ascending order
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use binmatch::Pattern;
+    /// # let pattern = Pattern::new("C3").unwrap();
+    /// // Only code, i.e. `r-x` regions
+    /// let found = pattern.find_in_process_with(1234, |region| region.permissions().execute());
+    /// ```
+    pub fn find_in_process_with(
+        &self,
+        pid: u32,
+        filter: impl Fn(&MemoryRegion) -> bool,
+    ) -> Result<Vec<RegionMatch>, ProcessScanError> {
+        let mut process = ProcessMemory::open(pid)?;
+        process.regions.retain(|region| filter(region));
+        Ok(self.scan_process(&mut process, &mut vec![0; CHUNK_SIZE]))
+    }
+
+    /// <div class="warning"> Only available using the <code>process</code> feature on Linux </div>
+    ///
+    /// Scans every readable region of the current process, see [Pattern::find_in_self_with]
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("DE C0 AD DE ?? ?? ?? ??").unwrap();
+    /// for found in pattern.find_in_self() {
+    ///     println!("{:#x} in {:?}", found.address(), found.region().path());
+    /// }
+    /// ```
+    pub fn find_in_self(&self) -> Vec<RegionMatch> {
+        self.find_in_self_with(|_| true)
+    }
+
+    /// <div class="warning"> Only available using the <code>process</code> feature on Linux </div>
+    ///
+    /// Same as [Pattern::find_in_process_with] for the current process, without needing any
+    /// permissions
+    ///
+    /// The memory is read through `/proc/self/mem` instead of slices created from the region
+    /// bounds: other threads may unmap or change a region at any time, which would be undefined
+    /// behaviour for a slice but only results in a skipped page or outdated bytes here  
+    /// The region holding the scan buffer is skipped, so the bytes copied by the scan itself aren't
+    /// reported. Copies of matching bytes made elsewhere, e.g. by the caller, are still found  
+    /// Empty if `/proc` isn't available
+    pub fn find_in_self_with(&self, filter: impl Fn(&MemoryRegion) -> bool) -> Vec<RegionMatch> {
+        let Ok(mut process) = ProcessMemory::open(std::process::id()) else {
+            return Vec::new();
+        };
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let buffer = chunk.as_ptr() as u64;
+        process
+            .regions
+            .retain(|region| !(region.start()..region.end()).contains(&buffer) && filter(region));
+        self.scan_process(&mut process, &mut chunk)
+    }
+
+    fn scan_process(&self, process: &mut ProcessMemory, chunk: &mut [u8]) -> Vec<RegionMatch> {
+        // ProcessMemory only fails with MemError::Unreadable, which the scan skips
+        self.find_in_source_with_buffer(process, chunk)
+            .unwrap_or_default()
+    }
+}
+
+/// Parses a line of `/proc/<pid>/maps`
+fn parse_maps_line(line: &str) -> Option<MemoryRegion> {
+    let mut fields = line.splitn(6, ' ');
+    let (start, end) = fields.next()?.split_once('-')?;
+    let permissions = fields.next()?.as_bytes();
+    if permissions.len() != 4 {
+        return None;
+    }
+    // offset, device and inode
+    fields.nth(2)?;
+    let region = MemoryRegion::new(
+        u64::from_str_radix(start, 16).ok()?..u64::from_str_radix(end, 16).ok()?,
+        Permissions::new(
+            permissions[0] == b'r',
+            permissions[1] == b'w',
+            permissions[2] == b'x',
+            permissions[3] == b's',
+        ),
+    );
+    Some(
+        match fields.next().map(str::trim).filter(|path| !path.is_empty()) {
+            Some(path) => region.with_path(path),
+            None => region,
+        },
+    )
+}
+
+/// The memory of a process as a [MemorySource], limited to some of its regions
+struct ProcessMemory {
+    memory: File,
+    regions: Vec<MemoryRegion>,
+}
+
+impl ProcessMemory {
+    /// Reads the memory map of `pid` and opens its memory
+    fn open(pid: u32) -> Result<ProcessMemory, ProcessScanError> {
+        let maps = fs::read_to_string(format!("/proc/{pid}/maps"))
+            .map_err(|source| ProcessScanError::Maps { pid, source })?;
+        let regions = maps
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                parse_maps_line(line).ok_or(ProcessScanError::MalformedMaps {
+                    pid,
+                    line: index + 1,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let memory = File::open(format!("/proc/{pid}/mem"))
+            .map_err(|source| ProcessScanError::Memory { pid, source })?;
+        Ok(ProcessMemory { memory, regions })
+    }
+}
+
+impl MemorySource for ProcessMemory {
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> Result<usize, MemError> {
+        // Reads fail with EIO for unmapped or guarded pages
+        self.memory
+            .read_at(buf, address)
+            .map_err(|_| MemError::Unreadable { address })
+    }
+
+    fn regions(&self) -> Vec<MemoryRegion> {
+        self.regions.clone()
+    }
+}
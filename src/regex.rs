@@ -0,0 +1,298 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{vec, Pattern, PatternElement, String, Vec};
+use core::fmt::Write;
+
+/// Why [Pattern::from_bytes_regex_str] rejected a regular expression
+///
+/// Positions are byte offsets into the regular expression
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum RegexImportError {
+    #[cfg_attr(
+        feature = "std",
+        error("The {construct} at position {position} can't be expressed as a Pattern")
+    )]
+    UnsupportedSyntax {
+        construct: &'static str,
+        position: usize,
+    },
+    #[cfg_attr(
+        feature = "std",
+        error("The regular expression is malformed at position {position}")
+    )]
+    Malformed { position: usize },
+}
+
+impl Pattern {
+    /// Translates the Pattern into the syntax of `regex::bytes::Regex`, e.g. to reuse the signature
+    /// syntax in code built around regular expressions
+    ///
+    /// Literals become escaped bytes, placeholders capture groups matching any byte and ignored
+    /// bytes plain wildcards, so group `n + 1` of the regex holds capture `n`  
+    /// The flags `(?s-u)` make `.` match every byte, including `\n` and bytes that aren't UTF-8  
+    /// The regex finds the same offsets as the Pattern, except that a regex search doesn't report
+    /// overlapping matches
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("E8 ?? __ 0A").unwrap();
+    /// assert_eq!(pattern.to_bytes_regex_source(), r"(?s-u)\xE8(.).\x0A");
+    /// // let regex = regex::bytes::Regex::new(&pattern.to_bytes_regex_source()).unwrap();
+    /// ```
+    pub fn to_bytes_regex_source(&self) -> String {
+        let mut source = String::from("(?s-u)");
+        for element in &self.data {
+            match element {
+                PatternElement::Literal(byte) => {
+                    let _ = write!(source, "\\x{byte:02X}");
+                }
+                PatternElement::Placeholder => source.push_str("(.)"),
+                PatternElement::Ignore => source.push('.'),
+            }
+        }
+        source
+    }
+
+    /// Imports the subset of `regex::bytes` syntax that a Pattern can express, e.g. to migrate
+    /// signature files written as regular expressions
+    ///
+    /// Accepted are:
+    ///
+    /// - Literal bytes, written as `\x41`, `\n`, `\r`, `\t`, escaped punctuation like `\.` or plain
+    ///   characters
+    /// - `.` and `(.)` as placeholders
+    /// - Classes matching a single byte like `[\x41]`, which become literals, and classes matching
+    ///   every byte like `[\x00-\xFF]`, which become placeholders
+    /// - Fixed repetitions like `.{4}` or `\x90{2,2}`
+    /// - Flag groups of `s`, `u` and `-` like the `(?s-u)` written by [Pattern::to_bytes_regex_source],
+    ///   the flags don't change the result
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - The regex uses alternations, anchors, word boundaries, backreferences, groups, classes
+    ///   matching some but not all bytes or repetitions of variable length, e.g. `[\x20-\x7E]` or
+    ///   `.{2,8}`
+    /// - The regex is malformed, e.g. an unclosed class
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Pattern, RegexImportError};
+    /// let pattern = Pattern::from_bytes_regex_str(r"\xE8.{4}[\xC3]").unwrap();
+    /// assert_eq!(pattern.to_string(), "E8 ?? ?? ?? ?? C3");
+    /// assert_eq!(
+    ///     Pattern::from_bytes_regex_str(r"\xE8|\xE9"),
+    ///     Err(RegexImportError::UnsupportedSyntax { construct: "alternation", position: 4 })
+    /// );
+    /// ```
+    pub fn from_bytes_regex_str(regex: &str) -> Result<Pattern, RegexImportError> {
+        let mut parser = RegexParser { regex, position: 0 };
+        let mut data = Vec::new();
+        while parser.position < parser.regex.len() {
+            let start = parser.position;
+            let atom = parser.atom()?;
+            let count = parser.repetition()?;
+            if atom.is_empty() && count != 1 {
+                return malformed(start);
+            }
+            for _ in 0..count {
+                data.extend_from_slice(&atom);
+            }
+        }
+        Ok(Pattern {
+            len: data.len(),
+            data,
+            ..Default::default()
+        })
+    }
+}
+
+struct RegexParser<'r> {
+    regex: &'r str,
+    position: usize,
+}
+
+fn unsupported<T>(construct: &'static str, position: usize) -> Result<T, RegexImportError> {
+    Err(RegexImportError::UnsupportedSyntax {
+        construct,
+        position,
+    })
+}
+
+fn malformed<T>(position: usize) -> Result<T, RegexImportError> {
+    Err(RegexImportError::Malformed { position })
+}
+
+impl<'r> RegexParser<'r> {
+    fn rest(&self) -> &'r [u8] {
+        &self.regex.as_bytes()[self.position..]
+    }
+
+    /// The elements matched by the next atom, empty for flag groups
+    fn atom(&mut self) -> Result<Vec<PatternElement>, RegexImportError> {
+        let start = self.position;
+        let rest = self.rest();
+        if rest.starts_with(b"(.)") {
+            self.position += 3;
+            return Ok(vec![PatternElement::Placeholder]);
+        }
+        if rest.starts_with(b"(?") {
+            let Some(end) = rest.iter().position(|byte| *byte == b')') else {
+                return malformed(start);
+            };
+            if rest[2..end].is_empty() || !rest[2..end].iter().all(|flag| b"su-".contains(flag)) {
+                return unsupported("group", start);
+            }
+            self.position += end + 1;
+            return Ok(Vec::new());
+        }
+        match rest[0] {
+            b'.' => {
+                self.position += 1;
+                Ok(vec![PatternElement::Placeholder])
+            }
+            b'\\' => Ok(vec![PatternElement::Literal(self.escape()?)]),
+            b'[' => self.class(),
+            b'|' => unsupported("alternation", start),
+            b'^' | b'$' => unsupported("anchor", start),
+            b'(' => unsupported("group", start),
+            b'*' | b'+' | b'?' => unsupported("repetition operator", start),
+            b'{' | b')' => malformed(start),
+            _ => {
+                // A plain character, which matches its UTF-8 encoding
+                let len = self.regex[start..].chars().next().map_or(1, char::len_utf8);
+                self.position += len;
+                Ok(rest[..len]
+                    .iter()
+                    .map(|byte| PatternElement::Literal(*byte))
+                    .collect())
+            }
+        }
+    }
+
+    /// The byte of the escape sequence at the current position
+    fn escape(&mut self) -> Result<u8, RegexImportError> {
+        let start = self.position;
+        let Some(&kind) = self.regex.as_bytes().get(start + 1) else {
+            return malformed(start);
+        };
+        self.position += 2;
+        match kind {
+            b'x' => {
+                let byte = self
+                    .regex
+                    .get(start + 2..start + 4)
+                    .filter(|digits| digits.bytes().all(|digit| digit.is_ascii_hexdigit()))
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+                match byte {
+                    Some(byte) => {
+                        self.position += 2;
+                        Ok(byte)
+                    }
+                    None => malformed(start),
+                }
+            }
+            b'n' => Ok(b'\n'),
+            b'r' => Ok(b'\r'),
+            b't' => Ok(b'\t'),
+            b'1'..=b'9' => unsupported("backreference", start),
+            b'b' | b'B' => unsupported("word boundary", start),
+            b'A' | b'z' => unsupported("anchor", start),
+            b'd' | b'D' | b'w' | b'W' | b's' | b'S' | b'p' | b'P' => {
+                unsupported("character class", start)
+            }
+            kind if kind.is_ascii_punctuation() => Ok(kind),
+            _ => malformed(start),
+        }
+    }
+
+    /// A class, which has to match either one byte or all of them
+    fn class(&mut self) -> Result<Vec<PatternElement>, RegexImportError> {
+        let start = self.position;
+        self.position += 1;
+        let negated = self.rest().first() == Some(&b'^');
+        if negated {
+            self.position += 1;
+        }
+        let mut bytes = [false; 256];
+        let mut first = true;
+        loop {
+            let Some(&next) = self.rest().first() else {
+                return malformed(start);
+            };
+            if next == b']' && !first {
+                self.position += 1;
+                break;
+            }
+            first = false;
+            let low = self.class_byte(start)?;
+            let high = match self.rest() {
+                [b'-', next, ..] if *next != b']' => {
+                    self.position += 1;
+                    self.class_byte(start)?
+                }
+                _ => low,
+            };
+            if low > high {
+                return malformed(start);
+            }
+            bytes[usize::from(low)..=usize::from(high)].fill(true);
+        }
+        let matched: Vec<u8> = (0..=255u8)
+            .filter(|byte| bytes[usize::from(*byte)] != negated)
+            .collect();
+        match matched.as_slice() {
+            [byte] => Ok(vec![PatternElement::Literal(*byte)]),
+            all if all.len() == 256 => Ok(vec![PatternElement::Placeholder]),
+            _ => unsupported("character class", start),
+        }
+    }
+
+    /// A single byte inside the class starting at `class`
+    fn class_byte(&mut self, class: usize) -> Result<u8, RegexImportError> {
+        match self.rest() {
+            [b'\\', ..] => self.escape(),
+            [b'[', b':', ..] => unsupported("character class", self.position),
+            [byte, ..] if byte.is_ascii() => {
+                self.position += 1;
+                Ok(*byte)
+            }
+            [_, ..] => unsupported("character class", class),
+            [] => malformed(class),
+        }
+    }
+
+    /// The number of times the previous atom is repeated
+    fn repetition(&mut self) -> Result<usize, RegexImportError> {
+        let start = self.position;
+        if self.rest().first() != Some(&b'{') {
+            return Ok(1);
+        }
+        let Some(end) = self.rest().iter().position(|byte| *byte == b'}') else {
+            return malformed(start);
+        };
+        let bounds = &self.rest()[1..end];
+        let number = |digits: &[u8]| {
+            core::str::from_utf8(digits)
+                .ok()
+                .filter(|digits| digits.bytes().all(|digit| digit.is_ascii_digit()))
+                .and_then(|digits| digits.parse::<usize>().ok())
+        };
+        let (min, max) = match bounds.iter().position(|byte| *byte == b',') {
+            Some(comma) => (number(&bounds[..comma]), number(&bounds[comma + 1..])),
+            None => (number(bounds), number(bounds)),
+        };
+        match (min, max) {
+            (Some(min), Some(max)) if min == max => {
+                self.position += end + 1;
+                Ok(min)
+            }
+            (Some(min), Some(max)) if min < max => unsupported("variable repetition", start),
+            // `{2,}` is a repetition without upper bound
+            (Some(_), None) if bounds.ends_with(b",") => unsupported("variable repetition", start),
+            _ => malformed(start),
+        }
+    }
+}
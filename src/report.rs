@@ -0,0 +1,141 @@
+use crate::{Match, PatternSet};
+use std::fmt::Write;
+
+/// A report of the matches of one or more named patterns, which can be written as CSV or JSON
+///
+/// Both formats have one row per match with the columns `pattern`, `offset`, `length` and `captures`,
+/// where `captures` is the hex encoded captured bytes in pattern order
+///
+/// # Example:
+/// ```
+/// # use binmatch::{MatchOptions, Pattern, Report};
+/// let pattern = Pattern::new("AA ??").unwrap();
+/// let data = vec![0xAA, 0x42];
+/// let mut report = Report::new();
+/// report.add("marker", pattern.find_with(&data, &MatchOptions::new()));
+/// assert_eq!(report.to_csv(), "pattern,offset,length,captures\nmarker,0,2,42\n");
+/// assert_eq!(
+///     report.to_json(),
+///     r#"[{"pattern":"marker","offset":0,"length":2,"captures":"42"}]"#
+/// );
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Report {
+    entries: Vec<(String, Vec<Match>)>,
+}
+
+impl Report {
+    pub fn new() -> Report {
+        Self::default()
+    }
+
+    /// Adds the matches of the pattern called `name`, rows keep the order they were added in
+    pub fn add(&mut self, name: impl Into<String>, matches: Vec<Match>) -> &mut Report {
+        self.entries.push((name.into(), matches));
+        self
+    }
+
+    /// Adds the result of [PatternSet::find_matches], every match is reported under the name of its Pattern
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Pattern, PatternSet, Report};
+    /// let set: PatternSet = [("nop", Pattern::new("90").unwrap())].into_iter().collect();
+    /// let data = vec![0xCC, 0x90];
+    /// let mut report = Report::new();
+    /// report.add_set(&set, set.find_matches(&data));
+    /// assert_eq!(report.to_csv(), "pattern,offset,length,captures\nnop,1,1,\n");
+    /// ```
+    pub fn add_set(&mut self, set: &PatternSet, matches: Vec<(usize, Match)>) -> &mut Report {
+        for (index, m) in matches {
+            let name = set.name(index).unwrap_or_default();
+            self.entries.push((name.to_string(), vec![m]));
+        }
+        self
+    }
+
+    fn rows(&self) -> impl Iterator<Item = (&str, &Match)> {
+        self.entries
+            .iter()
+            .flat_map(|(name, matches)| matches.iter().map(move |m| (name.as_str(), m)))
+    }
+
+    /// Number of matches in the report
+    pub fn len(&self) -> usize {
+        self.rows().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes the report as CSV with a header line, fields are quoted where needed
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("pattern,offset,length,captures\n");
+        for (name, m) in self.rows() {
+            let _ = writeln!(
+                out,
+                "{},{},{},{}",
+                csv_field(name),
+                m.start(),
+                m.len(),
+                hex_captures(m)
+            );
+        }
+        out
+    }
+
+    /// Writes the report as a JSON array with one object per match, the fields are always in the same order
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("[");
+        for (i, (name, m)) in self.rows().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                r#"{{"pattern":{},"offset":{},"length":{},"captures":"{}"}}"#,
+                json_string(name),
+                m.start(),
+                m.len(),
+                hex_captures(m)
+            );
+        }
+        out.push(']');
+        out
+    }
+}
+
+fn hex_captures(m: &Match) -> String {
+    m.captures().iter().fold(String::new(), |mut out, capture| {
+        let _ = write!(out, "{:02x}", capture.value);
+        out
+    })
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut out = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
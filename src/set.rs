@@ -0,0 +1,385 @@
+use crate::prefilter::Prefilter;
+use crate::{vec, Match, MatchBitmap, Pattern, String, Vec};
+use alloc::string::ToString;
+
+/// A collection of named [Pattern]s that are searched for in a single pass over the haystack
+///
+/// Names don't have to be unique, Patterns sharing a name are still searched for separately and
+/// each of their matches is reported under that name  
+/// Patterns added without a name (see [PatternSet::new]) are named after their pattern string
+///
+/// # Example:
+/// ```
+/// # use binmatch::{Pattern, PatternSet};
+/// let set = PatternSet::new(vec![
+///     Pattern::new("00 ??").unwrap(),
+///     Pattern::new("11 22 33").unwrap(),
+/// ]);
+/// let data = vec![0x11, 0x22, 0x33, 0x00, 0x42];
+/// let found: Vec<(usize, usize)> = set
+///     .find_matches(&data)
+///     .iter()
+///     .map(|(index, m)| (*index, m.start()))
+///     .collect();
+/// assert_eq!(found, vec![(1, 0), (0, 3)]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PatternSet {
+    patterns: Vec<Pattern>,
+    names: Vec<String>,
+    priorities: Vec<i32>,
+    prefilter: Prefilter,
+}
+
+/// Decides what happens when matches of different Patterns of a [PatternSet] overlap
+///
+/// Patterns rank by their priority (see [PatternSet::set_priority]), then by insertion order
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum MatchPolicy {
+    /// Every match of every Pattern is reported
+    #[default]
+    All,
+    /// A match is dropped when it overlaps a reported match of a Pattern that ranks higher
+    ///
+    /// Overlapping matches of the same Pattern are all reported
+    FirstWins,
+    /// Reports the longest of the matches with the leftmost start, then continues after its end
+    ///
+    /// Matches of equal length are decided by the ranking of their Patterns, the result never
+    /// overlaps, like the tokens of a lexer
+    LeftmostLongest,
+}
+
+impl PatternSet {
+    pub fn new(patterns: Vec<Pattern>) -> PatternSet {
+        let names = patterns.iter().map(|pattern| pattern.to_string()).collect();
+        let priorities = vec![0; patterns.len()];
+        Self::from_parts(patterns, names, priorities)
+    }
+
+    /// Builds the set with the prefilter only once, all three have to be of the same length
+    pub(crate) fn from_parts(
+        patterns: Vec<Pattern>,
+        names: Vec<String>,
+        priorities: Vec<i32>,
+    ) -> PatternSet {
+        let prefilter = Prefilter::new(&patterns);
+        Self {
+            patterns,
+            names,
+            priorities,
+            prefilter,
+        }
+    }
+
+    /// Adds `pattern` under `name` and returns its index in the set
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Pattern, PatternSet};
+    /// let mut set = PatternSet::default();
+    /// set.insert("call", Pattern::new("E8 ?? ?? ?? ??").unwrap());
+    /// set.insert("ret", Pattern::new("C3").unwrap());
+    /// let data = vec![0x90, 0xC3];
+    /// let found = set.find_named(&data);
+    /// assert_eq!(found[0].0, "ret");
+    /// ```
+    pub fn insert(&mut self, name: impl Into<String>, pattern: Pattern) -> usize {
+        self.patterns.push(pattern);
+        self.names.push(name.into());
+        self.priorities.push(0);
+        self.prefilter = Prefilter::new(&self.patterns);
+        self.patterns.len() - 1
+    }
+
+    /// The name of the Pattern at `index`, `None` if `index` is out of bounds
+    #[inline(always)]
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.names.get(index).map(String::as_str)
+    }
+
+    /// The priority of the Pattern at `index`, `None` if `index` is out of bounds
+    #[inline(always)]
+    pub fn priority(&self, index: usize) -> Option<i32> {
+        self.priorities.get(index).copied()
+    }
+
+    /// Sets the priority of the Pattern at `index` for [MatchPolicy::FirstWins], higher priorities win
+    ///
+    /// Every Pattern starts with priority 0, so by default the Pattern inserted first wins  
+    /// Returns `false` and changes nothing if `index` is out of bounds
+    pub fn set_priority(&mut self, index: usize, priority: i32) -> bool {
+        match self.priorities.get_mut(index) {
+            Some(slot) => {
+                *slot = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Finds all matches of all Patterns while reading the haystack only once
+    ///
+    /// Every match is returned together with the index of its Pattern in the set  
+    /// The matches are ordered by their start and then by the index of the Pattern, Patterns
+    /// longer than the rest of the haystack are skipped near its end
+    ///
+    /// Candidate starts come from a scan for the longest literal run of every Pattern, only
+    /// Patterns without any literal are tested at every position
+    pub fn find_matches(&self, haystack: &[u8]) -> Vec<(usize, Match)> {
+        let mut matches = Vec::new();
+        self.prefilter
+            .candidates(&self.patterns, haystack, |index, start| {
+                if let Some(m) = self.patterns[index].match_at_unchecked(haystack, start) {
+                    matches.push((index, m));
+                }
+            });
+        matches.sort_by_key(|(index, m)| (m.start(), *index));
+        matches
+    }
+
+    /// Counts the matches of every Pattern without building any [Match]
+    ///
+    /// There is one entry per Pattern in the order of their indices, including Patterns that never matched
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Pattern, PatternSet};
+    /// let set = PatternSet::new(vec![Pattern::new("00").unwrap(), Pattern::new("FF").unwrap()]);
+    /// assert_eq!(set.count_matches(&[0x00, 0x01, 0x00]), vec![(0, 2), (1, 0)]);
+    /// ```
+    pub fn count_matches(&self, haystack: &[u8]) -> Vec<(usize, usize)> {
+        let mut counts: Vec<(usize, usize)> = (0..self.patterns.len()).map(|i| (i, 0)).collect();
+        self.prefilter
+            .candidates(&self.patterns, haystack, |index, start| {
+                let pattern = &self.patterns[index];
+                if pattern.matches_window(&haystack[start..start + pattern.len]) {
+                    counts[index].1 += 1;
+                }
+            });
+        counts
+    }
+
+    /// Same as [PatternSet::find_matches], but overlapping matches are resolved according to `policy`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{MatchPolicy, Pattern, PatternSet};
+    /// let mut set = PatternSet::default();
+    /// set.insert("any call", Pattern::new("E8 ?? ?? ?? ??").unwrap());
+    /// set.insert("call +0", Pattern::new("E8 00 00 00 00").unwrap());
+    /// set.set_priority(1, 10);
+    /// let data = vec![0xE8, 0x00, 0x00, 0x00, 0x00];
+    /// let found = set.find_matches_with_policy(&data, MatchPolicy::FirstWins);
+    /// assert_eq!(found.len(), 1);
+    /// assert_eq!(set.name(found[0].0), Some("call +0"));
+    /// ```
+    pub fn find_matches_with_policy(
+        &self,
+        haystack: &[u8],
+        policy: MatchPolicy,
+    ) -> Vec<(usize, Match)> {
+        let matches = self.find_matches(haystack);
+        match policy {
+            MatchPolicy::All => matches,
+            MatchPolicy::FirstWins => self.first_wins(haystack, matches),
+            MatchPolicy::LeftmostLongest => self.leftmost_longest(matches),
+        }
+    }
+
+    /// Same as [PatternSet::find_matches_with_policy] with [MatchPolicy::LeftmostLongest]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Pattern, PatternSet};
+    /// let set = PatternSet::new(vec![
+    ///     Pattern::new("61").unwrap(),
+    ///     Pattern::new("61 61").unwrap(),
+    ///     Pattern::new("62").unwrap(),
+    /// ]);
+    /// let tokens: Vec<(usize, usize)> = set
+    ///     .find_leftmost_longest(b"aaab")
+    ///     .iter()
+    ///     .map(|(index, m)| (*index, m.start()))
+    ///     .collect();
+    /// assert_eq!(tokens, vec![(1, 0), (0, 2), (2, 3)]);
+    /// ```
+    pub fn find_leftmost_longest(&self, haystack: &[u8]) -> Vec<(usize, Match)> {
+        self.find_matches_with_policy(haystack, MatchPolicy::LeftmostLongest)
+    }
+
+    /// Sort key of a Pattern for the policies, lower keys win
+    fn rank(&self, index: usize) -> (core::cmp::Reverse<i32>, usize) {
+        (core::cmp::Reverse(self.priorities[index]), index)
+    }
+
+    fn first_wins(&self, haystack: &[u8], matches: Vec<(usize, Match)>) -> Vec<(usize, Match)> {
+        let mut ranking: Vec<usize> = (0..self.patterns.len()).collect();
+        ranking.sort_by_key(|&index| self.rank(index));
+        let mut by_pattern: Vec<Vec<Match>> = vec![Vec::new(); self.patterns.len()];
+        for (index, m) in matches {
+            by_pattern[index].push(m);
+        }
+        // Bytes covered by matches of the Patterns that were already resolved
+        let mut covered = MatchBitmap::new(haystack.len());
+        let mut kept = Vec::new();
+        for index in ranking {
+            let resolved = core::mem::take(&mut by_pattern[index]);
+            let start = kept.len();
+            kept.extend(
+                resolved
+                    .into_iter()
+                    .filter(|m| !m.range().any(|offset| covered.get(offset)))
+                    .map(|m| (index, m)),
+            );
+            for (_, m) in &kept[start..] {
+                m.range().for_each(|offset| covered.set(offset));
+            }
+        }
+        kept.sort_by_key(|(index, m)| (m.start(), *index));
+        kept
+    }
+
+    /// Expects `matches` ordered by their start like [PatternSet::find_matches] returns them
+    fn leftmost_longest(&self, matches: Vec<(usize, Match)>) -> Vec<(usize, Match)> {
+        let mut kept: Vec<(usize, Match)> = Vec::new();
+        let mut next = 0;
+        for (index, m) in matches {
+            if m.start() < next {
+                continue;
+            }
+            if let Some((best_index, best)) = kept.last_mut() {
+                if best.start() == m.start() {
+                    if (core::cmp::Reverse(m.len()), self.rank(index))
+                        < (core::cmp::Reverse(best.len()), self.rank(*best_index))
+                    {
+                        (*best_index, *best) = (index, m);
+                    }
+                    continue;
+                }
+                next = best.end().max(best.start() + 1);
+                if m.start() < next {
+                    continue;
+                }
+            }
+            kept.push((index, m));
+        }
+        kept
+    }
+
+    /// [PatternSet::find_matches] without the prefilter, testing every Pattern at every position
+    #[cfg(all(test, feature = "std"))]
+    pub(crate) fn find_matches_naive(&self, haystack: &[u8]) -> Vec<(usize, Match)> {
+        let mut matches = Vec::new();
+        for start in 0..=haystack.len() {
+            let remaining = haystack.len() - start;
+            for (index, pattern) in self.patterns.iter().enumerate() {
+                if pattern.len > remaining {
+                    continue;
+                }
+                if let Some(m) = pattern.match_at_unchecked(haystack, start) {
+                    matches.push((index, m));
+                }
+            }
+        }
+        matches
+    }
+
+    /// Same as [PatternSet::find_matches], but every match is returned together with the name of its Pattern
+    pub fn find_named(&self, haystack: &[u8]) -> Vec<(&str, Match)> {
+        self.find_matches(haystack)
+            .into_iter()
+            .map(|(index, m)| (self.names[index].as_str(), m))
+            .collect()
+    }
+
+    /// The Patterns of the set, in the order their indices refer to
+    #[inline(always)]
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// Removes every Pattern whose matches are all matched by another Pattern of the set as well,
+    /// see [Pattern::is_subset_of]
+    ///
+    /// Of Patterns that match exactly the same windows, e.g. `00 ??` and `00 __`, the first one is
+    /// kept  
+    /// Returns the index of every removed Pattern together with the index of a kept Pattern that
+    /// contains it, both from before the removal. The kept Patterns keep their order, names and
+    /// priorities
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::PatternSet;
+    /// # use binmatch::Pattern;
+    /// let mut set: PatternSet = [("mov", "8B 45 08"), ("mov_any", "8B ?? ??"), ("ret", "C3")]
+    ///     .into_iter()
+    ///     .map(|(name, pattern)| (name, Pattern::new(pattern).unwrap()))
+    ///     .collect();
+    /// assert_eq!(set.deduplicate(), vec![(0, 1)]);
+    /// assert_eq!(set.name(0), Some("mov_any"));
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn deduplicate(&mut self) -> Vec<(usize, usize)> {
+        let patterns = &self.patterns;
+        let redundant: Vec<bool> = (patterns.iter().enumerate())
+            .map(|(index, pattern)| {
+                patterns.iter().enumerate().any(|(other_index, other)| {
+                    other_index != index
+                        && pattern.is_subset_of(other)
+                        && (other_index < index || !other.is_subset_of(pattern))
+                })
+            })
+            .collect();
+        // Containment is transitive, so some kept Pattern contains every removed one
+        let removed: Vec<(usize, usize)> = (0..patterns.len())
+            .filter(|&index| redundant[index])
+            .filter_map(|index| {
+                let kept = (0..patterns.len()).find(|&other| {
+                    !redundant[other] && patterns[index].is_subset_of(&patterns[other])
+                })?;
+                Some((index, kept))
+            })
+            .collect();
+
+        let (mut patterns, mut names, mut priorities) = (Vec::new(), Vec::new(), Vec::new());
+        let parts = self.patterns.drain(..).zip(self.names.drain(..));
+        for ((pattern, name), (&priority, &redundant)) in
+            parts.zip(self.priorities.iter().zip(&redundant))
+        {
+            if !redundant {
+                patterns.push(pattern);
+                names.push(name);
+                priorities.push(priority);
+            }
+        }
+        *self = PatternSet::from_parts(patterns, names, priorities);
+        removed
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+impl From<Vec<Pattern>> for PatternSet {
+    fn from(patterns: Vec<Pattern>) -> PatternSet {
+        PatternSet::new(patterns)
+    }
+}
+
+impl<N: Into<String>> FromIterator<(N, Pattern)> for PatternSet {
+    fn from_iter<I: IntoIterator<Item = (N, Pattern)>>(iter: I) -> PatternSet {
+        let mut set = PatternSet::default();
+        for (name, pattern) in iter {
+            set.insert(name, pattern);
+        }
+        set
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::{Pattern, PatternElement, Vec};
+use core::ops::Range;
+
+/// Why [Pattern::unique_signature] couldn't build a signature
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum SigGenError {
+    #[cfg_attr(
+        feature = "std",
+        error("Offset {at} is outside of the {len} bytes of the haystack")
+    )]
+    OutOfBounds { at: usize, len: usize },
+    #[cfg_attr(
+        feature = "std",
+        error("No signature of up to {max_len} bytes matches only at the requested offset")
+    )]
+    NotUnique { max_len: usize },
+}
+
+/// Options for [Pattern::unique_signature]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SigGenOptions {
+    pub(crate) max_len: usize,
+    pub(crate) wildcards: Vec<Range<usize>>,
+}
+
+impl Default for SigGenOptions {
+    fn default() -> SigGenOptions {
+        Self {
+            max_len: 64,
+            wildcards: Vec::new(),
+        }
+    }
+}
+
+impl SigGenOptions {
+    pub fn new() -> SigGenOptions {
+        Self::default()
+    }
+
+    /// Longest signature to try, 64 bytes by default
+    pub fn max_len(mut self, max_len: usize) -> SigGenOptions {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Turns the bytes at the haystack offsets `range` into placeholders (`??`), e.g. relocated
+    /// addresses that differ between builds
+    ///
+    /// Can be called multiple times, the ranges may overlap
+    pub fn wildcard(mut self, range: Range<usize>) -> SigGenOptions {
+        self.wildcards.push(range);
+        self
+    }
+}
+
+impl Pattern {
+    /// Builds the shortest Pattern starting at `at` that matches nowhere else in `haystack`
+    ///
+    /// The candidate grows one byte at a time, copying the bytes of the haystack except for the
+    /// [wildcarded](SigGenOptions::wildcard) ones, until every other start is ruled out  
+    /// Starts too close to the end of the haystack can't match a longer candidate, so they are
+    /// ruled out as well
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - `at` is not inside of `haystack`
+    /// - No candidate up to [SigGenOptions::max_len] bytes is unique, e.g. because the bytes at `at`
+    ///   are repeated elsewhere
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Pattern, SigGenOptions};
+    /// let binary = [0x55, 0x48, 0x89, 0xE5, 0x90, 0x55, 0x48, 0x89, 0xEC, 0x90];
+    /// let options = SigGenOptions::new().wildcard(7..8);
+    /// let signature = Pattern::unique_signature(&binary, 5, &options).unwrap();
+    /// assert_eq!(signature.to_string(), "55 48 ?? EC");
+    /// assert_eq!(signature.find_match_starts(&binary), vec![5]);
+    /// ```
+    pub fn unique_signature(
+        haystack: &[u8],
+        at: usize,
+        options: &SigGenOptions,
+    ) -> Result<Pattern, SigGenError> {
+        if at >= haystack.len() {
+            return Err(SigGenError::OutOfBounds {
+                at,
+                len: haystack.len(),
+            });
+        }
+        let max_len = options.max_len.min(haystack.len() - at);
+        let mut data = Vec::new();
+        // Starts that match the candidate so far, `at` among them
+        let mut candidates: Vec<usize> = (0..haystack.len()).collect();
+        for index in 0..max_len {
+            let offset = at + index;
+            let element = match options
+                .wildcards
+                .iter()
+                .any(|range| range.contains(&offset))
+            {
+                true => PatternElement::Placeholder,
+                false => PatternElement::Literal(haystack[offset]),
+            };
+            candidates.retain(|&start| match (haystack.get(start + index), element) {
+                (None, _) => false,
+                (Some(byte), PatternElement::Literal(literal)) => *byte == literal,
+                (Some(_), _) => true,
+            });
+            data.push(element);
+            if candidates.len() == 1 {
+                let signature = Pattern::from_elements(data);
+                debug_assert_eq!(signature.find_match_starts(haystack), [at]);
+                return Ok(signature);
+            }
+        }
+        Err(SigGenError::NotUnique {
+            max_len: options.max_len,
+        })
+    }
+}
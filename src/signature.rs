@@ -0,0 +1,102 @@
+use crate::{BinmatchError, Pattern, PatternSet};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Why a signature file could not be loaded, every variant carries the 1-based line number
+#[derive(Error, Debug)]
+pub enum SignatureFileError {
+    #[error("Could not read line {line}: {source}")]
+    Io { line: usize, source: io::Error },
+    #[error("Line {line} is not of the form `name: pattern`")]
+    MissingName { line: usize },
+    #[error("Invalid pattern on line {line}: {source}")]
+    Pattern { line: usize, source: BinmatchError },
+    #[error("The name {name} on line {line} was already used on line {first}")]
+    DuplicateName {
+        line: usize,
+        first: usize,
+        name: String,
+    },
+}
+
+impl PatternSet {
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Loads a signature file with one `name: pattern` per line
+    ///
+    /// Names and patterns are trimmed, blank lines and everything after a `#` are ignored  
+    /// A file without any signature results in an empty set
+    ///
+    /// # Returns an Error when:
+    ///
+    /// - A line can't be read or has no `:`, or its name is empty
+    /// - A pattern can't be parsed by [Pattern::new]
+    /// - A name was already used on an earlier line
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::PatternSet;
+    /// let file = "# x86 prologues\npush_rbp: 55 48 89 E5\n\nsub_rsp: 48 83 EC ?? # frame size\n";
+    /// let set = PatternSet::from_reader(file.as_bytes()).unwrap();
+    /// assert_eq!(set.name(1), Some("sub_rsp"));
+    /// ```
+    pub fn from_reader(reader: impl BufRead) -> Result<PatternSet, SignatureFileError> {
+        let mut set = PatternSet::default();
+        let mut first_lines: Vec<usize> = Vec::new();
+        for (index, text) in reader.lines().enumerate() {
+            let line = index + 1;
+            let text = text.map_err(|source| SignatureFileError::Io { line, source })?;
+            let text = text.split('#').next().unwrap_or_default().trim();
+            if text.is_empty() {
+                continue;
+            }
+            let Some((name, pattern)) = text.split_once(':') else {
+                return Err(SignatureFileError::MissingName { line });
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(SignatureFileError::MissingName { line });
+            }
+            if let Some(index) = (0..set.len()).find(|&index| set.name(index) == Some(name)) {
+                return Err(SignatureFileError::DuplicateName {
+                    line,
+                    first: first_lines[index],
+                    name: name.to_string(),
+                });
+            }
+            let pattern = Pattern::try_new(pattern.trim())
+                .map_err(|source| SignatureFileError::Pattern { line, source })?;
+            set.insert(name, pattern);
+            first_lines.push(line);
+        }
+        Ok(set)
+    }
+
+    /// <div class="warning"> Only available using the <code>std</code> feature </div>
+    ///
+    /// Writes the set in the format read by [PatternSet::from_reader]
+    ///
+    /// Loading the output again gives the same set, as long as the names are unique, already trimmed
+    /// and contain neither `:`, `#` nor line breaks
+    pub fn to_writer(&self, mut writer: impl Write) -> io::Result<()> {
+        for (index, pattern) in self.patterns().iter().enumerate() {
+            writeln!(
+                writer,
+                "{}: {}",
+                self.name(index).unwrap_or_default(),
+                pattern
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a signature file, e.g. one embedded with `include_str!`, see [PatternSet::from_reader]
+impl FromStr for PatternSet {
+    type Err = SignatureFileError;
+
+    fn from_str(file: &str) -> Result<PatternSet, SignatureFileError> {
+        PatternSet::from_reader(file.as_bytes())
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{Arc, LintWarning, Pattern, String, Vec};
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::ops::Range;
+
+/// The string a Pattern was parsed from and the byte range of every element in it, if it was
+/// parsed at all
+///
+/// Only used for diagnostics, so it never takes part in comparisons or hashing: Patterns parsed
+/// from differently formatted strings are still equal, as are parsed and decoded ones
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PatternSource(Option<Arc<SourceText>>);
+
+#[derive(Debug)]
+struct SourceText {
+    text: String,
+    spans: Vec<Range<usize>>,
+}
+
+impl PatternSource {
+    pub(crate) fn new(text: &str, spans: Vec<Range<usize>>) -> PatternSource {
+        PatternSource(Some(Arc::new(SourceText {
+            text: text.into(),
+            spans,
+        })))
+    }
+}
+
+impl PartialEq for PatternSource {
+    fn eq(&self, _: &PatternSource) -> bool {
+        true
+    }
+}
+
+impl Eq for PatternSource {}
+
+impl PartialOrd for PatternSource {
+    fn partial_cmp(&self, other: &PatternSource) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PatternSource {
+    fn cmp(&self, _: &PatternSource) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl Hash for PatternSource {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl Pattern {
+    /// The string the Pattern was parsed from, exactly as it was passed to [Pattern::new]
+    ///
+    /// `None` for Patterns that weren't parsed from a string, e.g. decoded or generated ones  
+    /// The source is only kept for diagnostics, it is ignored when comparing or hashing Patterns
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("48 8b  ??").unwrap();
+    /// assert_eq!(pattern.source(), Some("48 8b  ??"));
+    /// assert_eq!(pattern, Pattern::new("488B??").unwrap());
+    /// ```
+    pub fn source(&self) -> Option<&str> {
+        self.source.0.as_ref().map(|source| source.text.as_str())
+    }
+
+    /// The byte range of the element at `index` in [Pattern::source]
+    ///
+    /// `None` if the Pattern has no source or `index` is out of bounds
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("48 8b  ??").unwrap();
+    /// assert_eq!(pattern.element_span(2), Some(7..9));
+    /// assert_eq!(pattern.element_span(3), None);
+    /// ```
+    pub fn element_span(&self, index: usize) -> Option<Range<usize>> {
+        self.source.0.as_ref()?.spans.get(index).cloned()
+    }
+
+    /// The byte range in [Pattern::source] a [LintWarning] refers to, e.g. to underline it
+    ///
+    /// Warnings about the ends of the Pattern cover those elements, all other warnings the whole
+    /// Pattern  
+    /// `None` if the Pattern has no source or is empty
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("__ __ 48 8B 05 C3").unwrap();
+    /// let warning = pattern.lint()[0];
+    /// assert_eq!(pattern.warning_span(&warning), Some(0..5));
+    /// ```
+    pub fn warning_span(&self, warning: &LintWarning) -> Option<Range<usize>> {
+        let elements = match *warning {
+            LintWarning::LeadingIgnores(count) => 0..count,
+            LintWarning::TrailingIgnores(count) => self.len.saturating_sub(count)..self.len,
+            _ => 0..self.len,
+        };
+        let first = self.element_span(elements.start)?;
+        let last = self.element_span(elements.end.checked_sub(1)?)?;
+        Some(first.start..last.end)
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{vec, Match, Pattern, PatternElement, Vec};
+
+/// Statistics collected by [Pattern::find_with_stats]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct ScanStats {
+    /// Number of candidate windows the Pattern was tested against
+    pub windows_checked: u64,
+    /// Number of windows that matched
+    pub matches: u64,
+    /// For every pattern element, the number of windows it rejects on its own  
+    /// Each element is checked independently of the others, so the counts don't depend on the order of the elements
+    pub rejections_by_element: Vec<u64>,
+}
+
+impl ScanStats {
+    /// The pattern index that rejected the most windows, `None` if nothing was rejected
+    pub fn most_selective_element(&self) -> Option<usize> {
+        self.rejections_by_element
+            .iter()
+            .enumerate()
+            .filter(|(_, rejections)| **rejections > 0)
+            .max_by_key(|(index, rejections)| (**rejections, core::cmp::Reverse(*index)))
+            .map(|(index, _)| index)
+    }
+}
+
+impl Pattern {
+    /// Same as a default [Pattern::find_with], but also counts how many windows every element rejects  
+    /// This is slower than a normal scan, the normal scan doesn't pay for any of it
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ?? 7F").unwrap();
+    /// let data = vec![0x00, 0x01, 0x7F, 0x00, 0x02, 0x00];
+    /// let (matches, stats) = pattern.find_with_stats(&data);
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(stats.windows_checked, 4);
+    /// assert_eq!(stats.rejections_by_element, vec![2, 0, 3]);
+    /// ```
+    pub fn find_with_stats(&self, haystack: &[u8]) -> (Vec<Match>, ScanStats) {
+        let mut stats = ScanStats {
+            rejections_by_element: vec![0; self.len],
+            ..ScanStats::default()
+        };
+        let mut matches = Vec::new();
+        // An empty Pattern never matches, see Pattern::find_with
+        let Some(last) = haystack
+            .len()
+            .checked_sub(self.len)
+            .filter(|_| self.len > 0)
+        else {
+            return (matches, stats);
+        };
+        for start in 0..=last {
+            stats.windows_checked += 1;
+            let mut rejected = false;
+            for (index, element) in self.data.iter().enumerate() {
+                if let PatternElement::Literal(expected) = element {
+                    if haystack[start + index] != *expected {
+                        stats.rejections_by_element[index] += 1;
+                        rejected = true;
+                    }
+                }
+            }
+            if !rejected {
+                stats.matches += 1;
+                matches.extend(self.match_at_unchecked(haystack, start));
+            }
+        }
+        (matches, stats)
+    }
+
+    /// Suggests the literal that filters out the most windows as an anchor for a prefilter, as `(pattern index, byte)`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ?? 7F").unwrap();
+    /// let (_, stats) = pattern.find_with_stats(&[0x00, 0x01, 0x7F, 0x00, 0x02, 0x00]);
+    /// assert_eq!(pattern.suggest_anchor(&stats), Some((2, 0x7F)));
+    /// ```
+    pub fn suggest_anchor(&self, stats: &ScanStats) -> Option<(usize, u8)> {
+        let index = stats.most_selective_element()?;
+        match self.data.get(index)? {
+            PatternElement::Literal(byte) => Some((index, *byte)),
+            PatternElement::Placeholder | PatternElement::Ignore => None,
+        }
+    }
+}
+
+impl Pattern {
+    /// How strongly the Pattern constrains a window, in bits
+    ///
+    /// Every literal contributes 8 bits, placeholders (`??`) and ignored bytes (`__`) contribute
+    /// nothing, since they accept any byte  
+    /// A window of uniformly random bytes matches with a probability of `2^-specificity`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// assert_eq!(Pattern::new("00 ?? FF").unwrap().specificity(), 16.0);
+    /// assert_eq!(Pattern::new("?? __").unwrap().specificity(), 0.0);
+    /// ```
+    pub fn specificity(&self) -> f64 {
+        8.0 * self.literal_count() as f64
+    }
+
+    /// The expected number of matches in `haystack_len` uniformly random bytes
+    ///
+    /// This is the number of windows, `haystack_len - len() + 1`, times the probability of a single
+    /// window matching, see [Pattern::specificity]. Overlapping windows aren't independent, but the
+    /// expected value is exact regardless  
+    /// 0 if the haystack is shorter than the Pattern or the Pattern is empty, an empty Pattern never
+    /// matches
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("00 ?? FF").unwrap();
+    /// // (1 MiB - 2 windows) / 2^16
+    /// assert_eq!(pattern.expected_matches(1 << 20), 15.999969482421875);
+    /// ```
+    pub fn expected_matches(&self, haystack_len: usize) -> f64 {
+        let Some(windows) = haystack_len.checked_sub(self.len).filter(|_| self.len > 0) else {
+            return 0.0;
+        };
+        // Dividing by a power of two is exact, no need for powi from std
+        (0..self.literal_count()).fold(windows as f64 + 1.0, |expected, _| expected / 256.0)
+    }
+
+    fn literal_count(&self) -> usize {
+        self.data
+            .iter()
+            .filter(|element| matches!(element, PatternElement::Literal(_)))
+            .count()
+    }
+}
@@ -0,0 +1,282 @@
+use crate::{Captures, Match, MatchOptions, Pattern, Vec};
+
+/// A match inside a stream, e.g. a file too large to be searched as a slice
+///
+/// Unlike [Match] the offset is a `u64` stream position and the matched bytes are always stored,
+/// since the searched data is gone once the stream moved on  
+/// The [Captures] are relative to [FileMatch::bytes], so `capture.haystack_offset` is the index into
+/// the matched bytes and `offset() + capture.haystack_offset` the stream position
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct FileMatch {
+    offset: u64,
+    bytes: Vec<u8>,
+    captures: Captures,
+}
+
+impl FileMatch {
+    /// Converts a match found in `haystack`, where `haystack` starts at the stream position `position`,
+    /// e.g. a region read from the middle of a file
+    ///
+    /// `None` if the stream position of the match doesn't fit into a `u64`
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{FileMatch, MatchOptions, Pattern};
+    /// let pattern = Pattern::new("AA ??").unwrap();
+    /// let region = vec![0x00, 0xAA, 0x01];
+    /// let found = pattern.find_with(&region, &MatchOptions::new());
+    /// let found = FileMatch::from_match(0x1_0000_0000, &found[0], &region).unwrap();
+    /// assert_eq!(found.offset(), 0x1_0000_0001);
+    /// assert_eq!(found.captures().get(0).unwrap().haystack_offset, 1);
+    /// ```
+    pub fn from_match(position: u64, m: &Match, haystack: &[u8]) -> Option<FileMatch> {
+        let offset = position.checked_add(u64::try_from(m.start()).ok()?)?;
+        offset.checked_add(u64::try_from(m.len()).ok()?)?;
+        Some(FileMatch::new(offset, m, haystack))
+    }
+
+    /// `m` is a match inside `buf`, starting at the stream position `offset`
+    pub(crate) fn new(offset: u64, m: &Match, buf: &[u8]) -> FileMatch {
+        let mut captures = m.captures().clone();
+        captures.unshift(m.start());
+        Self {
+            offset,
+            bytes: buf[m.range()].to_vec(),
+            captures,
+        }
+    }
+
+    /// Stream position of the first byte covered by the match
+    #[inline(always)]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Stream position one past the last byte covered by the match
+    #[inline(always)]
+    pub fn end(&self) -> u64 {
+        self.offset + self.bytes.len() as u64
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Every byte covered by the match, including the ones at ignored (`__`) positions
+    #[inline(always)]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The values matched by placeholders (`??`), relative to [FileMatch::bytes]
+    #[inline(always)]
+    pub fn captures(&self) -> &Captures {
+        &self.captures
+    }
+}
+
+/// Searches data that arrives in chunks, see [Pattern::stream_matcher]
+///
+/// Only the last `len() - 1` bytes are kept between pushes, so matches crossing chunk boundaries are
+/// found exactly once, no matter how the stream is split
+///
+/// # Async readers:
+///
+/// The matcher owns all of its state and never blocks, so it can be driven from any async read
+/// loop. Dropping the future between two pushes loses nothing but the bytes that weren't pushed yet
+/// ```ignore
+/// let mut matcher = pattern.stream_matcher();
+/// let mut chunk = vec![0; 64 * 1024];
+/// loop {
+///     let read = reader.read(&mut chunk).await?;
+///     if read == 0 {
+///         break;
+///     }
+///     for found in matcher.push(&chunk[..read]) {
+///         handle(found).await;
+///     }
+/// }
+/// matcher.finish();
+/// ```
+#[derive(Clone, Debug)]
+pub struct StreamMatcher<'p> {
+    pattern: &'p Pattern,
+    buf: Vec<u8>,
+    // Stream position of buf[0] and of the first start that wasn't scanned yet
+    buf_offset: u64,
+    unscanned: u64,
+    match_count: u64,
+}
+
+/// A checkpoint of a [StreamMatcher], see [StreamMatcher::save_state]
+///
+/// The fields are plain values, so the state can be persisted in any format and restored with
+/// [ScanState::new]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct ScanState {
+    offset: u64,
+    carry: Vec<u8>,
+    match_count: u64,
+}
+
+impl ScanState {
+    /// Restores a state from its parts, `None` if `carry` is longer than `offset`
+    pub fn new(offset: u64, carry: Vec<u8>, match_count: u64) -> Option<ScanState> {
+        (carry.len() as u64 <= offset).then_some(Self {
+            offset,
+            carry,
+            match_count,
+        })
+    }
+
+    /// Stream position reached, the input continues here after [StreamMatcher::resume]
+    #[inline(always)]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The last bytes before [ScanState::offset] that may still be the start of a match
+    #[inline(always)]
+    pub fn carry(&self) -> &[u8] {
+        &self.carry
+    }
+
+    /// Number of matches reported before the checkpoint
+    #[inline(always)]
+    pub fn match_count(&self) -> u64 {
+        self.match_count
+    }
+}
+
+impl<'p> StreamMatcher<'p> {
+    /// Appends `chunk` to the stream and returns the matches completed by it, in ascending order
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<FileMatch> {
+        self.buf.extend_from_slice(chunk);
+        let found = self
+            .pattern
+            .find_with(&self.buf, &MatchOptions::new())
+            .iter()
+            .filter_map(|m| FileMatch::from_match(self.buf_offset, m, &self.buf))
+            .filter(|found| found.offset() >= self.unscanned)
+            .collect::<Vec<FileMatch>>();
+        self.match_count = self.match_count.saturating_add(found.len() as u64);
+        let len = self.pattern.len;
+        self.unscanned =
+            (self.buf_offset).saturating_add((self.buf.len() + 1).saturating_sub(len) as u64);
+        let drained = self.buf.len() - self.buf.len().min(len.saturating_sub(1));
+        self.buf.drain(..drained);
+        self.buf_offset = self.buf_offset.saturating_add(drained as u64);
+        found
+    }
+
+    /// Number of bytes pushed so far, saturating at `u64::MAX`
+    #[inline(always)]
+    pub fn position(&self) -> u64 {
+        self.buf_offset.saturating_add(self.buf.len() as u64)
+    }
+
+    /// Number of matches reported so far, including those before [StreamMatcher::resume]
+    #[inline(always)]
+    pub fn match_count(&self) -> u64 {
+        self.match_count
+    }
+
+    /// Saves a checkpoint to continue the search later with [StreamMatcher::resume], e.g. after the
+    /// scan of a disk image was interrupted
+    ///
+    /// The state holds at most `len() - 1` carried over bytes, so it stays small no matter how large
+    /// the pushed chunks were
+    pub fn save_state(&self) -> ScanState {
+        ScanState {
+            offset: self.position(),
+            carry: self.buf.clone(),
+            match_count: self.match_count,
+        }
+    }
+
+    /// Continues a search from a checkpoint saved by [StreamMatcher::save_state]
+    ///
+    /// The carried over bytes are restored, so the input continues at [ScanState::offset]: seek it
+    /// there and push the rest of the stream. The matches are the same as those of an uninterrupted
+    /// search  
+    /// `state` has to be saved by a matcher of the same Pattern, otherwise matches crossing the
+    /// checkpoint may be missed
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::{Pattern, StreamMatcher};
+    /// let pattern = Pattern::new("AA BB CC").unwrap();
+    /// let mut matcher = pattern.stream_matcher();
+    /// matcher.push(&[0x00, 0xAA, 0xBB]);
+    /// let state = matcher.save_state();
+    /// assert_eq!(state.offset(), 3);
+    /// assert_eq!(state.carry(), &[0xAA, 0xBB]);
+    ///
+    /// let mut matcher = StreamMatcher::resume(&pattern, state);
+    /// assert_eq!(matcher.push(&[0xCC])[0].offset(), 1);
+    /// assert_eq!(matcher.match_count(), 1);
+    /// ```
+    pub fn resume(pattern: &Pattern, state: ScanState) -> StreamMatcher<'_> {
+        let mut carry = state.carry;
+        carry.drain(..carry.len() - carry.len().min(pattern.len.saturating_sub(1)));
+        let buf_offset = state.offset - carry.len() as u64;
+        StreamMatcher {
+            pattern,
+            buf: carry,
+            buf_offset,
+            unscanned: buf_offset,
+            match_count: state.match_count,
+        }
+    }
+
+    /// Ends the stream and returns the matches that weren't reported yet
+    ///
+    /// Every match is reported by the push completing it, so this is always empty for now  
+    /// Call it anyway, to not miss matches if that ever changes
+    pub fn finish(self) -> Vec<FileMatch> {
+        Vec::new()
+    }
+}
+
+impl Pattern {
+    /// Creates a [StreamMatcher] searching for the Pattern in data pushed chunk by chunk, e.g. as it
+    /// arrives from the network
+    ///
+    /// The result is the same as that of [Pattern::find_with] on the whole stream with default
+    /// options, but with `u64` stream positions, see [FileMatch]
+    ///
+    /// # Example:
+    /// ```
+    /// # use binmatch::Pattern;
+    /// let pattern = Pattern::new("DE AD ?? EF").unwrap();
+    /// let mut matcher = pattern.stream_matcher();
+    /// assert!(matcher.push(&[0x00, 0xDE]).is_empty());
+    /// let found = matcher.push(&[0xAD, 0x42, 0xEF]);
+    /// assert_eq!(found[0].offset(), 1);
+    /// assert_eq!(found[0].captures().get(0).unwrap().value, 0x42);
+    /// assert!(matcher.finish().is_empty());
+    /// ```
+    pub fn stream_matcher(&self) -> StreamMatcher<'_> {
+        self.stream_matcher_at(0)
+    }
+
+    /// Same as [Pattern::stream_matcher], but the stream starts at `position`, e.g. when resuming
+    /// the search of a file at an offset
+    ///
+    /// Stream positions saturate at `u64::MAX`, matches that would end past it are never reported
+    pub fn stream_matcher_at(&self, position: u64) -> StreamMatcher<'_> {
+        StreamMatcher {
+            pattern: self,
+            buf: Vec::new(),
+            buf_offset: position,
+            unscanned: position,
+            match_count: 0,
+        }
+    }
+}
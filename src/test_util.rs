@@ -0,0 +1,218 @@
+//! Support code for [assert_matches!](crate::assert_matches) and [assert_no_match!](crate::assert_no_match)
+//!
+//! Only available using the `test-util` feature, nothing in here is covered by semver
+//!
+//! Failing an assertion means panicking, so this is the one module allowed to panic
+
+#![allow(clippy::panic, clippy::expect_used)]
+
+use crate::hexdump::fmt_hexdump_lines;
+use crate::{Cow, Match, MatchExplanation, MatchOptions, Pattern, PatternElement};
+use core::fmt::Write;
+
+/// Data up to this length is dumped completely, longer data only around the interesting offset
+const FULL_DUMP_LIMIT: usize = 256;
+/// Bytes shown before and after the interesting offset when the data is too long to dump completely
+const DUMP_CONTEXT: usize = 64;
+
+/// Maximum number of elements of a Pattern built by [arbitrary_pattern]
+pub const MAX_ARBITRARY_LEN: usize = 16;
+/// Maximum number of random bytes [arbitrary_matching_data] puts before and after the match
+const MAX_ARBITRARY_PADDING: usize = 16;
+
+/// Takes the next byte of fuzzer input, 0 once it is exhausted
+fn take_byte(input: &mut &[u8]) -> u8 {
+    match input.split_first() {
+        Some((&byte, rest)) => {
+            *input = rest;
+            byte
+        }
+        None => 0,
+    }
+}
+
+/// Builds a Pattern of 1 to [MAX_ARBITRARY_LEN] literals, placeholders and ignored bytes from
+/// fuzzer input, consuming it from the front
+///
+/// Every input gives a valid Pattern, exhausted input is read as zeros, e.g. to drive a `cargo
+/// fuzz` target or a property test
+///
+/// # Example:
+/// ```
+/// # use binmatch::test_util::{arbitrary_matching_data, arbitrary_pattern};
+/// let mut input: &[u8] = &[2, 0xAA, 0x42, 0x00, 0x07, 0x01, 0x02];
+/// let pattern = arbitrary_pattern(&mut input);
+/// let data = arbitrary_matching_data(&pattern, &mut input);
+/// assert!(!pattern.find_with(&data, &Default::default()).is_empty());
+/// ```
+pub fn arbitrary_pattern(input: &mut &[u8]) -> Pattern {
+    let len = usize::from(take_byte(input)) % MAX_ARBITRARY_LEN + 1;
+    let data: Vec<PatternElement> = (0..len)
+        .map(|_| match take_byte(input) % 4 {
+            0 => PatternElement::Placeholder,
+            1 => PatternElement::Ignore,
+            _ => PatternElement::Literal(take_byte(input)),
+        })
+        .collect();
+    Pattern {
+        len: data.len(),
+        data,
+        ..Default::default()
+    }
+}
+
+/// Builds a haystack containing at least one match of `pattern` from fuzzer input, consuming it
+/// from the front
+///
+/// The match is surrounded by up to 16 bytes on either side, the wildcards take the input bytes
+pub fn arbitrary_matching_data(pattern: &Pattern, input: &mut &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let prefix = usize::from(take_byte(input)) % (MAX_ARBITRARY_PADDING + 1);
+    data.extend((0..prefix).map(|_| take_byte(input)));
+    for element in &pattern.data {
+        data.push(match element {
+            PatternElement::Literal(byte) => *byte,
+            PatternElement::Placeholder | PatternElement::Ignore => take_byte(input),
+        });
+    }
+    let suffix = usize::from(take_byte(input)) % (MAX_ARBITRARY_PADDING + 1);
+    data.extend((0..suffix).map(|_| take_byte(input)));
+    data
+}
+
+/// Anything the assertion macros accept as a Pattern
+pub trait PatternLike {
+    fn to_pattern(&self) -> Cow<'_, Pattern>;
+}
+
+impl PatternLike for Pattern {
+    fn to_pattern(&self) -> Cow<'_, Pattern> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl PatternLike for &Pattern {
+    fn to_pattern(&self) -> Cow<'_, Pattern> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl PatternLike for &str {
+    fn to_pattern(&self) -> Cow<'_, Pattern> {
+        Cow::Owned(Pattern::try_new(self).expect("The string isn't a valid Pattern"))
+    }
+}
+
+/// Asserts that `pattern` matches somewhere in `data`
+///
+/// The panic message contains the Pattern, the closest failing offset with its first mismatching
+/// element and a hexdump of the data
+#[track_caller]
+pub fn assert_matches<P: PatternLike + ?Sized>(pattern: &P, data: &[u8]) {
+    let pattern = pattern.to_pattern();
+    if !pattern
+        .find_with(data, &MatchOptions::new().max_matches(1))
+        .is_empty()
+    {
+        return;
+    }
+    let (offset, explanation) = closest_failure(&pattern, data);
+    let mut message = String::new();
+    let _ = writeln!(message, "pattern `{}` doesn't match", pattern);
+    let _ = writeln!(message, "closest offset: {:#x} ({})", offset, explanation);
+    write_dump(&mut message, data, offset..offset + pattern.len(), None);
+    panic!("{}", message);
+}
+
+/// Asserts that `pattern` matches nowhere in `data`
+///
+/// The panic message contains the Pattern, the first match and a hexdump of the data around it
+#[track_caller]
+pub fn assert_no_match<P: PatternLike + ?Sized>(pattern: &P, data: &[u8]) {
+    let pattern = pattern.to_pattern();
+    let Some(found) = pattern
+        .find_with(data, &MatchOptions::new().max_matches(1))
+        .pop()
+    else {
+        return;
+    };
+    let mut message = String::new();
+    let _ = writeln!(message, "pattern `{}` unexpectedly matches", pattern);
+    let _ = writeln!(message, "first match: {}", found);
+    write_dump(&mut message, data, found.range(), Some(&found));
+    panic!("{}", message);
+}
+
+/// Finds the offset where the Pattern got furthest before its first mismatching literal
+///
+/// Ties go to the earliest offset, data shorter than the Pattern is reported at offset 0
+fn closest_failure(pattern: &Pattern, data: &[u8]) -> (usize, MatchExplanation) {
+    let mut closest = (0, pattern.explain_at(data, 0));
+    let mut furthest = None;
+    for offset in 0..data.len().saturating_sub(pattern.len()).saturating_add(1) {
+        let explanation = pattern.explain_at(data, offset);
+        if let MatchExplanation::Mismatch { pattern_index, .. } = explanation {
+            if furthest.is_none_or(|furthest| pattern_index > furthest) {
+                furthest = Some(pattern_index);
+                closest = (offset, explanation);
+            }
+        }
+    }
+    closest
+}
+
+fn write_dump(
+    out: &mut String,
+    data: &[u8],
+    interesting: core::ops::Range<usize>,
+    marked: Option<&Match>,
+) {
+    let shown = if data.len() <= FULL_DUMP_LIMIT {
+        0..data.len()
+    } else {
+        interesting.start.saturating_sub(DUMP_CONTEXT)..interesting.end.saturating_add(DUMP_CONTEXT)
+    };
+    let _ = fmt_hexdump_lines(out, data, shown, marked);
+}
+
+/// Asserts that a [Pattern](crate::Pattern) (or a `&str` in the syntax of
+/// [Pattern::new](crate::Pattern::new)) matches somewhere in the data
+///
+/// On failure the panic message contains a hexdump of the data, the Pattern and the closest
+/// failing offset together with its first mismatching element
+///
+/// <div class="warning"> Only available using the <code>test-util</code> feature </div>
+///
+/// # Example:
+/// ```
+/// # use binmatch::{assert_matches, Pattern};
+/// let data = vec![0x48, 0x8B, 0x05, 0x8B];
+/// assert_matches!("48 8B ?? 8B", data);
+/// assert_matches!(Pattern::new("8B 05").unwrap(), data);
+/// ```
+#[macro_export]
+macro_rules! assert_matches {
+    ($pattern:expr, $data:expr $(,)?) => {
+        $crate::test_util::assert_matches(&$pattern, &$data[..])
+    };
+}
+
+/// Asserts that a [Pattern](crate::Pattern) (or a `&str` in the syntax of
+/// [Pattern::new](crate::Pattern::new)) matches nowhere in the data
+///
+/// On failure the panic message contains the Pattern, the first match and a hexdump of the data around it
+///
+/// <div class="warning"> Only available using the <code>test-util</code> feature </div>
+///
+/// # Example:
+/// ```
+/// # use binmatch::assert_no_match;
+/// let data = vec![0x48, 0x8B, 0x05, 0x89];
+/// assert_no_match!("48 8B ?? 8B", data);
+/// ```
+#[macro_export]
+macro_rules! assert_no_match {
+    ($pattern:expr, $data:expr $(,)?) => {
+        $crate::test_util::assert_no_match(&$pattern, &$data[..])
+    };
+}
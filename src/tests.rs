@@ -1,56 +1,3863 @@
 #[cfg(feature = "std")]
 mod std_tests {
-    use crate::Pattern;
+    use crate::{
+        Base64Error, BinmatchError, Capture, DbEntry, DirScanOptions, ExecutableFormat,
+        ExtractError, Extractor, FileMatch, FixedPattern, GeneralizeError, GeneralizeOptions,
+        HexError, LintWarning, Match, MatchExplanation, MatchOptions, MatchPolicy, MemError,
+        MemoryRegion, MemorySource, ParseError, PartialPatchError, PartialReadError, Patch,
+        PatchOptions, PatchRecord, PatchReport, PatchTemplate, Pattern, PatternDb,
+        PatternDecodeError, PatternSet, Permissions, ReaderSource, RegexImportError, Report,
+        RevertError, ScanState, ScanSummary, ScanWriter, SetDecodeError, SigGenError,
+        SigGenOptions, SignatureFileError, SliceSource, StreamMatcher, StreamScanner, VerifyError,
+    };
+    use std::borrow::Cow;
+    use std::str::FromStr;
+
+    /// Small deterministic xorshift generator for randomized tests
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    /// A random pattern string of 1 to `max_len` elements, using only the bytes 0 to 3 so it actually matches
+    fn random_pattern(state: &mut u64, max_len: u64) -> String {
+        let len = 1 + next_random(state) % max_len;
+        (0..len)
+            .map(|_| match next_random(state) % 6 {
+                0 => "??".to_string(),
+                1 => "__".to_string(),
+                v => format!("{:02X}", v - 2),
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn random_data(state: &mut u64, len: usize) -> Vec<u8> {
+        (0..len).map(|_| (next_random(state) % 4) as u8).collect()
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_pattern_chunk_matching() {
+        let pattern = Pattern::new("00 ?? 00 ??").unwrap();
+        let (matches, _) = pattern.match_chunk(vec![0, 42, 0, 13]);
+        assert_eq!(matches, vec![(42, 1), (13, 3)])
+    }
+
+    #[test]
+    fn test_pattern_whole_matching() {
+        let pattern = Pattern::new("DEADBEEF ??").unwrap();
+        let mut data: Vec<u8> = vec![0xDE, 0xAD, 0xBE, 0xEF, 23];
+        for i in 0..100 {
+            data.insert(0, i);
+        }
+        for i in 0..100 {
+            data.push(i);
+        }
+        let matches = pattern.find_matches_with_index(data);
+        assert_eq!(matches, vec![(23, 100 + 4)]);
+    }
+
+    #[test]
+    fn test_convenience_method() {
+        let pattern = Pattern::new("DEADBEEF ??").unwrap();
+        let mut data: Vec<u8> = vec![0xDE, 0xAD, 0xBE, 0xEF, 23];
+        for i in 0..100 {
+            data.insert(0, i);
+        }
+        for i in 0..100 {
+            data.push(i);
+        }
+        let matches = pattern.find_matches(data);
+        assert_eq!(matches, vec![23]);
+    }
+
+    #[test]
+    fn ignore_some_bytes() {
+        let pattern = Pattern::new("00 __ 12 __ ??").unwrap();
+        let data: Vec<u8> = vec![0x00, 0xFF, 0x12, 0xAA, 23];
+        let matches = pattern.find_matches(data);
+        assert_eq!(matches, vec![23]);
+    }
+
+    #[test]
+    fn strided_with_stride_one_is_a_full_scan() {
+        let pattern = Pattern::new("00 ?? 00").unwrap();
+        let data: Vec<u8> = vec![0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03];
+        let strided = pattern.find_matches_strided(&data, 1, 0).unwrap();
+        let starts: Vec<usize> = strided.iter().map(|m| m.start()).collect();
+        assert_eq!(starts, vec![0, 2, 4]);
+        let captures: Vec<(u8, usize)> = strided
+            .iter()
+            .flat_map(|m| m.captures().to_tuples())
+            .collect();
+        assert_eq!(captures, pattern.find_matches_with_index(data));
+    }
+
+    #[test]
+    fn strided_only_tests_record_offsets() {
+        let pattern = Pattern::new("DE AD ??").unwrap();
+        let mut data: Vec<u8> = vec![0; 0x38 * 4];
+        for record in 0..4 {
+            data[record * 0x38 + 0x10..record * 0x38 + 0x13].copy_from_slice(&[
+                0xDE,
+                0xAD,
+                record as u8,
+            ]);
+        }
+        data[0x38 + 0x20..0x38 + 0x23].copy_from_slice(&[0xDE, 0xAD, 0xFF]); // Not on a record boundary
+        let matches = pattern.find_matches_strided(&data, 0x38, 0x10).unwrap();
+        let values: Vec<u8> = matches
+            .iter()
+            .map(|m| m.captures().get(0).unwrap().value)
+            .collect();
+        assert_eq!(values, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn strided_normalizes_phase_and_rejects_zero_stride() {
+        let pattern = Pattern::new("AA").unwrap();
+        let data: Vec<u8> = vec![0x00, 0xAA, 0x00, 0xAA];
+        assert_eq!(
+            pattern.find_matches_strided(&data, 2, 3).unwrap(),
+            pattern.find_matches_strided(&data, 2, 1).unwrap()
+        );
+        assert!(matches!(
+            pattern.find_matches_strided(&data, 0, 0),
+            Err(BinmatchError::StrideError)
+        ));
+    }
+
+    #[test]
+    fn match_at_many_keeps_input_order() {
+        let pattern = Pattern::new("AA ?? BB").unwrap();
+        let data: Vec<u8> = vec![0xAA, 0x01, 0xBB, 0xAA, 0x02, 0xBB];
+        let offsets = [3, 0, 1, 4, 5, 100];
+        let results = pattern.match_at_many(&data, &offsets);
+        assert_eq!(results.len(), offsets.len());
+        for (offset, result) in offsets.iter().zip(&results) {
+            assert_eq!(result, &pattern.match_at(&data, *offset));
+        }
+        assert_eq!(results[0].as_ref().unwrap().captures(), &[(0x02, 4)]);
+        assert_eq!(results[1].as_ref().unwrap().captures(), &[(0x01, 1)]);
+        assert!(results[2..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn match_bitmap_agrees_with_match_starts() {
+        let pattern = Pattern::new("00 ?? 00").unwrap();
+        let mut data: Vec<u8> = (0..200u32).map(|i| (i * 7 % 5) as u8).collect();
+        data.extend([0x00, 0x42, 0x00, 0x00, 0x00]);
+        let bitmap = pattern.match_bitmap(&data);
+        assert_eq!(bitmap.len(), data.len());
+        assert_eq!(bitmap.as_words().len(), data.len().div_ceil(64));
+        let starts = pattern.find_match_starts(&data);
+        assert_eq!(bitmap.iter_ones().collect::<Vec<usize>>(), starts);
+        assert_eq!(bitmap.count_ones(), starts.len());
+        assert!(!bitmap.get(data.len()));
+
+        // An empty Pattern never matches instead of panicking in `windows(0)`
+        let empty = Pattern::new("").unwrap();
+        assert!(empty.find_match_starts(&data).is_empty());
+        assert!(empty.find_match_starts(&[]).is_empty());
+        let bitmap = empty.match_bitmap(&data);
+        assert_eq!((bitmap.len(), bitmap.count_ones()), (data.len(), 0));
+    }
+
+    #[test]
+    fn match_bitmaps_combine() {
+        let data: Vec<u8> = vec![0xAA, 0xBB, 0xAA, 0xBB, 0xCC];
+        let a = Pattern::new("AA").unwrap().match_bitmap(&data);
+        let ab = Pattern::new("AA BB").unwrap().match_bitmap(&data);
+        let c = Pattern::new("CC").unwrap().match_bitmap(&data);
+        assert_eq!((&a & &ab).iter_ones().collect::<Vec<usize>>(), vec![0, 2]);
+        let mut all = a.clone();
+        all |= &c;
+        assert_eq!(all.iter_ones().collect::<Vec<usize>>(), vec![0, 2, 4]);
+
+        // Different lengths keep the length of the left bitmap instead of panicking
+        let long = Pattern::new("AA").unwrap().match_bitmap(&[0xAA; 130]);
+        let short = &a | &long;
+        assert_eq!(short.len(), 5);
+        assert_eq!(
+            short.iter_ones().collect::<Vec<usize>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!((&long & &a).iter_ones().collect::<Vec<usize>>(), vec![0, 2]);
+        assert_eq!((&long | &a).count_ones(), 130);
+        let mut checked = a.clone();
+        assert!(checked.try_or_assign(&long).is_err());
+        assert_eq!(checked, a);
+    }
+
+    #[test]
+    fn match_regions() {
+        let pattern = Pattern::new("AA ??").unwrap();
+        // Isolated match
+        let data: Vec<u8> = vec![0x00, 0xAA, 0x01, 0x00, 0x00];
+        assert_eq!(pattern.find_match_regions(&data), vec![1..3]);
+        // Dense run of overlapping and touching matches
+        let data: Vec<u8> = vec![0xAA, 0xAA, 0xAA, 0x01, 0xAA, 0x02, 0x00];
+        assert_eq!(pattern.find_match_regions(&data), vec![0..6]);
+        // Match ending on the very last byte, two bytes away from the previous one
+        let data: Vec<u8> = vec![0xAA, 0x01, 0x00, 0x00, 0xAA, 0x02];
+        assert_eq!(pattern.find_match_regions(&data), vec![0..2, 4..6]);
+        assert_eq!(
+            pattern.find_match_regions_with_gap(&data, 1),
+            vec![0..2, 4..6]
+        );
+        assert_eq!(pattern.find_match_regions_with_gap(&data, 2), vec![0..6]);
+        assert_eq!(pattern.find_match_regions(&[0x00]), vec![]);
+    }
+
+    #[test]
+    fn skip_haystack_padding() {
+        let options = MatchOptions::new().skip_haystack_bytes(&[0xFF]);
+        let pattern = Pattern::new("11 22 ?? 44").unwrap();
+        // The field is interrupted by a run of padding, and the data is preceded by padding
+        let data: Vec<u8> = vec![0xFF, 0x11, 0x22, 0xFF, 0xFF, 0xFF, 0x33, 0x44, 0xFF];
+        let matches = pattern.find_with(&data, &options);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].range(), 1..8);
+        assert_eq!(matches[0].captures(), &[(0x33, 6)]);
+        assert!(pattern.find_with(&data, &MatchOptions::new()).is_empty());
+    }
+
+    #[test]
+    fn skip_haystack_padding_literal_in_pattern() {
+        let options = MatchOptions::new().skip_haystack_bytes(&[0xFF]);
+        let pattern = Pattern::new("11 FF ??").unwrap();
+        let data: Vec<u8> = vec![0x11, 0xFF, 0xFF, 0x22, 0xFF, 0x11, 0xFF, 0x33];
+        let matches = pattern.find_with(&data, &options);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].range(), 0..4);
+        assert_eq!(matches[0].captures(), &[(0x22, 3)]);
+        assert_eq!(matches[1].range(), 5..8);
+        let pattern = Pattern::new("FF 22").unwrap();
+        let matches = pattern.find_with(&data, &options);
+        assert_eq!(
+            matches.iter().map(|m| m.range()).collect::<Vec<_>>(),
+            vec![1..4, 2..4]
+        );
+    }
+
+    #[test]
+    fn ascii_case_insensitive_literals() {
+        let options = MatchOptions::new().ascii_case_insensitive();
+        let pattern = Pattern::new("47 45 54 20 ??").unwrap(); // "GET " followed by one byte
+        let data = b"GET /gEt\tget xGeT ?";
+        let matches = pattern.find_with(data, &options);
+        let captured: Vec<u8> = matches
+            .iter()
+            .map(|m| m.captures().get(0).unwrap().value)
+            .collect();
+        assert_eq!(captured, b"/x?".to_vec());
+        // 0x20 is not a letter, so a tab or a 0x00 must not match it
+        assert!(pattern.find_with(b"get\x00/", &options).is_empty());
+        assert_eq!(pattern.find_with(data, &MatchOptions::new()).len(), 1);
+    }
+
+    #[test]
+    fn ring_buffer_wraparound() {
+        let pattern = Pattern::new("11 22 33 ??").unwrap();
+        let mut ring: Vec<u8> = vec![0; 16];
+        // One match exactly across the wrap point, one inside the buffer
+        ring[14..16].copy_from_slice(&[0x11, 0x22]);
+        ring[0..2].copy_from_slice(&[0x33, 0x44]);
+        ring[4..8].copy_from_slice(&[0x11, 0x22, 0x33, 0x55]);
+        let head = 10;
+        let matches = pattern.find_in_ring(&ring, head);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].start(), 4);
+        assert_eq!(matches[0].captures(), &[(0x44, 7)]);
+        assert_eq!(matches[1].start(), 10);
+        assert_eq!(matches[1].captures(), &[(0x55, 13)]);
+
+        // The logical offsets agree with a scan of the linearized buffer
+        let mut linear = ring[head..].to_vec();
+        linear.extend_from_slice(&ring[..head]);
+        assert_eq!(pattern.find_with(&linear, &MatchOptions::new()), matches);
+        assert_eq!(pattern.find_in_ring(&ring, head + ring.len()), matches);
+        // A linear scan of the raw buffer misses the wrapping match
+        assert_eq!(pattern.find_matches(ring.clone()), vec![0x55]);
+        assert_eq!(pattern.find_in_ring(&ring, 0).len(), 1);
+    }
+
+    #[test]
+    fn excluded_ranges() {
+        let pattern = Pattern::new("AA ?? BB").unwrap();
+        let mut data: Vec<u8> = vec![0; 32];
+        for start in [2, 10, 20, 26] {
+            data[start..start + 3].copy_from_slice(&[0xAA, start as u8, 0xBB]);
+        }
+        let options = MatchOptions::new()
+            // Unsorted and overlapping, normalized to 0..2, 8..15 and 21..23
+            .exclude(&[12..15, 0..2, 8..13, 21..22])
+            .exclude(&[22..23, 5..5]);
+        let starts: Vec<usize> = pattern
+            .find_with(&data, &options)
+            .iter()
+            .map(|m| m.start())
+            .collect();
+        // 2 is adjacent to 0..2, 10 is fully inside 8..15, 20 partially overlaps 21..23
+        assert_eq!(starts, vec![2, 26]);
+    }
+
+    #[test]
+    fn excluded_ranges_in_swapped_view() {
+        let pattern = Pattern::new("AA BB").unwrap();
+        // The view is AA BB 00 00 | 00 00 AA BB | 00 AA BB 00, the words read from are 0..4, 4..8 and 8..12
+        let data = [
+            0x00, 0x00, 0xBB, 0xAA, 0xBB, 0xAA, 0x00, 0x00, 0x00, 0xBB, 0xAA, 0x00,
+        ];
+        let starts = |exclude: std::ops::Range<usize>| -> Vec<usize> {
+            let options = MatchOptions::new().swap_bytes(4).exclude(&[exclude]);
+            pattern
+                .find_with(&data, &options)
+                .iter()
+                .map(|m| m.start())
+                .collect()
+        };
+        assert_eq!(starts(0..0), vec![0, 6, 9]);
+        // Haystack bytes 2..4 hold the first match even though view 2..4 follows it
+        assert_eq!(starts(2..4), vec![6, 9]);
+        // Bytes 4..6 hold the second match even though its view range is 6..8
+        assert_eq!(starts(4..5), vec![0, 9]);
+        // Unused bytes of a word still exclude it
+        assert_eq!(starts(11..12), vec![0, 6]);
+        assert_eq!(starts(12..16), vec![0, 6, 9]);
+    }
+
+    #[test]
+    fn one_match_per_block() {
+        let pattern = Pattern::new("AA BB").unwrap();
+        let mut data: Vec<u8> = vec![0; 48];
+        // Two matches in the first block, one straddling the boundary of the second and third block
+        for start in [3, 8, 31, 40] {
+            data[start..start + 2].copy_from_slice(&[0xAA, 0xBB]);
+        }
+        let options = MatchOptions::new().one_per_block(16);
+        let starts: Vec<usize> = pattern
+            .find_with(&data, &options)
+            .iter()
+            .map(|m| m.start())
+            .collect();
+        // The straddling match belongs to the block it starts in, the third block has its own match
+        assert_eq!(starts, vec![3, 31, 40]);
+    }
+
+    #[test]
+    fn byte_swapped_view() {
+        // 32 bit words stored big endian, the Pattern is written in little endian order
+        let data: Vec<u8> = vec![0xAA, 0xBB, 0xCC, 0xDD, 0x11, 0x22, 0x33, 0x44, 0x55];
+        let pattern = Pattern::new("CC BB ?? 44 33 __").unwrap();
+        let options = MatchOptions::new().swap_bytes(4);
+        let matches = pattern.find_with(&data, &options);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].range(), 1..7);
+        // The placeholder sits at view position 3, which is read from index 0
+        assert_eq!(matches[0].captures(), &[(0xAA, 0)]);
+        for m in &matches {
+            for capture in m.captures() {
+                assert_eq!(data[capture.haystack_offset], capture.value);
+            }
+        }
+        // The trailing partial word is not part of the view
+        assert!(Pattern::new("55")
+            .unwrap()
+            .find_with(&data, &options)
+            .is_empty());
+        assert!(pattern.find_with(&data, &MatchOptions::new()).is_empty());
+        let pattern = Pattern::new("BB AA").unwrap();
+        let starts: Vec<usize> = pattern
+            .find_with(&data, &MatchOptions::new().swap_bytes(2))
+            .iter()
+            .map(|m| m.start())
+            .collect();
+        assert_eq!(starts, vec![0]);
+    }
+
+    #[test]
+    fn match_options_through_find_with() {
+        let pattern = Pattern::new("AA ??").unwrap();
+        let data: Vec<u8> = vec![0xAA, 0xAA, 0xAA, 0x01, 0x00, 0x00, 0x00, 0x00, 0xAA, 0x02];
+        let starts = |options: &MatchOptions| -> Vec<usize> {
+            pattern
+                .find_with(&data, options)
+                .iter()
+                .map(|m| m.start())
+                .collect()
+        };
+        assert_eq!(starts(&MatchOptions::default()), vec![0, 1, 2, 8]);
+        assert_eq!(
+            starts(&MatchOptions::new().non_overlapping()),
+            vec![0, 2, 8]
+        );
+        assert_eq!(starts(&MatchOptions::new().align(2)), vec![0, 2, 8]);
+        assert_eq!(starts(&MatchOptions::new().max_matches(2)), vec![0, 1]);
+        assert_eq!(starts(&MatchOptions::new().max_matches(0)), vec![]);
+        assert_eq!(starts(&MatchOptions::new().range(1..9)), vec![1, 2]);
+        assert_eq!(starts(&MatchOptions::new().range(3..100)), vec![8]);
+        let combined = MatchOptions::new()
+            .non_overlapping()
+            .align(2)
+            .max_matches(10)
+            .range(1..10);
+        assert_eq!(starts(&combined), vec![2, 8]);
+    }
+
+    #[test]
+    fn read_ignored_bytes_after_the_scan() {
+        let pattern = Pattern::new("AA __ __ ??").unwrap();
+        let data: Vec<u8> = vec![0x00, 0xAA, 0x11, 0x22, 0x33, 0xAA, 0x44, 0x55, 0x66];
+        let borrowed = pattern.find_with(&data, &MatchOptions::new());
+        let copied = {
+            let data = data.clone();
+            pattern.find_with(&data, &MatchOptions::new().copy_window())
+        };
+        assert_eq!(borrowed.len(), 2);
+        for (borrowed, copied) in borrowed.iter().zip(&copied) {
+            assert_eq!(borrowed.stored_window(), None);
+            assert_eq!(copied.stored_window(), borrowed.window(&data));
+            assert_eq!(borrowed.window(&data).unwrap().len(), pattern.len());
+            // The haystack isn't needed anymore once the window was copied
+            assert_eq!(copied.byte_at(&[], 1), borrowed.byte_at(&data, 1));
+            assert_eq!(copied.byte_at(&[], 4), None);
+        }
+        assert_eq!(borrowed[0].byte_at(&data, 2), Some(0x22));
+        assert_eq!(copied[1].byte_at(&[], 2), Some(0x55));
+    }
+
+    #[test]
+    fn captured_value_distributions() {
+        let pattern = Pattern::new("C3 ?? 00 ??").unwrap();
+        let mut data: Vec<u8> = Vec::new();
+        for i in 0..30u8 {
+            // The first placeholder cycles through three values, the second one is constant
+            data.extend([0xC3, 0x10 + i % 3, 0x00, 0xEE, 0x55]);
+        }
+        let histograms = pattern.captured_value_histogram(&data);
+        assert_eq!(histograms.len(), 2);
+        assert_eq!(&histograms[0][0x10..0x13], &[10, 10, 10]);
+        assert_eq!(histograms[0].iter().sum::<u32>(), 30);
+        assert_eq!(histograms[1][0xEE], 30);
+        assert_eq!(histograms[1].iter().sum::<u32>(), 30);
+        assert_eq!(
+            pattern.captured_value_set(&data),
+            vec![0x10, 0x11, 0x12, 0xEE]
+        );
+        assert_eq!(
+            Pattern::new("C3")
+                .unwrap()
+                .captured_value_histogram(&data)
+                .len(),
+            0
+        );
+        assert!(pattern.captured_value_set(&[0xC3]).is_empty());
+    }
+
+    #[test]
+    fn captures_as_offset_map() {
+        // Overlapping matches capture index 2 once through the first and once through the second placeholder
+        let pattern = Pattern::new("?? AA ??").unwrap();
+        let data: Vec<u8> = vec![0x01, 0xAA, 0x02, 0xAA, 0x03];
+        let matches = pattern.find_with(&data, &MatchOptions::new());
+        assert_eq!(matches[0].captures(), &[(0x01, 0), (0x02, 2)]);
+        assert_eq!(matches[1].captures(), &[(0x02, 2), (0x03, 4)]);
+        let map = pattern.find_matches_as_map(&data);
+        assert_eq!(
+            map.into_iter().collect::<Vec<(usize, u8)>>(),
+            vec![(0, 0x01), (2, 0x02), (4, 0x03)]
+        );
+    }
+
+    #[test]
+    fn reverse_search_mirrors_forward_search() {
+        let pattern = Pattern::new("00 ?? 00").unwrap();
+        let data: Vec<u8> = vec![0x00, 0x00, 0x00, 0x01, 0x00, 0x05, 0x00, 0x02, 0x00];
+        let mut reversed = pattern.rfind_matches(&data);
+        assert_eq!(reversed.first(), pattern.find_last(&data).as_ref());
+        assert_eq!(reversed[0].start(), 6);
+        reversed.reverse();
+        assert_eq!(reversed, pattern.find_with(&data, &MatchOptions::new()));
+        assert_eq!(pattern.find_last(&data[..2]), None);
+    }
+
+    #[test]
+    fn explain_why_an_offset_does_not_match() {
+        let pattern = Pattern::new("55 48 89 E5 ?? 8B").unwrap();
+        let data: Vec<u8> = vec![0x55, 0x48, 0x89, 0xE5, 0x42, 0x89, 0x55, 0x48];
+        assert_eq!(
+            pattern.explain_at(&data, 0),
+            MatchExplanation::Mismatch {
+                pattern_index: 5,
+                expected: 0x8B,
+                found: 0x89
+            }
+        );
+        assert_eq!(
+            pattern.explain_at(&data, 0).to_string(),
+            "mismatch at +5: expected 0x8B, found 0x89"
+        );
+        assert_eq!(
+            pattern.explain_at(&data, 6),
+            MatchExplanation::OutOfBounds {
+                needed: 6,
+                available: 2
+            }
+        );
+        let mut fixed = data.clone();
+        fixed[5] = 0x8B;
+        let explanation = pattern.explain_at(&fixed, 0);
+        assert_eq!(
+            explanation,
+            MatchExplanation::Matched(pattern.match_at(&fixed, 0).unwrap())
+        );
+        assert_eq!(
+            explanation.to_string(),
+            "match at 0x0, captures: [0x42 at 0x4]"
+        );
+    }
+
+    #[test]
+    fn rejection_statistics() {
+        let pattern = Pattern::new("AA 00").unwrap();
+        // 64 records of AA followed by a counter cycling through 0..4 and two zero bytes
+        let data: Vec<u8> = (0..64u8).flat_map(|i| [0xAA, i % 4, 0x00, 0x00]).collect();
+        let (matches, stats) = pattern.find_with_stats(&data);
+        assert_eq!(stats.windows_checked, 255);
+        // AA only starts 64 of the windows
+        assert_eq!(stats.rejections_by_element[0], 255 - 64);
+        // The second byte of a window is nonzero for 63 later AA bytes and 48 nonzero counters
+        assert_eq!(stats.rejections_by_element[1], 63 + 48);
+        assert_eq!(stats.matches, 16);
+        assert_eq!(matches, pattern.find_with(&data, &MatchOptions::new()));
+        assert_eq!(pattern.suggest_anchor(&stats), Some((0, 0xAA)));
+    }
+
+    #[test]
+    fn typed_reads_from_the_window() {
+        let pattern = Pattern::new("CA FE ?? ?? ?? ?? ?? ?? ?? ??").unwrap();
+        let mut data: Vec<u8> = vec![0xCA, 0xFE];
+        data.extend(1.5f64.to_le_bytes());
+        let m = &pattern.find_with(&data, &MatchOptions::new())[0];
+        assert_eq!(m.read_f64_le(&data, 2), Some(1.5));
+        assert_eq!(m.read_u16_be(&data, 0), Some(0xCAFE));
+        assert_eq!(m.read_u16_le(&data, 0), Some(0xFECA));
+        assert_eq!(m.read_i16_le(&data, 0), Some(0xFECAu16 as i16));
+        assert_eq!(
+            m.read_u64_le(&data, 2),
+            Some(u64::from_le_bytes(1.5f64.to_le_bytes()))
+        );
+        assert_eq!(m.read_u32_be(&data, 6), Some(0x0000F83F));
+        assert_eq!(m.read_u64_le(&data, 3), None);
+        assert_eq!(m.read_u16_le(&data, usize::MAX), None);
+    }
+
+    #[test]
+    fn captures_by_ordinal() {
+        let pattern = Pattern::new("?? 00 ?? ?? 00").unwrap();
+        let data: Vec<u8> = vec![0xFF, 0x11, 0x00, 0x22, 0x33, 0x00];
+        let found = pattern.find_with(&data, &MatchOptions::new());
+        let captures = found[0].captures();
+        assert_eq!(captures.len(), 3);
+        assert_eq!(
+            captures.get(2),
+            Some(Capture {
+                value: 0x33,
+                pattern_index: 3,
+                haystack_offset: 4
+            })
+        );
+        assert_eq!(captures.get(3), None);
+        assert_eq!(captures.to_tuples(), vec![(0x11, 1), (0x22, 3), (0x33, 4)]);
+        assert_eq!(Vec::from(captures), captures.to_tuples());
+        assert_eq!(captures, &[(0x11, 1), (0x22, 3), (0x33, 4)]);
+    }
+
+    #[test]
+    fn named_captures() {
+        let pattern = Pattern::new("AA ?? ?? ?? BB")
+            .unwrap()
+            .with_capture_names(&["kind", "", "kind"]);
+        let data: Vec<u8> = vec![0xAA, 0x01, 0x02, 0x03, 0xBB];
+        let found = pattern.find_with(&data, &MatchOptions::new());
+        let captures = found[0].captures();
+        // The first placeholder named "kind" wins, the unnamed second one isn't listed
+        assert_eq!(captures.name("kind").map(|c| c.value), Some(0x01));
+        assert_eq!(captures.name(""), None);
+        let named: Vec<(&str, u8)> = captures.named().map(|(n, c)| (n, c.value)).collect();
+        assert_eq!(named, vec![("kind", 0x01), ("kind", 0x03)]);
+        assert_eq!(pattern.capture_names().len(), 3);
+        let unnamed = Pattern::new("AA ??").unwrap();
+        assert!(unnamed.capture_names().is_empty());
+        assert_eq!(
+            unnamed.find_with(&data, &MatchOptions::new())[0]
+                .captures()
+                .name("kind"),
+            None
+        );
+    }
+
+    #[test]
+    fn extractor_reports_every_failing_field() {
+        let pattern = Pattern::new("AA ?? ?? BB ??")
+            .unwrap()
+            .with_capture_names(&["size", "", "flags"]);
+        let data: Vec<u8> = vec![0xAA, 0x34, 0x12, 0xBB, 0x07];
+        let m = &pattern.find_with(&data, &MatchOptions::new())[0];
+        let fields = Extractor::new()
+            .field_u16_le("size")
+            .field_u8("flags")
+            .extract(m, &data)
+            .unwrap();
+        assert_eq!(fields.u16("size"), Some(0x1234));
+        assert_eq!(fields.u8("flags"), Some(0x07));
+        assert_eq!(fields.u8("size"), None); // Doesn't fit
+        let errors = Extractor::new()
+            .field_u16_be("size")
+            .field_u32_le("flags")
+            .field_u8("missing")
+            .extract(m, &data)
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ExtractError::OutOfBounds {
+                    field: "flags".to_string(),
+                    width: 4
+                },
+                ExtractError::MissingCapture {
+                    field: "missing".to_string()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn capture_runs_borrow_from_the_haystack() {
+        let pattern = Pattern::new("4C ?? ?? ?? __ ?? ?? 00").unwrap();
+        let data: Vec<u8> = vec![0xFF, 0x4C, 0x01, 0x02, 0x03, 0xEE, 0x04, 0x05, 0x00];
+        let found = pattern.find_with(&data, &MatchOptions::new());
+        let captures = found[0].captures();
+        let run = captures.run(&data, 0, 3).unwrap();
+        assert_eq!(run, &[0x01, 0x02, 0x03]);
+        assert!(core::ptr::eq(run.as_ptr(), &data[2]));
+        let tail = captures.run(&data, 3, 2).unwrap();
+        assert!(core::ptr::eq(tail.as_ptr(), &data[6]));
+        // The ignored byte interrupts the run
+        assert_eq!(captures.run(&data, 2, 2), None);
+        assert_eq!(captures.run(&data, 4, 2), None);
+        assert_eq!(captures.run(&data, 0, 0), None);
+        assert_eq!(captures.run(&data, usize::MAX, 2), None);
+    }
+
+    #[test]
+    fn captures_into_caller_storage() {
+        let pattern = Pattern::new("?? 00 ?? ?? 00").unwrap();
+        let data: Vec<u8> = vec![0x11, 0x00, 0x22, 0x33, 0x00];
+        let m = &pattern.find_with(&data, &MatchOptions::new())[0];
+        assert_eq!(m.captures().to_array::<3>(), Some([0x11, 0x22, 0x33]));
+        assert_eq!(m.captures().to_array::<2>(), None);
+        assert_eq!(m.captures().to_array::<4>(), None);
+        let mut buf = [0xFF; 4];
+        assert_eq!(m.captures().read_into(&mut buf).unwrap(), 3);
+        assert_eq!(buf, [0x11, 0x22, 0x33, 0xFF]);
+        let mut small = [0xFF; 2];
+        assert!(matches!(
+            m.captures().read_into(&mut small),
+            Err(BinmatchError::CaptureBufferError {
+                needed: 3,
+                available: 2
+            })
+        ));
+        assert_eq!(small, [0xFF; 2]);
+    }
+
+    #[test]
+    fn capture_transforms_only_change_reported_values() {
+        let pattern = Pattern::new("E8 ?? ?? 00").unwrap();
+        let mapped = pattern
+            .clone()
+            .map_capture(1, |value| value.wrapping_add(0x10))
+            .map_capture(1, |value| value & 0xF0);
+        let data: Vec<u8> = vec![0xE8, 0x01, 0x0F, 0x00, 0xE8, 0x02, 0x03, 0x01];
+        // The same windows match, only the second capture changes
+        assert_eq!(
+            mapped.find_match_starts(&data),
+            pattern.find_match_starts(&data)
+        );
+        assert_eq!(mapped.find_matches(data.clone()), vec![0x01, 0x10]);
+        let m = mapped.match_at(&data, 0).unwrap();
+        assert_eq!(m.captures().get(1).unwrap().value, 0x10);
+        assert_eq!(m.captures().get(0).unwrap().value, 0x01);
+        assert_eq!(
+            mapped.try_match_chunk(&data[..4]).unwrap().0,
+            vec![(0x01, 1), (0x10, 2)]
+        );
+        assert_eq!(mapped.captured_value_set(&data), vec![0x01, 0x10]);
+        // Transforms are ignored when comparing, so the order doesn't depend on closure addresses
+        assert_eq!(mapped, pattern);
+        assert_eq!(mapped.cmp(&pattern), std::cmp::Ordering::Equal);
+        assert_eq!(mapped.clone(), mapped);
+    }
+
+    #[test]
+    fn capture_offsets_are_consistent() {
+        let mut state = 0x2545F4914F6CDD1D;
+        for _ in 0..200 {
+            let pattern = Pattern::new(&random_pattern(&mut state, 6)).unwrap();
+            let data = random_data(&mut state, 64);
+            for m in pattern.find_with(&data, &MatchOptions::new()) {
+                for capture in m.captures() {
+                    assert_eq!(data[capture.haystack_offset], capture.value);
+                    assert_eq!(capture.haystack_offset, m.start() + capture.pattern_index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resolve_relative_targets() {
+        let pattern = Pattern::new("E9 ?? ?? ?? ??").unwrap();
+        let mut code: Vec<u8> = vec![0xCC; 0x20];
+        code[0x10..0x15].copy_from_slice(&[0xE9, 0xEB, 0xFF, 0xFF, 0xFF]); // jmp -0x15
+        code[0x18..0x1D].copy_from_slice(&[0xE9, 0x10, 0x00, 0x00, 0x00]); // jmp +0x10
+        let jumps = pattern.find_with(&code, &MatchOptions::new());
+        // Negative displacement landing exactly on 0
+        assert_eq!(jumps[0].rel32_target(&code, 1, 5), Some(0));
+        assert_eq!(jumps[1].rel32_target(&code, 1, 5), Some(0x2D));
+        // The displacement reaches below the start of the image, unless there is a base
+        assert_eq!(jumps[0].rel32_target(&code, 1, 4), None);
+        assert_eq!(
+            jumps[0].rel32_target_with_base(&code, 1, 4, 0x1000),
+            Some(0xFFF)
+        );
+        assert_eq!(jumps[1].rel32_target_with_base(&code, 1, 5, u64::MAX), None);
+        // The displacement doesn't fit into the match
+        assert_eq!(jumps[0].rel32_target(&code, 2, 5), None);
+    }
+
+    #[test]
+    fn context_is_clamped_at_the_edges() {
+        let pattern = Pattern::new("AA ??").unwrap();
+        let data: Vec<u8> = vec![0xAA, 0x01, 0x10, 0x11, 0x12, 0x13, 0xAA, 0x02];
+        let found = pattern.find_with_context(&data, 3, 3);
+        assert_eq!(found.len(), 2);
+        // Within `before` bytes of offset 0
+        assert!(found[0].pre.is_empty());
+        assert_eq!(&found[0].post[..], &[0x10, 0x11, 0x12]);
+        // Within `after` bytes of the end
+        assert_eq!(&found[1].pre[..], &[0x11, 0x12, 0x13]);
+        assert!(found[1].post.is_empty());
+        assert_eq!(found[1].matched.start(), 6);
+        assert!(matches!(found[1].pre, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn matched_bytes_cover_the_whole_extent() {
+        let pattern = Pattern::new("AA __ ??").unwrap();
+        let data: Vec<u8> = vec![0xAA, 0x01, 0x02, 0xAA, 0x03, 0x04];
+        for m in pattern.find_with(&data, &MatchOptions::new()) {
+            assert_eq!(m.as_bytes(&data).unwrap().len(), pattern.len());
+            assert_eq!(m.as_bytes(&data), m.window(&data));
+        }
+        // With skipped padding the matched extent is longer than the Pattern
+        let options = MatchOptions::new().skip_haystack_bytes(&[0xFF]);
+        let padded: Vec<u8> = vec![0xAA, 0xFF, 0x01, 0xFF, 0x02];
+        let m = &pattern.find_with(&padded, &options)[0];
+        assert_eq!(m.as_bytes(&padded), Some(&padded[..]));
+
+        // A shorter or different haystack is reported instead of panicking
+        assert_eq!(m.as_bytes(&padded[..4]), None);
+        assert_eq!(m.window(&padded[..4]), None);
+        assert_eq!(m.byte_at(&[], 0), None);
+        assert_eq!(m.read_u16_le(&padded[..1], 0), None);
+        let copied = &pattern.find_with(&padded, &options.copy_window())[0];
+        assert_eq!(copied.window(&[]), Some(&padded[..]));
+    }
+
+    #[test]
+    fn hexdump_golden_output() {
+        let pattern = Pattern::new("00 01 ?? 03").unwrap();
+        let data: Vec<u8> = (0..40).collect();
+        let found = pattern.find_with(&data, &MatchOptions::new());
+        // Match at offset 0
+        assert_eq!(
+            found[0].hexdump(&data, 2),
+            "00000000  00 01 02 03 04 05 06 07  08 09 0a 0b 0c 0d 0e 0f |................|\n          ^^ ^^ ?? ^^\n"
+        );
+        // Match in the last, partial line of the buffer, with context reaching into the previous line
+        let pattern = Pattern::new("21 ?? 23").unwrap();
+        let found = pattern.find_with(&data, &MatchOptions::new());
+        assert_eq!(
+            found[0].hexdump(&data, 4),
+            "00000010  10 11 12 13 14 15 16 17  18 19 1a 1b 1c 1d 1e 1f |................|\n\
+             00000020  20 21 22 23 24 25 26 27                          | !\"#$%&'|\n\
+             \x20            ^^ ?? ^^\n"
+        );
+        let mut out = String::new();
+        found[0].fmt_hexdump(&mut out, &data, 4).unwrap();
+        assert_eq!(out, found[0].hexdump(&data, 4));
+    }
+
+    #[test]
+    fn csv_and_json_reports() {
+        let data: Vec<u8> = vec![0xAA, 0x01, 0x02, 0xBB, 0xAA, 0x03, 0x04];
+        let first = Pattern::new("AA ?? ??").unwrap();
+        let second = Pattern::new("BB").unwrap();
+        let mut report = Report::new();
+        report
+            .add(
+                "first, with \"quotes\"",
+                first.find_with(&data, &MatchOptions::new()),
+            )
+            .add("second", second.find_with(&data, &MatchOptions::new()))
+            .add("none", Vec::new());
+        assert_eq!(report.len(), 3);
+        assert_eq!(
+            report.to_csv(),
+            "pattern,offset,length,captures\n\
+             \"first, with \"\"quotes\"\"\",0,3,0102\n\
+             \"first, with \"\"quotes\"\"\",4,3,0304\n\
+             second,3,1,\n"
+        );
+        assert_eq!(
+            report.to_json(),
+            concat!(
+                r#"[{"pattern":"first, with \"quotes\"","offset":0,"length":3,"captures":"0102"},"#,
+                r#"{"pattern":"first, with \"quotes\"","offset":4,"length":3,"captures":"0304"},"#,
+                r#"{"pattern":"second","offset":3,"length":1,"captures":""}]"#
+            )
+        );
+        assert_eq!(Report::new().to_json(), "[]");
+    }
+
+    #[test]
+    fn display_matches_and_summaries() {
+        let pattern = Pattern::new("AA ?? ??").unwrap();
+        let mut data: Vec<u8> = vec![0; 0x4a18];
+        data[0x4a10..0x4a13].copy_from_slice(&[0xAA, 0x42, 0x7F]);
+        data[0x10..0x13].copy_from_slice(&[0xAA, 0x00, 0x01]);
+        let found = pattern.find_with(&data, &MatchOptions::new());
+        assert_eq!(
+            found[1].to_string(),
+            "0x00004a10..0x00004a13 captures=[42, 7f]"
+        );
+        assert_eq!(
+            Pattern::new("AA")
+                .unwrap()
+                .match_at(&data, 0x4a10)
+                .unwrap()
+                .to_string(),
+            "0x00004a10..0x00004a11 captures=[]"
+        );
+        assert_eq!(
+            found[0].clone().shifted(0x1_0000_0000).to_string(),
+            "0x0000000100000010..0x0000000100000013 captures=[00, 01]"
+        );
+        assert_eq!(
+            found[0].clone().shifted(0xffff_ffee).to_string(),
+            "0x00000000fffffffe..0x0000000100000001 captures=[00, 01]"
+        );
+        assert_eq!(
+            found[0].clone().shifted(0xffff_ffe0).to_string(),
+            "0xfffffff0..0xfffffff3 captures=[00, 01]"
+        );
+        assert_eq!(
+            ScanSummary::new("X", &found, data.len()).to_string(),
+            "2 matches of pattern X in 18968 bytes (first at 0x00000010, last at 0x00004a10)"
+        );
+        assert_eq!(
+            ScanSummary::new("X", &found[..1], 5 << 32).to_string(),
+            "1 match of pattern X in 21474836480 bytes (first at 0x0000000000000010, last at 0x0000000000000010)"
+        );
+        assert_eq!(
+            ScanSummary::new("X", &found[..1], (1 << 32) - 1).to_string(),
+            "1 match of pattern X in 4294967295 bytes (first at 0x00000010, last at 0x00000010)"
+        );
+        assert_eq!(
+            ScanSummary::new("X", &found[..1], 1 << 32).to_string(),
+            "1 match of pattern X in 4294967296 bytes (first at 0x0000000000000010, last at 0x0000000000000010)"
+        );
+        assert_eq!(
+            ScanSummary::new("X", &[], 3).to_string(),
+            "0 matches of pattern X in 3 bytes"
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn assert_macros_report_closest_offset() {
+        let data = vec![0x00, 0x48, 0x8B, 0x05, 0x89, 0x48, 0x00];
+        crate::assert_matches!("48 8B ?? 89", data);
+        crate::assert_matches!(Pattern::new("8B 05").unwrap(), data);
+        crate::assert_no_match!("48 8B ?? 8B", data);
+
+        let failure = std::panic::catch_unwind(|| crate::assert_matches!("48 8B ?? 8B", data))
+            .expect_err("The Pattern doesn't match");
+        let message = failure.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with(
+            "pattern `48 8B ?? 8B` doesn't match\n\
+             closest offset: 0x1 (mismatch at +3: expected 0x8B, found 0x89)\n\
+             00000000  00 48 8b 05 89 48 00"
+        ));
+
+        let failure = std::panic::catch_unwind(|| crate::assert_no_match!("89 __", &data))
+            .expect_err("The Pattern matches");
+        let message = failure.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with(
+            "pattern `89 __` unexpectedly matches\nfirst match: 0x00000004..0x00000006"
+        ));
+        assert!(message.ends_with("             ^^ ^^\n"));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn find_with_agrees_with_match_at_on_arbitrary_input() {
+        use crate::test_util::{arbitrary_matching_data, arbitrary_pattern};
+
+        let mut state = 0xA4B1_72A7u32;
+        for _ in 0..500 {
+            let input: Vec<u8> = (0..64)
+                .map(|_| {
+                    state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                    (state >> 16) as u8
+                })
+                .collect();
+            let mut input = input.as_slice();
+            let pattern = arbitrary_pattern(&mut input);
+            let data = arbitrary_matching_data(&pattern, &mut input);
+
+            let found = pattern.find_with(&data, &MatchOptions::new());
+            assert!(!found.is_empty(), "{pattern} in {data:02X?}");
+            for m in &found {
+                assert_eq!(pattern.match_at(&data, m.start()).as_ref(), Some(m));
+            }
+            let starts: Vec<usize> = (0..data.len())
+                .filter(|&offset| pattern.match_at(&data, offset).is_some())
+                .collect();
+            assert_eq!(found.iter().map(Match::start).collect::<Vec<_>>(), starts);
+        }
+    }
+
+    #[test]
+    fn pattern_set_agrees_with_single_scans() {
+        let mut state = 0x5e7_5e7_5e7;
+        for _ in 0..200 {
+            let count = 1 + next_random(&mut state) % 5;
+            let patterns: Vec<Pattern> = (0..count)
+                .map(|_| Pattern::new(&random_pattern(&mut state, 6)).unwrap())
+                .collect();
+            let len = (next_random(&mut state) % 64) as usize;
+            let data = random_data(&mut state, len);
+            let mut expected: Vec<(usize, Match)> = patterns
+                .iter()
+                .enumerate()
+                .flat_map(|(index, pattern)| {
+                    pattern
+                        .find_with(&data, &MatchOptions::new())
+                        .into_iter()
+                        .map(move |m| (index, m))
+                })
+                .collect();
+            expected.sort_by_key(|(index, m)| (m.start(), *index));
+            assert_eq!(PatternSet::new(patterns).find_matches(&data), expected);
+        }
+    }
+
+    #[test]
+    fn named_pattern_sets() {
+        let set: PatternSet = [
+            ("zero", Pattern::new("00").unwrap()),
+            ("pair", Pattern::new("00 ??").unwrap()),
+            ("zero", Pattern::new("00 00").unwrap()),
+        ]
+        .into_iter()
+        .collect();
+        let data = vec![0x00, 0x00];
+        let names: Vec<(&str, usize)> = set
+            .find_named(&data)
+            .into_iter()
+            .map(|(name, m)| (name, m.start()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("zero", 0), ("pair", 0), ("zero", 0), ("zero", 1)]
+        );
+        assert_eq!(set.name(3), None);
+
+        let unnamed = PatternSet::new(vec![Pattern::new("00 ?? __").unwrap()]);
+        assert_eq!(unnamed.name(0), Some("00 ?? __"));
+
+        let mut report = Report::new();
+        report.add_set(&set, set.find_matches(&data));
+        assert_eq!(
+            report.to_csv(),
+            "pattern,offset,length,captures\nzero,0,1,\npair,0,2,00\nzero,0,2,\nzero,1,1,\n"
+        );
+    }
+
+    #[test]
+    fn pattern_set_prefilter_agrees_with_naive_scan() {
+        let mut state = 0xAC0_FFEE;
+        for _ in 0..300 {
+            let count = 1 + next_random(&mut state) % 12;
+            let patterns: Vec<Pattern> = (0..count)
+                .map(|_| Pattern::new(&random_pattern(&mut state, 8)).unwrap())
+                .collect();
+            let len = (next_random(&mut state) % 128) as usize;
+            let data = random_data(&mut state, len);
+            let set = PatternSet::new(patterns);
+            assert_eq!(set.find_matches(&data), set.find_matches_naive(&data));
+        }
+        let set = PatternSet::new(vec![
+            Pattern::new("").unwrap(),
+            Pattern::new("?? __").unwrap(),
+        ]);
+        assert_eq!(set.find_matches(&[0x01]), set.find_matches_naive(&[0x01]));
+        assert_eq!(set.find_matches(&[]), set.find_matches_naive(&[]));
+    }
+
+    #[test]
+    fn signature_files() {
+        let file = "\
+# comment only
+
+first: 48 8B ?? ?? 89
+second :C3# trailing comment
+   third:   ?? __ 00
+";
+        let set: PatternSet = file.parse().unwrap();
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.name(1), Some("second"));
+        assert_eq!(set.patterns()[2].to_string(), "?? __ 00");
+
+        let mut written = Vec::new();
+        set.to_writer(&mut written).unwrap();
+        assert_eq!(
+            String::from_utf8(written.clone()).unwrap(),
+            "first: 48 8B ?? ?? 89\nsecond: C3\nthird: ?? __ 00\n"
+        );
+        assert_eq!(PatternSet::from_reader(&written[..]).unwrap(), set);
+
+        assert!(PatternSet::from_str("").unwrap().is_empty());
+        assert!(PatternSet::from_str("\n  # nothing\n").unwrap().is_empty());
+        assert!(matches!(
+            PatternSet::from_str("a: 00\n\nb: 01\na: 02"),
+            Err(SignatureFileError::DuplicateName {
+                line: 4,
+                first: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            PatternSet::from_str("a: 00\n48 8B"),
+            Err(SignatureFileError::MissingName { line: 2 })
+        ));
+        assert!(matches!(
+            PatternSet::from_str(": 00"),
+            Err(SignatureFileError::MissingName { line: 1 })
+        ));
+        assert!(matches!(
+            PatternSet::from_str("a: 00\nb: 0G"),
+            Err(SignatureFileError::Pattern {
+                line: 2,
+                source: BinmatchError::PatternParseError {
+                    ch: 'G',
+                    position: 1
+                }
+            })
+        ));
+        assert!(matches!(
+            PatternSet::from_str("a: ?0"),
+            Err(SignatureFileError::Pattern {
+                line: 1,
+                source: BinmatchError::MixedToken {
+                    token: ['?', '0'],
+                    position: 0
+                }
+            })
+        ));
+    }
+
+    #[test]
+    fn pattern_string_round_trip() {
+        let mut state = 0x0DDBA11;
+        for _ in 0..100 {
+            let text = random_pattern(&mut state, 12);
+            let pattern = Pattern::from_str(&text).unwrap();
+            assert_eq!(pattern.to_string().parse::<Pattern>().unwrap(), pattern);
+        }
+        assert!(matches!(
+            Pattern::from_str("0"),
+            Err(BinmatchError::PatternLengthError { len: 1 })
+        ));
+        assert!(matches!(
+            Pattern::from_str("_0"),
+            Err(BinmatchError::MixedToken {
+                token: ['_', '0'],
+                position: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn pattern_set_first_wins() {
+        let mut set = PatternSet::default();
+        set.insert("prefix", Pattern::new("AA BB").unwrap());
+        set.insert("longer", Pattern::new("AA BB CC").unwrap());
+        set.insert("tail", Pattern::new("CC DD").unwrap());
+        set.insert("again", Pattern::new("AA").unwrap());
+        let data = vec![0xAA, 0xBB, 0xCC, 0xDD, 0xAA, 0xAA];
+        let found = |set: &PatternSet, policy| -> Vec<(usize, usize)> {
+            set.find_matches_with_policy(&data, policy)
+                .into_iter()
+                .map(|(index, m)| (index, m.start()))
+                .collect()
+        };
+        assert_eq!(
+            found(&set, MatchPolicy::All),
+            vec![(0, 0), (1, 0), (3, 0), (2, 2), (3, 4), (3, 5)]
+        );
+        // The prefix suppresses the longer Pattern, but not the match starting right after it
+        assert_eq!(
+            found(&set, MatchPolicy::FirstWins),
+            vec![(0, 0), (2, 2), (3, 4), (3, 5)]
+        );
+        // Priorities invert the insertion order, the longer match now also suppresses the tail
+        set.set_priority(1, 1);
+        assert_eq!(
+            found(&set, MatchPolicy::FirstWins),
+            vec![(1, 0), (3, 4), (3, 5)]
+        );
+        assert_eq!(set.priority(1), Some(1));
+        assert_eq!(set.priority(4), None);
+    }
+
+    #[test]
+    fn pattern_set_leftmost_longest() {
+        let mut set = PatternSet::default();
+        set.insert("outer", Pattern::new("01 02 03 04").unwrap());
+        set.insert("nested", Pattern::new("02 03").unwrap());
+        set.insert("crossing", Pattern::new("03 04 05 06").unwrap());
+        set.insert("short", Pattern::new("05 06").unwrap());
+        set.insert("same length", Pattern::new("05 ??").unwrap());
+        set.insert("empty", Pattern::new("").unwrap());
+        let data = vec![
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x02, 0x03, 0x04, 0x05, 0x06,
+        ];
+        let tokens = |set: &PatternSet| -> Vec<(usize, usize, usize)> {
+            set.find_leftmost_longest(&data)
+                .into_iter()
+                .map(|(index, m)| (index, m.start(), m.end()))
+                .collect()
+        };
+        // The nested match is swallowed, the crossing one starts inside the outer match and the
+        // empty Pattern never matches
+        assert_eq!(
+            tokens(&set),
+            vec![(0, 0, 4), (3, 4, 6), (1, 6, 8), (3, 9, 11)]
+        );
+        set.set_priority(4, 1);
+        assert_eq!(
+            tokens(&set),
+            vec![(0, 0, 4), (4, 4, 6), (1, 6, 8), (4, 9, 11)]
+        );
+        set.set_priority(2, -1);
+        assert_eq!(
+            set.find_matches_with_policy(&data, MatchPolicy::LeftmostLongest),
+            set.find_leftmost_longest(&data)
+        );
+    }
+
+    #[test]
+    fn pattern_set_counts_agree_with_matches() {
+        let mut state = 0xC0FFEE;
+        for _ in 0..100 {
+            let count = 1 + next_random(&mut state) % 8;
+            let patterns: Vec<Pattern> = (0..count)
+                .map(|_| Pattern::new(&random_pattern(&mut state, 5)).unwrap())
+                .collect();
+            let data = random_data(&mut state, 96);
+            let set = PatternSet::new(patterns);
+            let mut expected: Vec<(usize, usize)> = (0..set.len()).map(|i| (i, 0)).collect();
+            for (index, _) in set.find_matches(&data) {
+                expected[index].1 += 1;
+            }
+            assert_eq!(set.count_matches(&data), expected);
+        }
+        assert_eq!(PatternSet::default().count_matches(&[0x00]), vec![]);
+    }
+
+    #[test]
+    fn pattern_db_groups_by_tag() {
+        let mut db = PatternDb::new();
+        db.insert(
+            DbEntry::new("aes sbox", Pattern::new("63 7C 77 7B").unwrap())
+                .tag("crypto")
+                .metadata("algorithm", "aes"),
+        )
+        .insert(DbEntry::new("upx", Pattern::new("55 50 58").unwrap()).tag("packer"))
+        .insert(
+            DbEntry::new("zero dword", Pattern::new("00 00 00 00").unwrap())
+                .tag("crypto")
+                .tag("noise"),
+        );
+        let data = vec![
+            0x63, 0x7C, 0x77, 0x7B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55, 0x50, 0x58,
+        ];
+        let report = db.scan(&data);
+        assert_eq!(report.total(), 4);
+        assert_eq!(report.tag("crypto"), ["aes sbox", "zero dword"]);
+        assert_eq!(report.tag_total("crypto"), 3);
+        assert_eq!(report.tag("packer"), ["upx"]);
+        assert!(report.tag("missing").is_empty());
+        assert_eq!(report.matches("zero dword").len(), 2);
+        assert_eq!(report.by_pattern().len(), 3);
+        assert_eq!(
+            report.to_report().to_csv(),
+            "pattern,offset,length,captures\naes sbox,0,4,\nupx,9,3,\nzero dword,4,4,\nzero dword,5,4,\n"
+        );
+
+        let tagged = db.scan_tagged(&data, &["packer", "noise"]);
+        assert_eq!(tagged.total(), 3);
+        assert!(tagged.matches("aes sbox").is_empty());
+        assert_eq!(tagged.tag("crypto"), ["zero dword"]);
+        assert_eq!(db.scan_tagged(&data, &[]).total(), 0);
+        assert_eq!(db.entries()[0].get_metadata("algorithm"), Some("aes"));
+        assert_eq!(
+            db.entries()[2].tags().collect::<Vec<&str>>(),
+            ["crypto", "noise"]
+        );
+    }
+
+    #[test]
+    fn pattern_set_binary_round_trip() {
+        let mut set: PatternSet = [
+            ("every kind", Pattern::new("00 ?? __ FF").unwrap()),
+            ("", Pattern::new("").unwrap()),
+            ("unicode ✓", Pattern::new("?? ?? __").unwrap()),
+        ]
+        .into_iter()
+        .collect();
+        assert!(set.set_priority(2, -7));
+        assert!(!set.set_priority(3, 1));
+        let bytes = set.to_bytes();
+        assert_eq!(&bytes[..5], b"BMPS\x02");
+        let decoded = PatternSet::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, set);
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(
+            PatternSet::from_bytes(&PatternSet::default().to_bytes()).unwrap(),
+            PatternSet::default()
+        );
+        // Lengths of 128 and more take multiple varint bytes
+        let long: PatternSet = [("x".repeat(300), Pattern::new(&"?? ".repeat(200)).unwrap())]
+            .into_iter()
+            .collect();
+        assert_eq!(PatternSet::from_bytes(&long.to_bytes()).unwrap(), long);
+
+        let mut corrupted = bytes.clone();
+        corrupted[10] ^= 1;
+        assert_eq!(
+            PatternSet::from_bytes(&corrupted),
+            Err(SetDecodeError::ChecksumMismatch)
+        );
+        let mut stale = bytes.clone();
+        stale[4] = 0;
+        assert_eq!(
+            PatternSet::from_bytes(&stale),
+            Err(SetDecodeError::UnsupportedVersion(0))
+        );
+        assert_eq!(
+            PatternSet::from_bytes(b"ELF"),
+            Err(SetDecodeError::BadMagic)
+        );
+        assert_eq!(
+            PatternSet::from_bytes(b"BMPS"),
+            Err(SetDecodeError::Truncated)
+        );
+        assert_eq!(
+            PatternSet::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(SetDecodeError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn pattern_set_parallel_scans_agree_with_serial() {
+        let mut state = 0x9A2A11E1;
+        let data: Vec<u8> = (0..10 * 1024 * 1024)
+            .map(|_| next_random(&mut state) as u8)
+            .collect();
+        // Windows of the data with one wildcard, so every Pattern matches at least once
+        let patterns: Vec<Pattern> = (0..50)
+            .map(|_| {
+                let start = (next_random(&mut state) % (data.len() as u64 - 8)) as usize;
+                let len = 3 + (next_random(&mut state) % 4) as usize;
+                let wildcard = (next_random(&mut state) % len as u64) as usize;
+                let text: Vec<String> = (0..len)
+                    .map(|i| match i == wildcard {
+                        true => "??".to_string(),
+                        false => format!("{:02X}", data[start + i]),
+                    })
+                    .collect();
+                Pattern::new(&text.join(" ")).unwrap()
+            })
+            .collect();
+        let set = PatternSet::new(patterns);
+        let serial = set.find_matches(&data);
+        assert_eq!(set.par_find_matches(&data), serial);
+        assert_eq!(set.par_find_by_chunks(&data, 7), serial);
+        assert_eq!(set.par_find_by_patterns(&data, 16), serial);
+
+        // Patterns matching everywhere, including at the very end of the haystack
+        let set = PatternSet::new(vec![
+            Pattern::new("").unwrap(),
+            Pattern::new("?? ??").unwrap(),
+        ]);
+        let short = &data[..100];
+        let serial = set.find_matches(short);
+        assert_eq!(set.par_find_by_chunks(short, 200), serial);
+        assert_eq!(set.par_find_by_patterns(short, 3), serial);
+        assert_eq!(set.par_find_by_chunks(&[], 4), set.find_matches(&[]));
+    }
+
+    #[test]
+    fn replace_matches_non_overlapping() {
+        let pattern = Pattern::new("00 ??").unwrap();
+        let data = vec![0x11, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00];
+        assert!(matches!(
+            pattern.replace_matches_cow(&data, &[0xFF, 0xFF]).unwrap(),
+            Cow::Owned(_)
+        ));
+        // Adjacent matches, then an overlapping run ending on the very last byte
+        assert_eq!(
+            pattern.replace_matches(&data, &[0xAB, 0xCD]).unwrap(),
+            vec![0x11, 0xAB, 0xCD, 0xAB, 0xCD, 0xAB, 0xCD, 0x00]
+        );
+        let data = vec![0x11, 0x00, 0x00];
+        assert_eq!(
+            pattern.replace_matches(&data, &[0xAB, 0xCD]).unwrap(),
+            vec![0x11, 0xAB, 0xCD]
+        );
+
+        let data = vec![0x11, 0x22];
+        assert!(matches!(
+            pattern.replace_matches_cow(&data, &[0xFF, 0xFF]).unwrap(),
+            Cow::Borrowed(borrowed) if borrowed == data.as_slice()
+        ));
+        assert!(matches!(
+            pattern.replace_matches(&data, &[0xFF]),
+            Err(BinmatchError::ReplacementLengthError {
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn patch_in_place_uses_original_contents() {
+        // Every replacement creates a new match with the byte after it, which must not be patched
+        let pattern = Pattern::new("?? 01").unwrap();
+        let mut buf = vec![0x00, 0x01, 0x01, 0x05, 0x01];
+        assert_eq!(pattern.patch_in_place(&mut buf, &[0x01, 0x01]).unwrap(), 2);
+        assert_eq!(buf, vec![0x01, 0x01, 0x01, 0x01, 0x01]);
+        assert_eq!(pattern.patch_in_place(&mut buf, &[0x01, 0x01]).unwrap(), 2);
+
+        // A replacement destroying the next match doesn't stop it from being patched
+        let pattern = Pattern::new("AA BB").unwrap();
+        let mut buf = vec![0xAA, 0xBB, 0xAA, 0xBB];
+        assert_eq!(pattern.patch_in_place(&mut buf, &[0x00, 0xAA]).unwrap(), 2);
+        assert_eq!(buf, vec![0x00, 0xAA, 0x00, 0xAA]);
+        assert!(matches!(
+            pattern.patch_in_place(&mut buf, &[]),
+            Err(BinmatchError::ReplacementLengthError {
+                expected: 2,
+                found: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn patch_templates_keep_wildcard_bytes() {
+        let pattern = Pattern::new("48 8B ?? __ 00").unwrap();
+        let template = PatchTemplate::new(&pattern, "90 __ ?? CC __").unwrap();
+        let data = vec![0x48, 0x8B, 0x05, 0x11, 0x00, 0x48, 0x8B, 0x0D, 0x22, 0x00];
+        assert_eq!(
+            pattern.replace_with_template(&data, &template).unwrap(),
+            vec![0x90, 0x8B, 0x05, 0xCC, 0x00, 0x90, 0x8B, 0x0D, 0xCC, 0x00]
+        );
+        assert_eq!(
+            pattern
+                .replace_with_template(&data, &PatchTemplate::literal(&[0; 5]))
+                .unwrap(),
+            vec![0; 10]
+        );
+        assert!(matches!(
+            PatchTemplate::new(&pattern, "90 90"),
+            Err(BinmatchError::ReplacementLengthError {
+                expected: 5,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            PatchTemplate::new(&pattern, "90 9"),
+            Err(BinmatchError::PatternLengthError { len: 3 })
+        ));
+        assert!(matches!(
+            pattern.replace_with_template(&data, &PatchTemplate::literal(&[0; 4])),
+            Err(BinmatchError::ReplacementLengthError {
+                expected: 5,
+                found: 4
+            })
+        ));
+    }
+
+    /// A file in the temporary directory that is removed again when dropped
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &[u8]) -> TempFile {
+            let path =
+                std::env::temp_dir().join(format!("binmatch-{}-{}", std::process::id(), name));
+            std::fs::write(&path, contents).unwrap();
+            TempFile(path)
+        }
+
+        fn read(&self) -> Vec<u8> {
+            std::fs::read(&self.0).unwrap()
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn patch_file_in_place() {
+        let mut state = 0xF11E;
+        let mut data: Vec<u8> = (0..300_000)
+            .map(|_| next_random(&mut state) as u8 | 0x80)
+            .collect();
+        // One signature crosses the boundary of the first two chunks, one ends on the last byte
+        let offsets = [10, 64 * 1024 - 2, 200_000, data.len() - 4];
+        for &offset in &offsets {
+            data[offset..offset + 4].copy_from_slice(&[0x0F, 0x84, offset as u8 & 0x7F, 0x00]);
+        }
+        let file = TempFile::new("patch_file_in_place", &data);
+        let pattern = Pattern::new("0F 84 ?? 00").unwrap();
+        let template = PatchTemplate::new(&pattern, "90 E9 __ __").unwrap();
+
+        let report = pattern
+            .patch_file(&file.0, &template, &PatchOptions::new().dry_run())
+            .unwrap();
+        assert_eq!(report.len(), 4);
+        assert_eq!(file.read(), data);
+
+        let report = pattern
+            .patch_file(&file.0, &template, &PatchOptions::new().max_patches(3))
+            .unwrap();
+        let patched: Vec<u64> = report.records().iter().map(|r| r.offset).collect();
+        assert_eq!(patched, [10, 64 * 1024 - 2, 200_000]);
+        let mut expected = data.clone();
+        for &offset in &offsets[..3] {
+            expected[offset] = 0x90;
+            expected[offset + 1] = 0xE9;
+        }
+        assert_eq!(report.records()[0].old, [0x0F, 0x84, 10, 0x00]);
+        assert_eq!(report.records()[0].new, [0x90, 0xE9, 10, 0x00]);
+        assert_eq!(file.read(), expected);
+
+        let report = pattern
+            .patch_file(&file.0, &template, &PatchOptions::new())
+            .unwrap();
+        assert_eq!(report.len(), 1);
+        let offset = offsets[3];
+        expected[offset] = 0x90;
+        expected[offset + 1] = 0xE9;
+        assert_eq!(file.read(), expected);
+
+        let err = pattern
+            .patch_file(
+                &file.0,
+                &PatchTemplate::literal(&[0x90]),
+                &PatchOptions::new(),
+            )
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn patch_reports_restore_the_original() {
+        let pattern = Pattern::new("E8 ?? ?? __ 00").unwrap();
+        let template = PatchTemplate::new(&pattern, "90 90 __ 90 __").unwrap();
+        let original = vec![
+            0xE8, 0x01, 0x02, 0x03, 0x00, 0xCC, 0xE8, 0x05, 0x90, 0x07, 0x00,
+        ];
+        let mut buf = original.clone();
+        let report = pattern
+            .patch(&mut buf, &template, &PatchOptions::new())
+            .unwrap();
+        assert_eq!(report.len(), 2);
+        assert_eq!(
+            report.to_string(),
+            "@@ 0x00000000 @@\n-e8 01 02 03 00\n+90 90 02 90 00\n\
+             @@ 0x00000006 @@\n-e8 05 90 07 00\n+90 90 90 90 00\n"
+        );
+        for record in report.records() {
+            let offset = record.offset as usize;
+            assert_eq!(buf[offset..offset + record.new.len()], record.new);
+            buf[offset..offset + record.old.len()].copy_from_slice(&record.old);
+        }
+        assert_eq!(buf, original);
+
+        let report = pattern
+            .patch(
+                &mut buf,
+                &template,
+                &PatchOptions::new().dry_run().max_patches(1),
+            )
+            .unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(buf, original);
+        assert!(pattern
+            .patch(
+                &mut buf,
+                &PatchTemplate::literal(&[0]),
+                &PatchOptions::new()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn conditional_patching() {
+        let pattern = Pattern::new("83 F8 ??").unwrap();
+        let template = PatchTemplate::new(&pattern, "83 F8 00").unwrap();
+        let mut buf = vec![
+            0x83, 0xF8, 0x01, // patched
+            0x83, 0xF8, 0x02, // skipped, wrong immediate
+            0x83, 0xF8, 0x01, // skipped, outside of the range
+            0x83, 0xF8, 0x83, 0xF8, 0x01, // skipped, but the overlapping match is patched
+        ];
+        let report = pattern
+            .patch_if(&mut buf, &template, &PatchOptions::new(), |m| {
+                m.captures().get(0).unwrap().value == 0x01 && !(6..9).contains(&m.start())
+                    || m.start() == 11
+            })
+            .unwrap();
+        let patched: Vec<u64> = report.records().iter().map(|r| r.offset).collect();
+        assert_eq!(patched, [0, 11]);
+        assert_eq!(report.skipped(), 3);
+        assert_eq!(
+            buf,
+            vec![
+                0x83, 0xF8, 0x00, 0x83, 0xF8, 0x02, 0x83, 0xF8, 0x01, 0x83, 0xF8, 0x83, 0xF8, 0x00
+            ]
+        );
+    }
+
+    #[test]
+    fn fill_matches_leaves_other_bytes_alone() {
+        let mut state = 0xF1;
+        let original = random_data(&mut state, 512);
+        let pattern = Pattern::new("01 ?? 02").unwrap();
+        let matches = pattern.find_with(&original, &MatchOptions::new().non_overlapping());
+        assert!(!matches.is_empty());
+
+        let mut buf = original.clone();
+        assert_eq!(pattern.fill_matches(&mut buf, 0xCC), matches.len());
+        let mut filled = vec![false; buf.len()];
+        for m in &matches {
+            filled[m.range()].iter_mut().for_each(|f| *f = true);
+        }
+        for (index, byte) in buf.iter().enumerate() {
+            match filled[index] {
+                true => assert_eq!(*byte, 0xCC),
+                false => assert_eq!(*byte, original[index]),
+            }
+        }
+
+        let file = TempFile::new("fill_matches", &original);
+        let report = pattern
+            .fill_file(&file.0, 0xCC, &PatchOptions::new())
+            .unwrap();
+        assert_eq!(report.len(), matches.len());
+        assert_eq!(file.read(), buf);
+    }
+
+    #[test]
+    fn patch_limits_leave_later_matches_untouched() {
+        let pattern = Pattern::new("C3 ??").unwrap();
+        let template = PatchTemplate::new(&pattern, "CC __").unwrap();
+        let mut original = vec![0x00; 50];
+        for offset in [3, 10, 20, 30, 48] {
+            original[offset] = 0xC3;
+        }
+        let mut buf = original.clone();
+        let report = pattern
+            .patch(&mut buf, &template, &PatchOptions::new().max_patches(2))
+            .unwrap();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report.candidates(), 2);
+        assert!(report.limit_reached());
+        assert_eq!((buf[3], buf[10]), (0xCC, 0xCC));
+        assert_eq!(buf[11..], original[11..]);
+
+        let (_, report) = pattern
+            .replace_matches_if(
+                &original,
+                &template,
+                &PatchOptions::new().max_patches(2),
+                |m| m.start() != 3,
+            )
+            .unwrap();
+        assert_eq!(
+            (report.len(), report.skipped(), report.candidates()),
+            (2, 1, 3)
+        );
+
+        let report = pattern
+            .patch(&mut buf, &template, &PatchOptions::new())
+            .unwrap();
+        assert_eq!(report.len(), 3);
+        assert!(!report.limit_reached());
+        let report = pattern
+            .patch(
+                &mut original.clone(),
+                &template,
+                &PatchOptions::new().max_patches(5),
+            )
+            .unwrap();
+        assert!(!report.limit_reached());
+
+        let file = TempFile::new("patch_limits", &original);
+        let report = pattern
+            .patch_file(&file.0, &template, &PatchOptions::new().max_patches(2))
+            .unwrap();
+        assert!(report.limit_reached());
+        let written = file.read();
+        assert_eq!((written[3], written[10]), (0xCC, 0xCC));
+        assert_eq!(written[11..], original[11..]);
+
+        // The last match is exactly the limit, so there is nothing left that could be patched
+        let file = TempFile::new("patch_limits_last", &original);
+        let options = PatchOptions::new().max_patches(5).dry_run();
+        let report = pattern.patch_file(&file.0, &template, &options).unwrap();
+        assert_eq!((report.len(), report.candidates()), (5, 5));
+        assert!(!report.limit_reached());
+        let in_memory = pattern
+            .patch(&mut original.clone(), &template, &options)
+            .unwrap();
+        assert_eq!(report, in_memory);
+        let options = PatchOptions::new().max_patches(4).dry_run();
+        let report = pattern.patch_file(&file.0, &template, &options).unwrap();
+        assert!(report.limit_reached());
+        let in_memory = pattern
+            .patch(&mut original.clone(), &template, &options)
+            .unwrap();
+        assert_eq!(report, in_memory);
+    }
+
+    #[test]
+    fn patch_expressions() {
+        let patch = Patch::parse("0f 85 ?? ?? ?? ?? -> 90 E9 ?? __ __ __").unwrap();
+        assert_eq!(patch.to_string(), "0F 85 ?? ?? ?? ?? -> 90 E9 __ __ __ __");
+        let mut buf = vec![0x0F, 0x85, 0x10, 0x20, 0x30, 0x40];
+        let report = patch.apply(&mut buf);
+        assert_eq!(report.len(), 1);
+        assert_eq!(buf, vec![0x90, 0xE9, 0x10, 0x20, 0x30, 0x40]);
+        assert_eq!(Patch::parse(&patch.to_string()).unwrap(), patch);
+        assert!(Patch::parse("AA->BB").is_ok());
+
+        for malformed in ["74 ?? EB __", "74 -> EB -> 90", "74 - > EB", "74 => EB"] {
+            assert!(
+                matches!(Patch::parse(malformed), Err(BinmatchError::PatchArrowError))
+                    || matches!(
+                        Patch::parse(malformed),
+                        Err(BinmatchError::PatternParseError { .. })
+                    ),
+                "{}",
+                malformed
+            );
+        }
+        assert!(matches!(
+            Patch::parse("74 ?? EB __"),
+            Err(BinmatchError::PatchArrowError)
+        ));
+        assert!(matches!(
+            Patch::parse("74 ?? -> EB"),
+            Err(BinmatchError::ReplacementLengthError {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            Patch::parse("74 ?? -> EB _"),
+            Err(BinmatchError::PatternLengthError { len: 3 })
+        ));
+        assert!(matches!(
+            Patch::new(Pattern::new("74").unwrap(), PatchTemplate::literal(&[])),
+            Err(BinmatchError::ReplacementLengthError {
+                expected: 1,
+                found: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn dry_run_reports_are_verified_before_applying() {
+        let pattern = Pattern::new("E8 ?? ?? ?? ??").unwrap();
+        let template = PatchTemplate::literal(&[0x90; 5]);
+        let original = vec![
+            0x55, 0xE8, 0x01, 0x02, 0x03, 0x04, 0xC3, 0xE8, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let mut buf = original.clone();
+        let report = pattern
+            .patch_if(&mut buf, &template, &PatchOptions::new().dry_run(), |m| {
+                m.start() > 0
+            })
+            .unwrap();
+        assert_eq!(buf, original);
+        assert_eq!(report.len(), 2);
+        assert_eq!(report.verify_against(&buf), Ok(()));
+
+        let mut changed = original.clone();
+        changed[9] = 0xFF;
+        assert_eq!(
+            report.apply(&mut changed),
+            Err(VerifyError::Mismatch {
+                offset: 9,
+                index: 2,
+                expected: 0x00,
+                found: 0xFF
+            })
+        );
+        assert_eq!(changed[1..6], original[1..6]);
+        assert_eq!(
+            report.verify_against(&original[..10]),
+            Err(VerifyError::OutOfBounds {
+                offset: 7,
+                len: 5,
+                available: 10
+            })
+        );
+
+        assert_eq!(report.apply(&mut buf), Ok(2));
+        assert_eq!(buf[1..6], [0x90; 5]);
+        assert_eq!(buf[7..], [0x90; 5]);
+        assert!(matches!(
+            report.verify_against(&buf),
+            Err(VerifyError::Mismatch { offset: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn patch_report_from_records() {
+        let patch = Patch::parse("74 ?? -> EB __").unwrap();
+        let original = vec![0x90, 0x74, 0x05, 0x90, 0x74, 0x10];
+        let mut data = original.clone();
+        let report = patch.apply(&mut data);
+
+        // Rebuilt from the records alone, e.g. after saving them in another process
+        let saved = report.records().to_vec();
+        let rebuilt = PatchReport::from_records(saved.clone());
+        assert_eq!(rebuilt, report);
+        assert_eq!(saved.iter().cloned().collect::<PatchReport>(), report);
+        let mut reverted = data.clone();
+        assert_eq!(rebuilt.revert(&mut reverted), Ok(2));
+        assert_eq!(reverted, original);
+        assert_eq!(rebuilt.apply(&mut reverted), Ok(2));
+        assert_eq!(reverted, data);
+
+        // Records built by hand are checked instead of panicking on the buffer
+        let uneven = PatchReport::from_records(vec![PatchRecord {
+            offset: 1,
+            old: vec![0x74],
+            new: vec![0xEB, 0x05],
+        }]);
+        assert_eq!(
+            uneven.apply(&mut reverted),
+            Err(VerifyError::LengthMismatch {
+                offset: 1,
+                old: 1,
+                new: 2
+            })
+        );
+        assert_eq!(
+            uneven.revert(&mut reverted),
+            Err(RevertError::LengthMismatch {
+                offset: 1,
+                old: 1,
+                new: 2
+            })
+        );
+        assert_eq!(reverted, data);
+        let past_end = PatchReport::from_records(vec![PatchRecord {
+            offset: 5,
+            old: vec![0x10, 0x00],
+            new: vec![0x90, 0x90],
+        }]);
+        assert!(matches!(
+            past_end.apply(&mut reverted),
+            Err(VerifyError::OutOfBounds { offset: 5, .. })
+        ));
+        assert!(matches!(
+            past_end.revert(&mut reverted),
+            Err(RevertError::OutOfBounds { offset: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn revert_patch_report() {
+        let patch = Patch::parse("74 ?? -> EB __").unwrap();
+        let original = vec![0x90, 0x74, 0x05, 0x90, 0x74, 0x10];
+        let mut data = original.clone();
+        let report = patch.apply(&mut data);
+        assert_eq!(report.len(), 2);
+
+        // Clean revert, then reverting again changes nothing
+        let mut reverted = data.clone();
+        assert_eq!(report.revert(&mut reverted), Ok(2));
+        assert_eq!(reverted, original);
+        assert_eq!(report.revert(&mut reverted), Ok(0));
+        assert_eq!(reverted, original);
+
+        // Modified after patching, nothing is written unless forced
+        let mut modified = data.clone();
+        modified[4] = 0xCC;
+        assert_eq!(
+            report.revert(&mut modified),
+            Err(RevertError::Modified { offset: 4 })
+        );
+        assert_eq!(modified[1], 0xEB);
+        assert_eq!(report.force_revert(&mut modified), Ok(2));
+        assert_eq!(modified, original);
+
+        assert_eq!(
+            report.revert(&mut data[..5]),
+            Err(RevertError::OutOfBounds {
+                offset: 4,
+                len: 2,
+                available: 5
+            })
+        );
+
+        let file = TempFile::new("revert", &data);
+        assert_eq!(report.revert_file(&file.0).unwrap(), 2);
+        assert_eq!(file.read(), original);
+        assert_eq!(report.revert_file(&file.0).unwrap(), 0);
+        std::fs::write(&file.0, &modified[..4]).unwrap();
+        let err = report.revert_file(&file.0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // A record ending past `u64::MAX` is out of bounds instead of overflowing
+        let report = PatchReport::from_records(vec![PatchRecord {
+            offset: u64::MAX,
+            old: vec![0x74],
+            new: vec![0xEB],
+        }]);
+        let expected = RevertError::OutOfBounds {
+            offset: u64::MAX,
+            len: 1,
+            available: 4,
+        };
+        assert_eq!(report.revert(&mut modified[..4]), Err(expected.clone()));
+        let err = report.revert_file(&file.0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let err = err.into_inner().unwrap().downcast::<RevertError>().unwrap();
+        assert_eq!(*err, expected);
+    }
+
+    #[test]
+    fn patch_hooks_writing_ahead_of_the_search() {
+        let pattern = Pattern::new("74 ??").unwrap();
+        let data = vec![0x74, 0x01, 0x90, 0x90, 0x74, 0x02, 0x90];
+        // Without hooks only the original matches are replaced
+        let mut plain = data.clone();
+        assert_eq!(
+            pattern.patch_in_place(&mut plain, &[0x90, 0x74]).unwrap(),
+            2
+        );
+        assert_eq!(plain, vec![0x90, 0x74, 0x90, 0x90, 0x90, 0x74, 0x90]);
+
+        // The first hook call creates a match at 2 and destroys the one at 4, the search sees both
+        let options = PatchOptions::new().after_patch(|buf, record| {
+            if record.offset == 0 {
+                buf[2] = 0x74;
+                buf[4] = 0x90;
+            }
+        });
+        let mut hooked = data.clone();
+        let report = pattern
+            .patch_in_place_with(&mut hooked, &[0x90, 0x74], &options)
+            .unwrap();
+        let offsets: Vec<u64> = report
+            .records()
+            .iter()
+            .map(|record| record.offset)
+            .collect();
+        assert_eq!(offsets, vec![0, 2]);
+        assert_eq!(hooked, vec![0x90, 0x74, 0x90, 0x74, 0x90, 0x02, 0x90]);
+    }
+
+    #[test]
+    fn patch_hooks_run_after_each_patch() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+
+        // The last byte holds the sum of the bytes before it
+        fn checksum(buf: &mut [u8]) {
+            let (body, sum) = buf.split_at_mut(buf.len() - 1);
+            sum[0] = body.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        }
+
+        let pattern = Pattern::new("74 ??").unwrap();
+        let template = PatchTemplate::new(&pattern, "EB __").unwrap();
+        let data = vec![0x74, 0x01, 0x74, 0x02, 0x90, 0x00];
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let options = PatchOptions::new()
+            .after_patch(move |buf, record| log.lock().unwrap().push((record.offset, buf.to_vec())))
+            .after_patch(|buf, _| checksum(buf));
+        let mut patched = data.clone();
+        let report = pattern.patch(&mut patched, &template, &options).unwrap();
+        assert_eq!(report.len(), 2);
+        // The first hook sees the earlier patches and their checksum, but not the later patches
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                (0, vec![0xEB, 0x01, 0x74, 0x02, 0x90, 0x00]),
+                (2, vec![0xEB, 0x01, 0xEB, 0x02, 0x90, 0xF2]),
+            ]
+        );
+        assert_eq!(patched, vec![0xEB, 0x01, 0xEB, 0x02, 0x90, 0x69]);
+        // Hooks are ignored when comparing, and the options can be shared between threads
+        assert_eq!(options, PatchOptions::new());
+        std::thread::scope(|scope| {
+            scope.spawn(|| pattern.patch(&mut data.clone(), &template, &options));
+        });
+
+        seen.lock().unwrap().clear();
+        let mut untouched = data.clone();
+        pattern
+            .patch(&mut untouched, &template, &options.clone().dry_run())
+            .unwrap();
+        assert_eq!(untouched, data);
+        assert!(seen.lock().unwrap().is_empty());
+
+        // Files get their own hook, buffer hooks don't run for them
+        let file = TempFile::new("after-patch", &data);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let options = options.after_patch_file(move |file, _| {
+            use std::io::{Read, Seek, SeekFrom, Write};
+            counter.fetch_add(1, Ordering::Relaxed);
+            let mut buf = Vec::new();
+            file.seek(SeekFrom::Start(0))?;
+            file.read_to_end(&mut buf)?;
+            checksum(&mut buf);
+            file.seek(SeekFrom::End(-1))?;
+            file.write_all(&buf[buf.len() - 1..])
+        });
+        pattern.patch_file(&file.0, &template, &options).unwrap();
+        assert_eq!(file.read(), patched);
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+        assert!(seen.lock().unwrap().is_empty());
+
+        let file = TempFile::new("after-patch-error", &data);
+        let options = PatchOptions::new()
+            .after_patch_file(|_, _| Err(std::io::Error::other("checksum region missing")));
+        let err = pattern
+            .patch_file(&file.0, &template, &options)
+            .unwrap_err();
+        let partial = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<PartialPatchError>())
+            .unwrap();
+        assert_eq!((partial.offset, partial.report.len()), (0, 1));
+        assert_eq!(file.read()[..4], [0xEB, 0x01, 0x74, 0x02]);
+    }
+
+    #[test]
+    fn find_in_reader_across_chunks() {
+        let pattern = Pattern::new("DE AD ?? EF").unwrap();
+        let mut data = [0u8; 40];
+        // Across the boundary between the first two chunks of 16 bytes, and ending on the last byte
+        data[14..18].copy_from_slice(&[0xDE, 0xAD, 0x01, 0xEF]);
+        data[36..40].copy_from_slice(&[0xDE, 0xAD, 0x02, 0xEF]);
+        for chunk_size in [0, 1, 3, 4, 16, 17, 64] {
+            let found = pattern.find_in_reader_with(&data[..], chunk_size).unwrap();
+            let offsets: Vec<u64> = found.iter().map(FileMatch::offset).collect();
+            assert_eq!(offsets, [14, 36], "chunk size {chunk_size}");
+            assert_eq!(found[1].end(), 40);
+            assert_eq!(found[0].bytes(), &[0xDE, 0xAD, 0x01, 0xEF]);
+            let capture = found[1].captures().get(0).unwrap();
+            assert_eq!((capture.value, capture.haystack_offset), (0x02, 2));
+        }
+
+        let mut state = 0x5EED;
+        for _ in 0..50 {
+            let pattern = Pattern::new(&random_pattern(&mut state, 4)).unwrap();
+            let data = random_data(&mut state, 300);
+            let chunk_size = (next_random(&mut state) % 40) as usize + 1;
+            let expected: Vec<u64> = pattern
+                .find_with(&data, &MatchOptions::new())
+                .iter()
+                .map(|m| m.start() as u64)
+                .collect();
+            let found = pattern.find_in_reader_with(&data[..], chunk_size).unwrap();
+            let offsets: Vec<u64> = found.iter().map(FileMatch::offset).collect();
+            assert_eq!(offsets, expected);
+        }
+
+        // Fails after handing out 20 bytes
+        struct Failing<'a>(&'a [u8]);
+        impl std::io::Read for Failing<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0.is_empty() {
+                    return Err(std::io::Error::other("bad sector"));
+                }
+                let n = buf.len().min(self.0.len());
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+        let err = pattern
+            .find_in_reader_with(Failing(&data[..20]), 8)
+            .unwrap_err();
+        let partial = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<PartialReadError>())
+            .unwrap();
+        assert_eq!(partial.offset, 20);
+        assert_eq!(partial.matches.len(), 1);
+    }
+
+    #[test]
+    fn find_in_file_edge_cases() {
+        let pattern = Pattern::new("CA FE ?? BA BE").unwrap();
+        let empty = TempFile::new("find-empty", &[]);
+        assert!(pattern.find_in_file(&empty.0).unwrap().is_empty());
+        let short = TempFile::new("find-short", &[0xCA, 0xFE, 0x00, 0xBA]);
+        assert!(pattern.find_in_file(&short.0).unwrap().is_empty());
+
+        let mut data = vec![0u8; 200_000];
+        data[65_534..65_539].copy_from_slice(&[0xCA, 0xFE, 0x01, 0xBA, 0xBE]);
+        data[199_995..].copy_from_slice(&[0xCA, 0xFE, 0x02, 0xBA, 0xBE]);
+        let file = TempFile::new("find-large", &data);
+        let found = pattern.find_in_file(&file.0).unwrap();
+        let offsets: Vec<u64> = found.iter().map(FileMatch::offset).collect();
+        assert_eq!(offsets, [65_534, 199_995]);
+
+        let missing = std::env::temp_dir().join("binmatch-does-not-exist");
+        assert!(pattern.find_in_file(&missing).is_err());
+    }
+
+    #[test]
+    fn stream_matcher_random_splits() {
+        let mut state = 0xC0FFEE;
+        for _ in 0..100 {
+            let pattern = Pattern::new(&random_pattern(&mut state, 5)).unwrap();
+            let data = random_data(&mut state, 200);
+            let expected: Vec<(u64, Vec<u8>)> = pattern
+                .find_with(&data, &MatchOptions::new())
+                .iter()
+                .map(|m| (m.start() as u64, data[m.range()].to_vec()))
+                .collect();
+            let mut matcher: StreamMatcher = pattern.stream_matcher();
+            let mut found = Vec::new();
+            let mut rest = &data[..];
+            while !rest.is_empty() {
+                // Mostly tiny pushes, sometimes large ones and empty ones
+                let len = match next_random(&mut state) % 4 {
+                    0 => 0,
+                    1 => next_random(&mut state) as usize % 64,
+                    _ => 1,
+                };
+                let (chunk, tail) = rest.split_at(len.min(rest.len()));
+                found.extend(matcher.push(chunk));
+                rest = tail;
+            }
+            assert_eq!(matcher.position(), data.len() as u64);
+            found.extend(matcher.finish());
+            let found: Vec<(u64, Vec<u8>)> = found
+                .iter()
+                .map(|m| (m.offset(), m.bytes().to_vec()))
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn stream_matcher_is_send() {
+        fn assert_send<T: Send>(_: &T) {}
+        let pattern = Pattern::new("AA").unwrap().map_capture(0, |byte| byte);
+        let matcher = pattern.stream_matcher();
+        assert_send(&matcher);
+        // A matcher moved to another thread between pushes keeps its carry
+        let mut matcher = std::thread::scope(|scope| {
+            scope
+                .spawn(move || {
+                    let mut matcher = matcher;
+                    assert!(matcher.push(&[0x00]).is_empty());
+                    matcher
+                })
+                .join()
+                .unwrap()
+        });
+        assert_eq!(matcher.push(&[0xAA])[0].offset(), 1);
+    }
+
+    #[test]
+    fn matches_in_reader_is_incremental() {
+        // Hands out one byte per read and counts the reads
+        struct Trickle<'a> {
+            data: &'a [u8],
+            reads: std::rc::Rc<std::cell::Cell<usize>>,
+        }
+        impl std::io::Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.reads.set(self.reads.get() + 1);
+                let Some((first, rest)) = self.data.split_first() else {
+                    return Ok(0);
+                };
+                buf[0] = *first;
+                self.data = rest;
+                Ok(1)
+            }
+        }
+
+        let pattern = Pattern::new("AA ?? AA").unwrap();
+        let data = [0x00, 0xAA, 0x01, 0xAA, 0x02, 0xAA, 0x00, 0x00];
+        let reads = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut found = pattern.matches_in_reader(Trickle {
+            data: &data,
+            reads: reads.clone(),
+        });
+        assert_eq!(found.next().unwrap().unwrap().offset(), 1);
+        assert_eq!(reads.get(), 4);
+        assert_eq!(found.next().unwrap().unwrap().offset(), 3);
+        assert_eq!(reads.get(), 6);
+        assert!(found.next().is_none());
+        assert!(found.next().is_none());
+
+        let offsets: Vec<u64> = pattern
+            .matches_in_reader(&data[..])
+            .map(|found| found.unwrap().offset())
+            .collect();
+        assert_eq!(offsets, [1, 3]);
+
+        let failing = std::io::Read::chain(&data[..4], FailingReader);
+        let results: Vec<_> = pattern.matches_in_reader(failing).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().offset(), 1);
+        assert!(results[1].is_err());
+    }
+
+    struct FailingReader;
+
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("connection reset"))
+        }
+    }
+
+    #[test]
+    fn scan_writer_matches_direct_scan() {
+        use std::io::Write;
+
+        // Accepts at most 3 bytes per write
+        struct Short(Vec<u8>);
+        impl Write for Short {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(3);
+                self.0.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut state = 0xBEEF;
+        for _ in 0..50 {
+            let pattern = Pattern::new(&random_pattern(&mut state, 4)).unwrap();
+            let data = random_data(&mut state, 150);
+            let mut writer = ScanWriter::new(&pattern, Short(Vec::new()));
+            let mut rest = &data[..];
+            while !rest.is_empty() {
+                let len = (next_random(&mut state) % 8) as usize;
+                let (chunk, tail) = rest.split_at(len.min(rest.len()));
+                writer.write_all(chunk).unwrap();
+                rest = tail;
+            }
+            writer.flush().unwrap();
+            assert_eq!(writer.position(), data.len() as u64);
+            let found: Vec<u64> = writer.matches().iter().map(FileMatch::offset).collect();
+            let sink = writer.into_inner().0;
+            assert_eq!(sink, data);
+            let expected: Vec<u64> = pattern
+                .find_with(&sink, &MatchOptions::new())
+                .iter()
+                .map(|m| m.start() as u64)
+                .collect();
+            assert_eq!(found, expected);
+        }
+
+        let pattern = Pattern::new("AA").unwrap();
+        let mut seen = Vec::new();
+        let mut writer = ScanWriter::new(&pattern, Vec::new()).on_match(|m| seen.push(m.offset()));
+        writer.write_all(&[0xAA, 0x00, 0xAA]).unwrap();
+        assert!(writer.matches().is_empty());
+        drop(writer);
+        assert_eq!(seen, [0, 2]);
+    }
 
     #[test]
-    fn test_pattern_chunk_matching() {
-        let pattern = Pattern::new("00 ?? 00 ??").unwrap();
-        let (matches, _) = pattern.match_chunk(vec![0, 42, 0, 13]);
-        assert_eq!(matches, vec![(42, 1), (13, 3)])
+    fn streaming_offsets_above_4_gib() {
+        use std::io::Write;
+        const BASE: u64 = 5 << 30;
+
+        let pattern = Pattern::new("AA ?? AA").unwrap();
+        let data = [0x00, 0xAA, 0x01, 0xAA, 0x00];
+
+        let mut matcher = pattern.stream_matcher_at(BASE);
+        let mut found = matcher.push(&data[..2]);
+        found.extend(matcher.push(&data[2..]));
+        assert_eq!(found[0].offset(), BASE + 1);
+        assert_eq!(found[0].end(), BASE + 4);
+        assert_eq!(matcher.position(), BASE + 5);
+
+        let found = pattern.find_in_reader_from(&data[..], 2, BASE).unwrap();
+        assert_eq!(found[0].offset(), BASE + 1);
+
+        let mut writer = ScanWriter::new_at(&pattern, Vec::new(), BASE);
+        writer.write_all(&data).unwrap();
+        assert_eq!(writer.matches()[0].offset(), BASE + 1);
+
+        let m = &pattern.find_with(&data, &MatchOptions::new())[0];
+        let found = FileMatch::from_match(BASE, m, &data).unwrap();
+        assert_eq!(found.offset(), BASE + 1);
+        assert_eq!(found.captures().get(0).unwrap().haystack_offset, 1);
+        assert_eq!(FileMatch::from_match(u64::MAX - 2, m, &data), None);
     }
 
     #[test]
-    fn test_pattern_whole_matching() {
-        let pattern = Pattern::new("DEADBEEF ??").unwrap();
-        let mut data: Vec<u8> = vec![0xDE, 0xAD, 0xBE, 0xEF, 23];
-        for i in 0..100 {
-            data.insert(0, i);
+    fn stream_positions_saturate() {
+        let pattern = Pattern::new("AA ?? AA").unwrap();
+        let data = [0x00, 0xAA, 0x01, 0xAA, 0x00, 0xAA, 0x02, 0xAA];
+
+        let mut matcher = pattern.stream_matcher_at(u64::MAX - 5);
+        let found = matcher.push(&data);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].end(), u64::MAX - 1);
+        assert_eq!(matcher.position(), u64::MAX);
+        assert!(matcher.push(&data).is_empty());
+        assert_eq!(matcher.position(), u64::MAX);
+
+        let found = pattern
+            .find_in_reader_from(&data[..], 3, u64::MAX - 5)
+            .unwrap();
+        assert_eq!(found.len(), 1);
+    }
+
+    #[test]
+    fn find_in_chained_readers() {
+        use std::io::Read;
+
+        // Like the members of a multi-member gzip file, the parts form one stream
+        let pattern = Pattern::new("1F 8B ?? 08").unwrap();
+        let first: &[u8] = &[0x00, 0x1F, 0x8B];
+        let second: &[u8] = &[0x01, 0x08, 0x1F, 0x8B, 0x02, 0x08];
+        let found = pattern.find_in_reader(first.chain(second)).unwrap();
+        let offsets: Vec<u64> = found.iter().map(FileMatch::offset).collect();
+        assert_eq!(offsets, [1, 5]);
+
+        let err = pattern
+            .find_in_reader(second.chain(FailingReader))
+            .unwrap_err();
+        let partial = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<PartialReadError>())
+            .unwrap();
+        assert_eq!((partial.offset, partial.matches.len()), (6, 1));
+    }
+
+    /// A little endian ELF64 with `.text` at 0x401000, a `.debug_info` and the name table, each
+    /// section holding `contents`
+    fn build_elf(contents: &[u8]) -> Vec<u8> {
+        let names = b"\0.text\0.debug_info\0.shstrtab\0";
+        let mut elf = vec![0u8; 0x40];
+        elf[..6].copy_from_slice(&[0x7F, b'E', b'L', b'F', 2, 1]);
+        let text = elf.len();
+        elf.extend_from_slice(contents);
+        let debug = elf.len();
+        elf.extend_from_slice(contents);
+        let strtab = elf.len();
+        elf.extend_from_slice(names);
+        let shoff = elf.len();
+        elf[0x28..0x30].copy_from_slice(&(shoff as u64).to_le_bytes());
+        elf[0x3A..0x3C].copy_from_slice(&0x40u16.to_le_bytes());
+        elf[0x3C..0x3E].copy_from_slice(&4u16.to_le_bytes());
+        elf[0x3E..0x40].copy_from_slice(&3u16.to_le_bytes());
+        let sections: [(u32, u32, u64, u64, usize, usize); 4] = [
+            (0, 0, 0, 0, 0, 0),
+            (1, 1, 0x6, 0x401000, text, contents.len()),
+            (7, 1, 0, 0, debug, contents.len()),
+            (19, 3, 0, 0, strtab, names.len()),
+        ];
+        for (name, kind, flags, address, offset, size) in sections {
+            let mut header = [0u8; 0x40];
+            header[..4].copy_from_slice(&name.to_le_bytes());
+            header[4..8].copy_from_slice(&kind.to_le_bytes());
+            header[8..16].copy_from_slice(&flags.to_le_bytes());
+            header[16..24].copy_from_slice(&address.to_le_bytes());
+            header[24..32].copy_from_slice(&(offset as u64).to_le_bytes());
+            header[32..40].copy_from_slice(&(size as u64).to_le_bytes());
+            elf.extend_from_slice(&header);
         }
-        for i in 0..100 {
-            data.push(i);
+        elf
+    }
+
+    /// A PE32+ with image base 0x140000000, a `.text` at 0x1000 and a `.data` at 0x2000, each
+    /// section holding `contents`
+    fn build_pe(contents: &[u8]) -> Vec<u8> {
+        let mut pe = vec![0u8; 0x40];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        pe.extend_from_slice(b"PE\0\0");
+        let mut coff = [0u8; 20];
+        coff[2..4].copy_from_slice(&2u16.to_le_bytes());
+        coff[16..18].copy_from_slice(&112u16.to_le_bytes());
+        pe.extend_from_slice(&coff);
+        let mut optional = [0u8; 112];
+        optional[..2].copy_from_slice(&0x20Bu16.to_le_bytes());
+        optional[24..32].copy_from_slice(&0x1_4000_0000u64.to_le_bytes());
+        pe.extend_from_slice(&optional);
+        let data_start = pe.len() + 2 * 40;
+        for (index, (name, address, characteristics)) in [
+            (b".text\0\0\0", 0x1000u32, 0x6000_0020u32),
+            (b".data\0\0\0", 0x2000, 0xC000_0040),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut header = [0u8; 40];
+            header[..8].copy_from_slice(name);
+            header[8..12].copy_from_slice(&(contents.len() as u32).to_le_bytes());
+            header[12..16].copy_from_slice(&address.to_le_bytes());
+            header[16..20].copy_from_slice(&(contents.len() as u32).to_le_bytes());
+            let offset = data_start + index * contents.len();
+            header[20..24].copy_from_slice(&(offset as u32).to_le_bytes());
+            header[36..40].copy_from_slice(&characteristics.to_le_bytes());
+            pe.extend_from_slice(&header);
         }
-        let matches = pattern.find_matches_with_index(data);
-        assert_eq!(matches, vec![(23, 100 + 4)]);
+        pe.extend_from_slice(contents);
+        pe.extend_from_slice(contents);
+        pe
     }
 
     #[test]
-    fn test_convenience_method() {
-        let pattern = Pattern::new("DEADBEEF ??").unwrap();
-        let mut data: Vec<u8> = vec![0xDE, 0xAD, 0xBE, 0xEF, 23];
-        for i in 0..100 {
-            data.insert(0, i);
+    fn find_in_executable_sections() {
+        let pattern = Pattern::new("55 48 89 E5").unwrap();
+        let contents = [0x90, 0x55, 0x48, 0x89, 0xE5, 0xC3];
+
+        let elf = build_elf(&contents);
+        let scan = pattern.find_in_executable(&elf);
+        assert_eq!(scan.format(), ExecutableFormat::Elf);
+        assert!(!scan.is_fallback());
+        assert_eq!(scan.matches().len(), 1);
+        let found = &scan.matches()[0];
+        assert_eq!(found.section(), ".text");
+        assert_eq!(found.file_offset(), 0x41);
+        assert_eq!(found.virtual_address(), Some(0x401001));
+        assert_eq!(&elf[found.as_match().range()], &contents[1..5]);
+
+        let all = pattern.find_in_executable_with(&elf, |_| true);
+        let sections: Vec<_> = all.matches().iter().map(|m| m.section()).collect();
+        assert_eq!(sections, [".text", ".debug_info"]);
+        assert_eq!(all.matches()[1].virtual_address(), None);
+
+        let pe = build_pe(&contents);
+        let scan = pattern.find_in_executable(&pe);
+        assert_eq!(scan.format(), ExecutableFormat::Pe);
+        assert_eq!(scan.matches().len(), 1);
+        assert_eq!(scan.matches()[0].section(), ".text");
+        assert_eq!(scan.matches()[0].virtual_address(), Some(0x1_4000_1001));
+        let data = pattern.find_in_executable_with(&pe, |section| !section.is_executable());
+        assert_eq!(data.matches()[0].section(), ".data");
+        assert_eq!(data.matches()[0].virtual_address(), Some(0x1_4000_2001));
+
+        // Section headers cut off, everything is scanned
+        let truncated = &elf[..elf.len() - 10];
+        let scan = pattern.find_in_executable(truncated);
+        assert!(scan.is_fallback());
+        assert_eq!(scan.matches().len(), 2);
+        assert_eq!(scan.matches()[0].virtual_address(), None);
+    }
+
+    #[cfg(all(feature = "process", target_os = "linux"))]
+    #[test]
+    fn find_in_own_process() {
+        use crate::ProcessScanError;
+
+        static MARKER: [u8; 16] = [
+            0x3C, 0x9A, 0x51, 0xE7, 0x08, 0xB2, 0x6D, 0xF4, 0x13, 0xC8, 0x7E, 0x29, 0xA5, 0x40,
+            0xD6, 0x9F,
+        ];
+        let marker = std::hint::black_box(&MARKER).as_ptr() as u64;
+        let pattern = Pattern::new("3C 9A 51 E7 08 B2 6D F4 13 C8 7E 29 A5 40 ?? 9F").unwrap();
+        let found = pattern.find_in_process(std::process::id()).unwrap();
+        let planted = found.iter().find(|m| m.address() == marker).unwrap();
+        assert!(planted.region().permissions().read());
+        assert!(!planted.region().permissions().write());
+        assert_eq!(planted.captures().get(0).unwrap().value, 0xD6);
+        assert_eq!(planted.bytes(), &MARKER);
+        assert!(planted.region().start() <= marker && marker < planted.region().end());
+
+        let code = pattern
+            .find_in_process_with(std::process::id(), |region| region.permissions().execute())
+            .unwrap();
+        assert!(code.iter().all(|m| m.region().permissions().execute()));
+        assert_eq!(
+            code.first().map(|m| m.region().permissions().to_string()),
+            code.first().map(|_| "r-xp".to_string())
+        );
+
+        assert!(matches!(
+            pattern.find_in_process(u32::MAX),
+            Err(ProcessScanError::Maps { .. })
+        ));
+    }
+
+    #[cfg(all(feature = "process", target_os = "linux"))]
+    #[test]
+    fn find_in_self_locates_marker() {
+        #[no_mangle]
+        static BINMATCH_SELF_SCAN_MARKER: [u8; 12] = [
+            0x42, 0x4D, 0x53, 0x45, 0x4C, 0x46, 0x91, 0x27, 0xE3, 0x5B, 0x0C, 0xA8,
+        ];
+        let marker = std::hint::black_box(&BINMATCH_SELF_SCAN_MARKER).as_ptr() as u64;
+        let pattern = Pattern::new("42 4D 53 45 4C 46 91 27 E3 5B ?? A8").unwrap();
+        let found = pattern.find_in_self();
+        let planted = found.iter().find(|m| m.address() == marker).unwrap();
+        assert_eq!(planted.captures().get(0).unwrap().value, 0x0C);
+        assert!(planted.region().path().is_some());
+
+        let none = pattern.find_in_self_with(|region| region.permissions().write());
+        assert!(none.iter().all(|m| m.address() != marker));
+    }
+
+    #[test]
+    fn find_in_memory_sources() {
+        /// Reads at most 3 bytes at once, with one page that can't be read and one region
+        /// without read permission
+        struct Debugger {
+            memory: Vec<u8>,
+            hole: std::ops::Range<u64>,
+            fail_at: Option<u64>,
         }
-        for i in 0..100 {
-            data.push(i);
+        impl MemorySource for Debugger {
+            fn read(&mut self, address: u64, buf: &mut [u8]) -> Result<usize, MemError> {
+                if self.fail_at == Some(address) {
+                    return Err(MemError::Failed {
+                        address,
+                        message: "connection lost".to_string(),
+                    });
+                }
+                if self.hole.contains(&address) {
+                    return Err(MemError::Unreadable { address });
+                }
+                let end = (address + 3).min(self.hole.start.max(address + 1));
+                let len = buf.len().min((end - address) as usize);
+                let start = address as usize;
+                buf[..len].copy_from_slice(&self.memory[start..start + len]);
+                Ok(len)
+            }
+            fn regions(&self) -> Vec<MemoryRegion> {
+                vec![
+                    MemoryRegion::new(0..48, Permissions::new(true, false, true, false))
+                        .with_path("rom"),
+                    MemoryRegion::new(48..64, Permissions::new(false, true, false, false)),
+                ]
+            }
+            fn page_size(&self) -> u64 {
+                16
+            }
         }
-        let matches = pattern.find_matches(data);
-        assert_eq!(matches, vec![23]);
+
+        let pattern = Pattern::new("AA ?? AA").unwrap();
+        let mut memory = vec![0u8; 64];
+        for start in [1, 14, 30, 50] {
+            memory[start..start + 3].copy_from_slice(&[0xAA, start as u8, 0xAA]);
+        }
+        let mut debugger = Debugger {
+            memory: memory.clone(),
+            hole: 16..32,
+            fail_at: None,
+        };
+        let found = pattern.find_in_source(&mut debugger).unwrap();
+        // 14 spans into the hole, 30 is inside it and 50 isn't readable
+        let addresses: Vec<u64> = found.iter().map(|m| m.address()).collect();
+        assert_eq!(addresses, [1]);
+        assert_eq!(found[0].region().path(), Some("rom"));
+        assert_eq!(found[0].captures().get(0).unwrap().value, 1);
+
+        debugger.hole = 40..48;
+        let addresses: Vec<u64> = pattern
+            .find_in_source(&mut debugger)
+            .unwrap()
+            .iter()
+            .map(|m| m.address())
+            .collect();
+        assert_eq!(addresses, [1, 14, 30]);
+
+        debugger.fail_at = Some(12);
+        assert!(matches!(
+            pattern.find_in_source(&mut debugger),
+            Err(MemError::Failed { address: 12, .. })
+        ));
+
+        let found = pattern
+            .find_in_source(&mut SliceSource::new(0x1_0000_0000, &memory))
+            .unwrap();
+        assert_eq!(found.len(), 4);
+        assert_eq!(found[3].address(), 0x1_0000_0000 + 50);
+
+        let file = TempFile::new("memory-source", &memory);
+        let mut source = ReaderSource::new(std::fs::File::open(&file.0).unwrap()).unwrap();
+        let found = pattern.find_in_source(&mut source).unwrap();
+        let addresses: Vec<u64> = found.iter().map(|m| m.address()).collect();
+        assert_eq!(addresses, [1, 14, 30, 50]);
     }
 
     #[test]
-    fn ignore_some_bytes() {
-        let pattern = Pattern::new("00 __ 12 __ ??").unwrap();
-        let data: Vec<u8> = vec![0x00, 0xFF, 0x12, 0xAA, 23];
-        let matches = pattern.find_matches(data);
-        assert_eq!(matches, vec![23]);
+    fn scan_dir_nested_tree() {
+        let root = std::env::temp_dir().join(format!("binmatch-{}-scan-dir", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("a/deep")).unwrap();
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        std::fs::write(root.join("top.bin"), [0xCA, 0xFE]).unwrap();
+        std::fs::write(root.join("a/one.bin"), [0x00, 0xCA, 0xFE, 0xBA, 0xBE]).unwrap();
+        std::fs::write(root.join("a/deep/two.bin"), [0xBA, 0xBE, 0xCA, 0xFE]).unwrap();
+        std::fs::write(root.join("a/deep/notes.txt"), b"cafe").unwrap();
+        std::fs::write(root.join("b/large.bin"), vec![0xCA; 1000]).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, root.join("b/loop")).unwrap();
+
+        let set: PatternSet = "cafe: CA FE\nbabe: BA BE".parse().unwrap();
+        let relative = |options: &DirScanOptions| {
+            set.scan_dir(&root, options)
+                .into_iter()
+                .map(|(path, result)| {
+                    let matches = result.unwrap();
+                    let path = path
+                        .strip_prefix(&root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned();
+                    (path, matches.iter().map(|(i, m)| (*i, m.start())).collect())
+                })
+                .collect::<Vec<(String, Vec<(usize, usize)>)>>()
+        };
+
+        let expected = vec![
+            ("a/deep/notes.txt".to_string(), vec![]),
+            ("a/deep/two.bin".to_string(), vec![(1, 0), (0, 2)]),
+            ("a/one.bin".to_string(), vec![(0, 1), (1, 3)]),
+            ("top.bin".to_string(), vec![(0, 0)]),
+        ];
+        let options = DirScanOptions::new().max_file_size(100);
+        assert_eq!(relative(&options), expected);
+        // Same result in parallel and when following the symlink back to the root
+        let options = options.threads(3).follow_symlinks();
+        assert_eq!(relative(&options), expected);
+
+        let options = DirScanOptions::new().glob("*.bin").threads(0);
+        let found = relative(&options);
+        let paths: Vec<&str> = found.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["a/deep/two.bin", "a/one.bin", "b/large.bin", "top.bin"]
+        );
+
+        let missing = set.scan_dir(&root.join("missing"), &DirScanOptions::new());
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].1.is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn scan_to_channel_stops_when_receiver_drops() {
+        let pattern = Pattern::new("CC").unwrap();
+        let haystack = vec![0xCC; 1 << 20];
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sent = std::thread::scope(|scope| {
+            let scan = scope.spawn(|| pattern.scan_to_channel(&haystack, tx));
+            let received: Vec<usize> = rx.iter().take(2).map(|m| m.start()).collect();
+            assert_eq!(received, vec![0, 1]);
+            drop(rx);
+            scan.join().unwrap()
+        });
+        assert!((2..haystack.len()).contains(&sent));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        drop(rx);
+        assert_eq!(pattern.scan_to_channel(&haystack, tx), 0);
+        let (tx, rx) = std::sync::mpsc::channel();
+        assert_eq!(pattern.scan_to_channel(&[0x00, 0xCC, 0xCC], tx), 2);
+        let starts: Vec<usize> = rx.iter().map(|m| m.start()).collect();
+        assert_eq!(starts, vec![1, 2]);
+    }
+
+    #[test]
+    fn scan_reader_to_channel_stops_when_receiver_drops() {
+        let pattern = Pattern::new("CC ??").unwrap();
+        let haystack = vec![0xCC; 1 << 20];
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sent = std::thread::scope(|scope| {
+            let scan = scope.spawn(|| pattern.scan_reader_to_channel(haystack.as_slice(), tx));
+            let received: Vec<u64> = rx.iter().take(2).map(|m| m.offset()).collect();
+            assert_eq!(received, vec![0, 1]);
+            drop(rx);
+            scan.join().unwrap().unwrap()
+        });
+        assert!((2..haystack.len() - 1).contains(&sent));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let data: &[u8] = &[0xCC, 0x01, 0xCC, 0x02];
+        assert_eq!(pattern.scan_reader_to_channel(data, tx).unwrap(), 2);
+        let found: Vec<FileMatch> = rx.iter().collect();
+        assert_eq!(found, pattern.find_in_reader(data).unwrap());
+
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let reader = std::io::Read::chain([0xCC, 0x01].as_slice(), FailingReader);
+        assert!(pattern.scan_reader_to_channel(reader, tx).is_err());
+    }
+
+    #[test]
+    fn par_scan_to_channel_matches_find_matches() {
+        let set: PatternSet = "a: AA BB\nb: BB ?? AA\nc: AA".parse().unwrap();
+        let haystack: Vec<u8> = (0..10_000u32)
+            .map(|i| [0xAA, 0xBB, 0x00][(i * 7 % 5 % 3) as usize])
+            .collect();
+        let expected = set.find_matches(&haystack);
+        for (threads, chunk_len) in [(1, 1), (3, 7), (4, 1000), (8, 100_000)] {
+            let (tx, rx) = std::sync::mpsc::channel();
+            let sent = set.par_scan_to_channel_with(&haystack, tx, threads, chunk_len);
+            let mut found: Vec<(usize, Match)> = rx.iter().collect();
+            found.sort_by_key(|(index, m)| (m.start(), *index));
+            assert_eq!(sent, expected.len());
+            assert_eq!(found, expected);
+        }
+
+        let haystack = vec![0xAA; 1 << 20];
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sent = std::thread::scope(|scope| {
+            let scan = scope.spawn(|| set.par_scan_to_channel_with(&haystack, tx, 4, 4096));
+            assert_eq!(rx.iter().take(2).count(), 2);
+            drop(rx);
+            scan.join().unwrap()
+        });
+        assert!((2..haystack.len()).contains(&sent));
+    }
+
+    #[test]
+    fn stream_matcher_resumes_from_saved_state() {
+        let pattern = Pattern::new("DE ?? __ EF").unwrap();
+        let mut state = 7u32;
+        let mut data: Vec<u8> = (0..5000)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                [0xDE, 0xAD, 0xBE, 0xEF][(state >> 16) as usize % 4]
+            })
+            .collect();
+        data.extend_from_slice(&[0xDE, 0x01, 0x02, 0xEF]);
+
+        let mut matcher = pattern.stream_matcher();
+        let expected = matcher.push(&data);
+        assert!(!expected.is_empty());
+        for split in [0, 1, 2, 3, 1000, 2501, data.len() - 2, data.len()] {
+            let mut first = pattern.stream_matcher();
+            let mut found = first.push(&data[..split]);
+            let saved = first.save_state();
+            assert_eq!(saved.offset(), split as u64);
+            assert!(saved.carry().len() < pattern.len());
+
+            // Persisted and restored through the plain fields
+            let restored =
+                ScanState::new(saved.offset(), saved.carry().to_vec(), saved.match_count())
+                    .unwrap();
+            let mut second = StreamMatcher::resume(&pattern, restored);
+            found.extend(second.push(&data[saved.offset() as usize..]));
+            assert_eq!(found, expected);
+            assert_eq!(second.match_count(), expected.len() as u64);
+            assert_eq!(second.save_state(), matcher.save_state());
+        }
+        assert_eq!(ScanState::new(1, vec![0, 0], 0), None);
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn ffi_finds_matches_through_the_c_abi() {
+        use crate::ffi::*;
+        use std::ffi::{c_char, c_int};
+        use std::ptr;
+
+        // Called through function pointers, just like C would
+        let new: unsafe extern "C" fn(*const c_char, *mut *mut BinmatchPattern) -> c_int =
+            binmatch_pattern_new;
+        let find: unsafe extern "C" fn(
+            *const BinmatchPattern,
+            *const u8,
+            usize,
+            *mut *mut BinmatchMatch,
+            *mut usize,
+        ) -> c_int = binmatch_find;
+        let free_matches: unsafe extern "C" fn(*mut BinmatchMatch, usize) = binmatch_matches_free;
+        let free_pattern: unsafe extern "C" fn(*mut BinmatchPattern) = binmatch_pattern_free;
+
+        unsafe {
+            let mut pattern = ptr::null_mut();
+            assert_eq!(
+                new(c"AA ?? __ ??".as_ptr(), &mut pattern),
+                BinmatchStatus::Ok as c_int
+            );
+            let data = [0xAA, 0x01, 0x02, 0x03, 0xAA, 0x04, 0x05, 0x06];
+            let (mut matches, mut count) = (ptr::null_mut(), 0);
+            assert_eq!(
+                find(pattern, data.as_ptr(), data.len(), &mut matches, &mut count),
+                BinmatchStatus::Ok as c_int
+            );
+            assert_eq!(count, 2);
+            let found = std::slice::from_raw_parts(matches, count);
+            assert_eq!((found[1].start, found[1].len), (4, 4));
+            assert_eq!(
+                std::slice::from_raw_parts(found[1].captures, found[1].capture_count),
+                [0x04, 0x06]
+            );
+            free_matches(matches, count);
+
+            // Nothing matched, data may be null for an empty haystack
+            assert_eq!(find(pattern, ptr::null(), 0, &mut matches, &mut count), 0);
+            assert_eq!((matches, count), (ptr::null_mut(), 0));
+
+            // Patterns without placeholders have no capture array
+            let mut literal = ptr::null_mut();
+            assert_eq!(new(c"AA".as_ptr(), &mut literal), 0);
+            assert_eq!(
+                find(literal, data.as_ptr(), data.len(), &mut matches, &mut count),
+                0
+            );
+            assert!((*matches).captures.is_null());
+            free_matches(matches, count);
+            free_pattern(literal);
+
+            // Errors and null pointers
+            let mut failed = ptr::null_mut();
+            assert_eq!(
+                new(c"AAA".as_ptr(), &mut failed),
+                BinmatchStatus::PatternLengthError as c_int
+            );
+            assert_eq!(
+                new(c"AX".as_ptr(), &mut failed),
+                BinmatchStatus::PatternParseError as c_int
+            );
+            assert_eq!(
+                new(c"\xFF".as_ptr(), &mut failed),
+                BinmatchStatus::InvalidUtf8 as c_int
+            );
+            assert!(failed.is_null());
+            assert_eq!(
+                new(ptr::null(), &mut failed),
+                BinmatchStatus::NullPointer as c_int
+            );
+            assert_eq!(
+                new(c"AA".as_ptr(), ptr::null_mut()),
+                BinmatchStatus::NullPointer as c_int
+            );
+            assert_eq!(
+                find(ptr::null(), data.as_ptr(), 1, &mut matches, &mut count),
+                -1
+            );
+            assert_eq!(find(pattern, ptr::null(), 1, &mut matches, &mut count), -1);
+            assert_eq!(
+                find(pattern, data.as_ptr(), 1, ptr::null_mut(), &mut count),
+                -1
+            );
+            free_matches(ptr::null_mut(), 3);
+            free_pattern(ptr::null_mut());
+            free_pattern(pattern);
+        }
+    }
+
+    #[test]
+    fn bytes_regex_source_translates_every_element() {
+        let pattern = Pattern::new("00 0A ?? __ FF 2E").unwrap();
+        assert_eq!(
+            pattern.to_bytes_regex_source(),
+            r"(?s-u)\x00\x0A(.).\xFF\x2E"
+        );
+        assert_eq!(Pattern::new("").unwrap().to_bytes_regex_source(), "(?s-u)");
+    }
+
+    #[test]
+    fn bytes_regex_import_accepts_the_simple_subset() {
+        let import = |regex: &str| Pattern::from_bytes_regex_str(regex).unwrap().to_string();
+        assert_eq!(import(r"\x41\x42"), "41 42");
+        assert_eq!(import(r"MZ\.\n\r\t\\"), "4D 5A 2E 0A 0D 09 5C");
+        assert_eq!(import(r"\xE8.{4}\xC3"), "E8 ?? ?? ?? ?? C3");
+        assert_eq!(import(r"\x90{2,2}(.)"), "90 90 ??");
+        assert_eq!(
+            import(r"[\x41][A-A][^\x00-\xFE][\x00-\xFF][^\x00-\x40\x42-\xFF]"),
+            "41 41 FF ?? 41"
+        );
+        assert_eq!(import(r"(?s-u)(?s)\x00"), "00");
+        assert_eq!(import("é"), "C3 A9");
+        assert_eq!(import(""), "");
+
+        // The source written by to_bytes_regex_source finds the same offsets
+        let pattern = Pattern::new("E8 ?? __ 0A").unwrap();
+        let imported = Pattern::from_bytes_regex_str(&pattern.to_bytes_regex_source()).unwrap();
+        assert_eq!(imported.to_string(), "E8 ?? ?? 0A");
+        let data = [0xE8, 0x01, 0x02, 0x0A, 0xE8, 0xE8, 0x0A, 0x0A];
+        assert_eq!(
+            imported.find_match_starts(&data),
+            pattern.find_match_starts(&data)
+        );
+    }
+
+    #[test]
+    fn bytes_regex_import_rejects_real_regex_power() {
+        let unsupported = |construct, position| {
+            Err(RegexImportError::UnsupportedSyntax {
+                construct,
+                position,
+            })
+        };
+        let import = Pattern::from_bytes_regex_str;
+        assert_eq!(import(r"\x41|\x42"), unsupported("alternation", 4));
+        assert_eq!(import(r"^\x41"), unsupported("anchor", 0));
+        assert_eq!(import(r"\x41$"), unsupported("anchor", 4));
+        assert_eq!(import(r"(\x41)\1"), unsupported("group", 0));
+        assert_eq!(import(r"\x41\1"), unsupported("backreference", 4));
+        assert_eq!(import(r"\bA"), unsupported("word boundary", 0));
+        assert_eq!(import(r"A[\x20-\x7e]"), unsupported("character class", 1));
+        assert_eq!(import(r"\d"), unsupported("character class", 0));
+        assert_eq!(import(r"..{2,8}"), unsupported("variable repetition", 2));
+        assert_eq!(import(r".{2,}"), unsupported("variable repetition", 1));
+        assert_eq!(import(r"\x41+"), unsupported("repetition operator", 4));
+        assert_eq!(import(r"(?i)A"), unsupported("group", 0));
+
+        let malformed = |position| Err(RegexImportError::Malformed { position });
+        assert_eq!(import(r"[\x41"), malformed(0));
+        assert_eq!(import(r"\x4"), malformed(0));
+        assert_eq!(import(r"A{2"), malformed(1));
+        assert_eq!(import(r"{2}"), malformed(0));
+        assert_eq!(import(r"[\x42-\x41]"), malformed(0));
+        assert_eq!(import(r"(?s){2}"), malformed(0));
+        assert_eq!(import(r"\"), malformed(0));
+        assert_eq!(
+            import(r"..{2,8}").unwrap_err().to_string(),
+            "The variable repetition at position 2 can't be expressed as a Pattern"
+        );
+    }
+
+    #[test]
+    fn exports_round_trip_through_their_importers() {
+        let mut state = 0x1234_5678u32;
+        for _ in 0..200 {
+            let len = (state % 12) as usize;
+            let mut pattern = String::new();
+            for _ in 0..len {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                match (state >> 16) % 4 {
+                    0 => pattern.push_str("?? "),
+                    _ => pattern.push_str(&format!("{:02X} ", state >> 24)),
+                }
+            }
+            let pattern = Pattern::new(&pattern).unwrap();
+            assert_eq!(
+                Pattern::from_ida_string(&pattern.to_ida_string()).unwrap(),
+                pattern
+            );
+            let (bytes, mask) = pattern.to_code_style();
+            assert_eq!(Pattern::from_code_style(&bytes, &mask).unwrap(), pattern);
+            assert_eq!(
+                Pattern::from_yara_hex(&pattern.to_yara_hex()).unwrap(),
+                pattern
+            );
+        }
+
+        // Ignored bytes become wildcards, which are read back as placeholders
+        let pattern = Pattern::new("E8 __ ?? 90").unwrap();
+        let wildcards = Pattern::new("E8 ?? ?? 90").unwrap();
+        assert_eq!(
+            Pattern::from_ida_string(&pattern.to_ida_string()).unwrap(),
+            wildcards
+        );
+        assert_eq!(
+            Pattern::from_yara_hex(&pattern.to_yara_hex()).unwrap(),
+            wildcards
+        );
+        assert_eq!(Pattern::new("").unwrap().to_yara_hex(), "{ }");
+    }
+
+    #[test]
+    fn importers_reject_foreign_syntax() {
+        use BinmatchError::{LengthMismatchError, PatternLengthError, PatternParseError};
+
+        assert!(matches!(
+            Pattern::from_ida_string("E8 ? 9"),
+            Err(PatternLengthError { len: 1 })
+        ));
+        assert!(matches!(
+            Pattern::from_ida_string("E8 ?G"),
+            Err(PatternParseError {
+                ch: '?',
+                position: 3
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_ida_string("E8 *"),
+            Err(PatternParseError {
+                ch: '*',
+                position: 3
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_code_style(r"\xE8\x90", "x?x"),
+            Err(LengthMismatchError {
+                expected: 2,
+                found: 3
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_code_style(r"\xE8\x90", "x."),
+            Err(PatternParseError {
+                ch: '.',
+                position: 1
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_code_style(r"\xE8\xZ0", "xx"),
+            Err(PatternParseError {
+                ch: 'Z',
+                position: 6
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_code_style(r"\xE8\x9", "xx"),
+            Err(PatternLengthError { len: 1 })
+        ));
+        assert!(matches!(
+            Pattern::from_code_style("E8", "x"),
+            Err(PatternParseError {
+                ch: 'E',
+                position: 0
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_yara_hex("E8 ??"),
+            Err(PatternParseError {
+                ch: 'E',
+                position: 0
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_yara_hex("{ E8 ?? "),
+            Err(PatternParseError {
+                ch: '?',
+                position: 6
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_yara_hex("{ E8 [2-4] 90 }"),
+            Err(PatternParseError {
+                ch: '[',
+                position: 5
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_yara_hex("{ E8 ?0 }"),
+            Err(PatternParseError {
+                ch: '?',
+                position: 5
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_yara_hex("{ E8 ( 90 | 91 ) }"),
+            Err(PatternParseError {
+                ch: '(',
+                position: 5
+            })
+        ));
+        assert!(matches!(
+            Pattern::from_yara_hex("{ E8 9 }"),
+            Err(PatternLengthError { len: 3 })
+        ));
+        assert_eq!(
+            Pattern::from_yara_hex(" {E890??}\n").unwrap().to_string(),
+            "E8 90 ??"
+        );
+    }
+
+    #[test]
+    fn pattern_bytes_round_trip() {
+        let mut state = 0xC0FFEEu32;
+        for len in (0..40).chain([127, 128, 300, 20_000]) {
+            let mut pattern = String::new();
+            for _ in 0..len {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                match (state >> 16) % 3 {
+                    0 => pattern.push_str("??"),
+                    1 => pattern.push_str("__"),
+                    _ => pattern.push_str(&format!("{:02X}", state >> 24)),
+                }
+            }
+            let pattern = Pattern::new(&pattern).unwrap();
+            let bytes = pattern.to_bytes();
+            assert_eq!(Pattern::from_bytes(&bytes).unwrap(), pattern);
+            // Every prefix is rejected cleanly
+            for cut in [0, 3, 5, bytes.len() / 2, bytes.len().saturating_sub(1)] {
+                if cut < bytes.len() {
+                    assert!(Pattern::from_bytes(&bytes[..cut]).is_err());
+                }
+            }
+        }
+        // The length 128 takes a two byte varint
+        assert_eq!(
+            &Pattern::new(&"??".repeat(128)).unwrap().to_bytes()[5..7],
+            &[0x80, 0x01]
+        );
+    }
+
+    #[test]
+    fn pattern_bytes_reject_invalid_input() {
+        let bytes = Pattern::new("AA ??").unwrap().to_bytes();
+        assert_eq!(
+            Pattern::from_bytes(b"BMPS\x01\x00"),
+            Err(PatternDecodeError::BadMagic)
+        );
+        assert_eq!(
+            Pattern::from_bytes(b"BMPT"),
+            Err(PatternDecodeError::Truncated)
+        );
+        assert_eq!(
+            Pattern::from_bytes(b"BMPT\x02\x00"),
+            Err(PatternDecodeError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            Pattern::from_bytes(b"BMPT\x01\x01\x07"),
+            Err(PatternDecodeError::InvalidElement(7))
+        );
+        assert_eq!(
+            Pattern::from_bytes(b"BMPT\x01\x02\x00"),
+            Err(PatternDecodeError::Truncated)
+        );
+        assert_eq!(
+            Pattern::from_bytes(b"BMPT\x01\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x01"),
+            Err(PatternDecodeError::InvalidLength)
+        );
+        // A huge length isn't trusted for allocations
+        assert_eq!(
+            Pattern::from_bytes(b"BMPT\x01\xFF\xFF\xFF\xFF\x0F\x01"),
+            Err(PatternDecodeError::Truncated)
+        );
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Pattern::from_bytes(&trailing),
+            Err(PatternDecodeError::TrailingData(1))
+        );
+        assert_eq!(
+            Pattern::from_bytes(b"BMPT\x01\x00").unwrap(),
+            Pattern::new("").unwrap()
+        );
+    }
+
+    #[test]
+    fn find_matches_in_hex_decodes_the_haystack() {
+        let pattern = Pattern::new("DE AD ??").unwrap();
+        let starts = |hex| {
+            pattern.find_matches_in_hex(hex).map(|found| {
+                found
+                    .iter()
+                    .map(|m| (m.start(), m.captures().get(0).unwrap().value))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(starts("00deadbeef"), Ok(vec![(1, 0xBE)]));
+        assert_eq!(
+            starts("00 DE\tAD\r\n01 dead02"),
+            Ok(vec![(1, 0x01), (4, 0x02)])
+        );
+        assert_eq!(starts("0x00 0XDE 0xad 0x0f"), Ok(vec![(1, 0x0F)]));
+        assert_eq!(starts("0x00DEAD0F"), Ok(vec![(1, 0x0F)]));
+        assert_eq!(starts(""), Ok(vec![]));
+        // A byte of 0 followed by a prefix
+        assert_eq!(starts("000xdead00"), Ok(vec![(1, 0x00)]));
+
+        assert_eq!(
+            starts("de ad zz"),
+            Err(HexError::InvalidCharacter {
+                character: 'z',
+                position: 6
+            })
+        );
+        assert_eq!(starts("dea"), Err(HexError::MissingDigit { position: 2 }));
+        assert_eq!(
+            starts("de a d"),
+            Err(HexError::MissingDigit { position: 3 })
+        );
+        assert_eq!(
+            starts("déad").unwrap_err().to_string(),
+            "Invalid character 'é' at position 1"
+        );
+    }
+
+    #[test]
+    fn find_matches_in_base64_decodes_the_haystack() {
+        let pattern = Pattern::new("FB FF ??").unwrap();
+        let starts = |base64| {
+            pattern
+                .find_matches_in_base64(base64)
+                .map(|found| found.iter().map(|m| m.start()).collect::<Vec<_>>())
+        };
+        // 00 FB FF BF and 00 FB FF BF FB FF 00
+        assert_eq!(starts("APv/vw=="), Ok(vec![1]));
+        assert_eq!(starts("APv_vw"), Ok(vec![1]));
+        assert_eq!(starts("APv/v/v/AA=="), Ok(vec![1, 4]));
+        assert_eq!(starts("APv_v_v_AA"), Ok(vec![1, 4]));
+        assert_eq!(starts(""), Ok(vec![]));
+
+        assert_eq!(
+            starts("APv/v w=="),
+            Err(Base64Error::InvalidCharacter {
+                character: ' ',
+                position: 5
+            })
+        );
+        assert_eq!(
+            starts("AP=v/vw="),
+            Err(Base64Error::InvalidPadding { position: 2 })
+        );
+        assert_eq!(
+            starts("APv/vw="),
+            Err(Base64Error::InvalidPadding { position: 6 })
+        );
+        assert_eq!(
+            starts("APv/v==="),
+            Err(Base64Error::InvalidPadding { position: 5 })
+        );
+        assert_eq!(
+            starts("APv/v"),
+            Err(Base64Error::InvalidLength { position: 4 })
+        );
+    }
+
+    #[test]
+    fn stream_scanner_agrees_with_find_with() {
+        fn check<const N: usize>(state: &mut u64) {
+            let elements: Vec<String> = (0..N)
+                .map(|_| match next_random(state) % 6 {
+                    0 => "??".to_string(),
+                    1 => "__".to_string(),
+                    v => format!("{:02X}", v - 2),
+                })
+                .collect();
+            let fixed = FixedPattern::<N>::new(&elements.join(" ")).unwrap();
+            let pattern = Pattern::from(&fixed);
+            assert_eq!(pattern, Pattern::new(&elements.join(" ")).unwrap());
+
+            let data = random_data(state, 300);
+            let mut scanner = fixed.stream_scanner();
+            let found: Vec<u64> = data.iter().filter_map(|&byte| scanner.push(byte)).collect();
+            let expected: Vec<u64> = pattern
+                .find_with(&data, &MatchOptions::new())
+                .iter()
+                .map(|m| m.start() as u64)
+                .collect();
+            assert_eq!(found, expected);
+        }
+
+        let mut state = 0x5eed_1234;
+        for _ in 0..50 {
+            check::<1>(&mut state);
+            check::<2>(&mut state);
+            check::<3>(&mut state);
+            check::<5>(&mut state);
+            check::<8>(&mut state);
+        }
+    }
+
+    #[test]
+    fn stream_scanner_positions() {
+        assert!(matches!(
+            FixedPattern::<2>::new("AA"),
+            Err(BinmatchError::LengthMismatchError {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            FixedPattern::<2>::new("AA B"),
+            Err(BinmatchError::PatternLengthError { len: 3 })
+        ));
+        assert!(matches!(
+            FixedPattern::<2>::new("AA ?0"),
+            Err(BinmatchError::MixedToken {
+                token: ['?', '0'],
+                position: 3
+            })
+        ));
+        assert!(FixedPattern::<0>::new("").is_err());
+
+        let pattern = FixedPattern::<2>::new("aa ??").unwrap();
+        assert!(pattern.matches(&[0xAA, 0x00]));
+        let mut scanner = StreamScanner::with_position(pattern, u64::MAX - 1);
+        assert_eq!(scanner.push(0xAA), None);
+        assert_eq!(scanner.push(0x01), Some(u64::MAX - 1));
+        assert_eq!(scanner.push(0xAA), None);
+        assert_eq!(scanner.position(), 1);
+        assert_eq!(scanner.push(0x02), Some(0));
+
+        // Matches don't span a reset
+        scanner.push(0xAA);
+        scanner.reset();
+        let mut found = Vec::new();
+        assert_eq!(
+            scanner.push_slice(&[0x00, 0xAA, 0xAA, 0xAA], |o| found.push(o)),
+            2
+        );
+        assert_eq!(found, vec![4, 5]);
+    }
+
+    #[test]
+    fn panic_free_variants() {
+        let pattern = Pattern::try_new("00 ?? 00 ??").unwrap();
+        assert_eq!(
+            pattern.try_match_chunk(&[0, 42, 0, 13]).unwrap(),
+            (vec![(42, 1), (13, 3)], true)
+        );
+        assert!(matches!(
+            pattern.try_match_chunk(&[0, 42, 0]),
+            Err(BinmatchError::LengthMismatchError {
+                expected: 4,
+                found: 3
+            })
+        ));
+        assert!(matches!(
+            Pattern::try_new("0?"),
+            Err(BinmatchError::MixedToken {
+                token: ['0', '?'],
+                position: 0
+            })
+        ));
+
+        let data = [0x00, 0x11, 0x00];
+        let mut zero = Pattern::try_new("00").unwrap().match_bitmap(&data);
+        let one = Pattern::try_new("11").unwrap().match_bitmap(&data);
+        zero.try_or_assign(&one).unwrap();
+        assert_eq!(zero.count_ones(), 3);
+        let short = Pattern::try_new("11").unwrap().match_bitmap(&data[..2]);
+        assert!(zero.try_and_assign(&short).is_err());
+        assert_eq!(zero.count_ones(), 3);
+    }
+
+    #[test]
+    fn dfa_agrees_with_find_match_starts() {
+        let mut state = 0xdfa_5eed;
+        for _ in 0..300 {
+            let pattern = Pattern::new(&random_pattern(&mut state, 8)).unwrap();
+            let dfa = pattern.compile_dfa().unwrap();
+            assert!(dfa.class_count() <= 5);
+            let data = random_data(&mut state, 400);
+            assert_eq!(
+                dfa.find_match_starts(&data),
+                pattern.find_match_starts(&data)
+            );
+        }
+
+        // Every byte value as a literal leaves no class for the other bytes
+        let all: Vec<String> = (0..=255).map(|byte| format!("{byte:02X}")).collect();
+        let pattern = Pattern::new(&all.join(" ")).unwrap();
+        let dfa = pattern.compile_dfa().unwrap();
+        assert_eq!(dfa.class_count(), 256);
+        let data: Vec<u8> = (0..=255).chain(0..=255).collect();
+        assert_eq!(dfa.find_match_starts(&data), vec![0, 256]);
+    }
+
+    #[test]
+    fn dfa_state_limit() {
+        let pattern = Pattern::new("00 ?? ?? ?? 01").unwrap();
+        // Remembers which of the last 4 bytes were 00, plus 8 accepting states after a 01
+        assert_eq!(pattern.compile_dfa().unwrap().state_count(), 24);
+        assert!(pattern.compile_dfa_with_limit(23).is_none());
+        assert!(pattern.compile_dfa_with_limit(24).is_some());
+
+        assert!(Pattern::new("")
+            .unwrap()
+            .compile_dfa()
+            .unwrap()
+            .find_match_starts(&[0])
+            .is_empty());
+        let dfa = Pattern::new("AA BB").unwrap().compile_dfa().unwrap();
+        let mut state = dfa.start();
+        assert_eq!(dfa.advance(&mut state, 0xAA), None);
+        let found = dfa.advance(&mut state, 0xBB).unwrap();
+        assert_eq!((found.start(), found.end()), (0, 2));
+        assert_eq!(state.position(), 2);
+    }
+
+    #[test]
+    fn specificity_and_expected_matches() {
+        let pattern = Pattern::new("00 ?? FF").unwrap();
+        assert_eq!(pattern.specificity(), 16.0);
+        assert_eq!(pattern.expected_matches(1 << 20), 15.999969482421875);
+        assert_eq!(pattern.expected_matches(3), 1.0 / 65536.0);
+        assert_eq!(pattern.expected_matches(2), 0.0);
+
+        let wildcards = Pattern::new("?? __").unwrap();
+        assert_eq!(wildcards.specificity(), 0.0);
+        assert_eq!(wildcards.expected_matches(10), 9.0);
+        assert_eq!(Pattern::new("").unwrap().expected_matches(3), 0.0);
+
+        // Agrees with the exact average over all inputs of two bytes
+        let pattern = Pattern::new("AB ??").unwrap();
+        let total: usize = (0..=0xFFFF_u16)
+            .map(|data| pattern.find_match_starts(&data.to_le_bytes()).len())
+            .sum();
+        assert_eq!(pattern.expected_matches(2), total as f64 / 65536.0);
+    }
+
+    #[test]
+    fn lint_warnings() {
+        let lint = |pattern: &str| Pattern::new(pattern).unwrap().lint();
+        assert!(lint("48 8B 05 C3 ?? ?? ?? ??").is_empty());
+
+        assert_eq!(lint("?? __ ??"), vec![LintWarning::AllWildcards]);
+        assert!(!lint("?? 00 ??").contains(&LintWarning::AllWildcards));
+
+        assert!(lint("__ __ 48 8B 05 C3").contains(&LintWarning::LeadingIgnores(2)));
+        assert!(lint("?? 48 8B 05 C3").is_empty());
+
+        assert!(lint("48 8B 05 C3 __").contains(&LintWarning::TrailingIgnores(1)));
+        assert!(lint("48 8B 05 C3 ??").is_empty());
+
+        assert!(lint("C3").contains(&LintWarning::TrivialLength));
+        assert!(!lint("C3 CC").contains(&LintWarning::TrivialLength));
+
+        assert_eq!(
+            lint("48 8B 05"),
+            vec![LintWarning::LowSpecificity { bits: 24.0 }]
+        );
+        assert!(lint("48 8B 05 C3").is_empty());
+
+        assert_eq!(
+            lint("48 ?? 8B ?? 05 ?? C3"),
+            vec![LintWarning::ShortLiteralRuns { longest: 1 }]
+        );
+        assert!(lint("48 8B ?? 05 ?? C3").is_empty());
+
+        let pattern = Pattern::new("48 8B 05 C3").unwrap();
+        assert!(pattern.lint_for_len(4).is_empty());
+        assert_eq!(
+            pattern.lint_for_len(3),
+            vec![LintWarning::LongerThanHaystack {
+                len: 4,
+                haystack_len: 3
+            }]
+        );
+        assert_eq!(
+            LintWarning::LowSpecificity { bits: 24.0 }.to_string(),
+            "The pattern only has 24 bits of specificity, at least 32 are recommended"
+        );
+    }
+
+    #[test]
+    fn pattern_compatibility() {
+        let p = |pattern: &str| Pattern::new(pattern).unwrap();
+        assert!(p("00 ?? 02").is_compatible_with(&p("00 01 __")));
+        assert!(!p("00 ?? 02").is_compatible_with(&p("00 01 03")));
+        assert!(p("?? __").is_compatible_with(&p("FF FF")));
+        // Only the shared prefix is compared
+        assert!(p("00 01").is_compatible_with(&p("00 ?? FF FF")));
+        assert!(!p("00 01").is_compatible_with(&p("01")));
+        assert!(p("").is_compatible_with(&p("00")));
+
+        let both = p("00 ?? __ __").intersection(&p("__ __ ?? 03")).unwrap();
+        assert_eq!(both, p("00 ?? ?? 03"));
+        let data = [0x00, 0x01, 0x02, 0x03, 0x00, 0x05, 0x06, 0x03];
+        assert_eq!(both.find_match_starts(&data), vec![0, 4]);
+        assert_eq!(p("00 ?? 02").intersection(&p("00 01 03")), None);
+        assert_eq!(p("00 ??").intersection(&p("00 ?? ??")), None);
+    }
+
+    #[test]
+    fn pattern_containment_and_deduplicate() {
+        let p = |pattern: &str| Pattern::new(pattern).unwrap();
+        assert!(p("00 01").is_subset_of(&p("00 ??")));
+        assert!(p("00 ??").is_subset_of(&p("00 __")));
+        assert!(p("00 __").is_subset_of(&p("00 ??")));
+        assert!(p("00 01").is_subset_of(&p("00 01")));
+        assert!(!p("00 ??").is_subset_of(&p("00 01")));
+        assert!(!p("00 01").is_subset_of(&p("00 02")));
+        assert!(!p("00 01").is_subset_of(&p("00")));
+
+        let mut set: PatternSet = [
+            ("exact", p("00 01 02")),
+            ("middle", p("00 ?? 02")),
+            ("general", p("00 ?? ??")),
+            ("other", p("FF ??")),
+            ("same", p("00 __ __")),
+            ("other_copy", p("FF ??")),
+        ]
+        .into_iter()
+        .collect();
+        set.set_priority(3, 7);
+        assert_eq!(set.deduplicate(), vec![(0, 2), (1, 2), (4, 2), (5, 3)]);
+        assert_eq!(set.len(), 2);
+        assert_eq!((set.name(0), set.name(1)), (Some("general"), Some("other")));
+        assert_eq!(set.priority(1), Some(7));
+        assert_eq!(set.find_matches(&[0x00, 0x05, 0x06, 0xFF, 0x01]).len(), 2);
+        assert!(set.deduplicate().is_empty());
+    }
+
+    #[test]
+    fn generalize_samples() {
+        let samples: [&[u8]; 3] = [
+            &[0xCC, 0x55, 0x48, 0x89, 0xE5, 0x10, 0xC3, 0x01],
+            &[0xCC, 0x55, 0x48, 0x89, 0xE5, 0x20, 0xC3, 0x02],
+            &[0x90, 0x55, 0x48, 0x89, 0xE5, 0x30, 0xC3, 0x03],
+        ];
+        let pattern = Pattern::generalize(&samples).unwrap();
+        assert_eq!(pattern, Pattern::new("?? 55 48 89 E5 ?? C3 ??").unwrap());
+        for sample in samples {
+            assert!(pattern.match_at(sample, 0).is_some());
+        }
+
+        let options = GeneralizeOptions::new().ignore_differences();
+        let pattern = Pattern::generalize_with(&samples, &options).unwrap();
+        assert_eq!(pattern, Pattern::new("__ 55 48 89 E5 __ C3 __").unwrap());
+        let pattern = Pattern::generalize_with(&samples, &options.trim()).unwrap();
+        assert_eq!(pattern, Pattern::new("55 48 89 E5 __ C3").unwrap());
+
+        let single = Pattern::generalize(&samples[..1]).unwrap();
+        assert_eq!(single.to_string(), "CC 55 48 89 E5 10 C3 01");
+
+        assert_eq!(Pattern::generalize(&[]), Err(GeneralizeError::NoSamples));
+        assert_eq!(
+            Pattern::generalize(&[&[0x00, 0x01], &[0x00, 0x01], &[0x00]]),
+            Err(GeneralizeError::LengthMismatch {
+                index: 2,
+                expected: 2,
+                found: 1
+            })
+        );
+        let options = GeneralizeOptions::new().trim();
+        assert_eq!(
+            Pattern::generalize_with(&[&[0x00, 0x01], &[0x01, 0x00]], &options),
+            Err(GeneralizeError::NothingInCommon)
+        );
+    }
+
+    #[test]
+    fn unique_signature_extends_past_near_duplicates() {
+        let mut binary = vec![0x90; 64];
+        let function = [0x48, 0x8B, 0x05, 0x11, 0x22, 0x33, 0x44, 0xC3];
+        binary[8..16].copy_from_slice(&function);
+        binary[32..40].copy_from_slice(&function);
+        // The near duplicate only differs in its last displacement byte
+        binary[38] = 0x55;
+
+        let options = SigGenOptions::new();
+        let signature = Pattern::unique_signature(&binary, 8, &options).unwrap();
+        assert_eq!(signature.to_string(), "48 8B 05 11 22 33 44");
+        assert_eq!(signature.find_match_starts(&binary), vec![8]);
+
+        // With the displacements wildcarded both copies look the same, only the padding after
+        // them differs once the copy at 8 reaches the one at 32
+        let relocated = options.clone().wildcard(11..15).wildcard(35..39);
+        assert_eq!(
+            Pattern::unique_signature(&binary, 8, &relocated.clone().max_len(16)),
+            Err(SigGenError::NotUnique { max_len: 16 })
+        );
+        let signature = Pattern::unique_signature(&binary, 32, &relocated).unwrap();
+        assert_eq!(signature.len(), 25);
+        assert!(signature
+            .to_string()
+            .starts_with("48 8B 05 ?? ?? ?? ?? C3 90"));
+        assert_eq!(signature.find_match_starts(&binary), vec![32]);
+
+        assert_eq!(
+            Pattern::unique_signature(&binary, 8, &options.clone().max_len(6)),
+            Err(SigGenError::NotUnique { max_len: 6 })
+        );
+        assert_eq!(
+            Pattern::unique_signature(&binary, 64, &options),
+            Err(SigGenError::OutOfBounds { at: 64, len: 64 })
+        );
+    }
+
+    #[test]
+    fn generated_data_matches() {
+        for seed in 1..=500 {
+            let mut state = seed;
+            let pattern = Pattern::new(&random_pattern(&mut state, 12)).unwrap();
+            let mut next_byte = || next_random(&mut state) as u8;
+            let data = pattern.generate_matching(&mut next_byte);
+            assert_eq!(data.len(), pattern.len());
+            assert!(pattern.match_at(&data, 0).is_some());
+
+            let mut buf = vec![0xEE; 32];
+            let at = (seed % 8) as usize;
+            let planted = pattern
+                .embed_match_in(&mut buf, at, &mut next_byte)
+                .unwrap();
+            assert_eq!(planted.start(), at);
+            assert!(pattern.find_match_starts(&buf).contains(&at));
+            assert!(buf[..at].iter().all(|&byte| byte == 0xEE));
+            assert!(buf[at + pattern.len()..].iter().all(|&byte| byte == 0xEE));
+        }
+
+        let pattern = Pattern::new("00 ??").unwrap();
+        let mut buf = [0xEE; 4];
+        assert!(pattern.embed_match_in(&mut buf, 3, || 0).is_none());
+        assert!(pattern.embed_match_in(&mut buf, usize::MAX, || 0).is_none());
+        assert_eq!(buf, [0xEE; 4]);
+    }
+
+    #[test]
+    fn source_spans_are_ignored_by_equality() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |pattern: &Pattern| {
+            let mut hasher = DefaultHasher::new();
+            pattern.hash(&mut hasher);
+            hasher.finish()
+        };
+        let spaced = Pattern::new(" 00  ?? ff ").unwrap();
+        let compact = Pattern::new("00??FF").unwrap();
+        assert_eq!(spaced, compact);
+        assert_eq!(hash(&spaced), hash(&compact));
+        assert_eq!(spaced.cmp(&compact), std::cmp::Ordering::Equal);
+        assert_ne!(spaced.source(), compact.source());
+
+        assert_eq!(spaced.source(), Some(" 00  ?? ff "));
+        let spans: Vec<_> = (0..3).map(|i| spaced.element_span(i).unwrap()).collect();
+        assert_eq!(spans, vec![1..3, 5..7, 8..10]);
+        assert_eq!(&spaced.source().unwrap()[spans[2].clone()], "ff");
+        // Bytes split by a space still form one element
+        assert_eq!(Pattern::new("0 0").unwrap().element_span(0), Some(0..3));
+        assert_eq!(
+            Pattern::from_bytes_regex_str("\\x00").unwrap().source(),
+            None
+        );
+
+        let pattern = Pattern::new("48 8B 05 C3 __ __").unwrap();
+        let warning = pattern.lint()[0];
+        assert_eq!(warning, LintWarning::TrailingIgnores(2));
+        assert_eq!(pattern.warning_span(&warning), Some(12..17));
+        let empty = Pattern::new("").unwrap();
+        assert_eq!(empty.warning_span(&LintWarning::TrivialLength), None);
+    }
+
+    #[test]
+    fn parse_all_errors_reports_every_problem() {
+        // An invalid character, a lone digit and a mixed token, each reported at its byte offset
+        let errors = Pattern::parse_all_errors("48 8B 0X ?? 5 E8 ?1 90").unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::InvalidCharacter {
+                    character: 'X',
+                    position: 7,
+                },
+                ParseError::LoneNibble { position: 12 },
+                ParseError::MixedToken {
+                    token: ['?', '1'],
+                    position: 17,
+                },
+            ]
+        );
+        assert_eq!(
+            errors[2].to_string(),
+            "mixed token \"?1\" at position 17, `?` and `_` can only be paired with themselves"
+        );
+        // Positions are byte offsets, so they stay correct after multi-byte characters
+        assert_eq!(
+            Pattern::parse_all_errors("ä 00 G0").unwrap_err(),
+            vec![
+                ParseError::InvalidCharacter {
+                    character: 'ä',
+                    position: 0,
+                },
+                ParseError::InvalidCharacter {
+                    character: 'G',
+                    position: 6,
+                },
+            ]
+        );
+
+        // Whatever try_new accepts is accepted as well, with the same elements and spans
+        for pattern in ["48 8B ?? __", "4 8 8B", "e8??__ 00"] {
+            let all = Pattern::parse_all_errors(pattern).unwrap();
+            let first = Pattern::try_new(pattern).unwrap();
+            assert_eq!(all, first);
+            assert_eq!(all.element_span(1), first.element_span(1));
+        }
+    }
+
+    #[test]
+    fn parse_error_positions() {
+        // Positions index the original input, spaces and multi-byte characters included
+        let error = Pattern::try_new("48 8B 05 ?? ?? G0").unwrap_err();
+        assert!(matches!(
+            error,
+            BinmatchError::PatternParseError {
+                ch: 'G',
+                position: 15
+            }
+        ));
+        assert_eq!(error.to_string(), "invalid character 'G' at position 15");
+        assert!(matches!(
+            Pattern::try_new("é0"),
+            Err(BinmatchError::PatternParseError {
+                ch: 'é',
+                position: 0
+            })
+        ));
+        assert!(matches!(
+            Pattern::try_new("00 é0 11"),
+            Err(BinmatchError::PatternParseError { position: 3, .. })
+        ));
+        assert!(matches!(
+            FixedPattern::<3>::new("00  11 X0"),
+            Err(BinmatchError::PatternParseError {
+                ch: 'X',
+                position: 7
+            })
+        ));
+    }
+
+    #[test]
+    fn mixed_tokens() {
+        for (pattern, token, position) in [
+            ("00 ?A", ['?', 'A'], 3),
+            ("a? 00", ['A', '?'], 0),
+            ("00 _0 11", ['_', '0'], 3),
+            ("000_", ['0', '_'], 2),
+        ] {
+            let error = BinmatchError::MixedToken { token, position };
+            assert_eq!(Pattern::new(pattern), Err(error.clone()), "{pattern}");
+            assert_eq!(Pattern::try_new(pattern), Err(error), "{pattern}");
+        }
+        assert_eq!(
+            Pattern::try_new("?A 00").unwrap_err().to_string(),
+            "mixed token \"?A\" at position 0, `?` and `_` can only be paired with themselves"
+        );
+        assert!(matches!(
+            FixedPattern::<2>::new("00 0_"),
+            Err(BinmatchError::MixedToken {
+                token: ['0', '_'],
+                position: 3
+            })
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "MixedToken")]
+    fn new_unchecked_rejects_mixed_tokens() {
+        Pattern::new_unchecked("?A 00");
+    }
+
+    #[test]
+    fn every_patch_api_can_return_a_report() {
+        let pattern = Pattern::new("74 ??").unwrap();
+        let template = PatchTemplate::literal(&[0x90, 0x90]);
+        let original = vec![0x74, 0x01, 0x74, 0x74, 0x02, 0x90, 0x74, 0x03];
+        let options = PatchOptions::new();
+
+        let mut expected = original.clone();
+        let report = pattern.patch(&mut expected, &template, &options).unwrap();
+        assert_eq!(report.len(), 3);
+
+        let (patched, with) = pattern
+            .replace_matches_with(&original, &[0x90, 0x90], &options)
+            .unwrap();
+        assert_eq!((&patched, &with), (&expected, &report));
+        assert_eq!(
+            pattern.replace_matches(&original, &[0x90, 0x90]).unwrap(),
+            expected
+        );
+        let (patched, with) = pattern
+            .replace_matches_cow_with(&original, &[0x90, 0x90], &options)
+            .unwrap();
+        assert_eq!((&*patched, &with), (expected.as_slice(), &report));
+        let (patched, with) = pattern
+            .replace_with_template_with(&original, &template, &options)
+            .unwrap();
+        assert_eq!((&patched, &with), (&expected, &report));
+        let mut patched = original.clone();
+        let with = pattern
+            .patch_in_place_with(&mut patched, &[0x90, 0x90], &options)
+            .unwrap();
+        assert_eq!((&patched, &with), (&expected, &report));
+        let mut patched = original.clone();
+        let with = pattern.fill_matches_with(&mut patched, 0x90, &options);
+        assert_eq!((&patched, &with), (&expected, &report));
+
+        // The report restores the original buffer
+        assert_eq!(with.revert(&mut patched), Ok(3));
+        assert_eq!(patched, original);
+
+        // Without a match nothing is copied and the report is empty
+        let (unchanged, with) = pattern
+            .replace_matches_cow_with(&[0x90; 4], &[0x90, 0x90], &options)
+            .unwrap();
+        assert!(matches!(unchanged, Cow::Borrowed(_)));
+        assert!(with.is_empty() && !with.limit_reached());
+    }
+
+    #[test]
+    fn max_patches_limits_every_patch_api() {
+        let pattern = Pattern::new("AA").unwrap();
+        let data = vec![0xAA; 5];
+        let limited = vec![0x00, 0x00, 0xAA, 0xAA, 0xAA];
+        let options = PatchOptions::new().max_patches(2);
+        let check = |patched: &[u8], report: &PatchReport| {
+            assert_eq!(patched, limited.as_slice());
+            assert_eq!(report.len(), 2);
+            assert!(report.limit_reached());
+        };
+
+        let (patched, report) = pattern
+            .replace_matches_with(&data, &[0x00], &options)
+            .unwrap();
+        check(&patched, &report);
+        let (patched, report) = pattern
+            .replace_matches_cow_with(&data, &[0x00], &options)
+            .unwrap();
+        check(&patched, &report);
+        let (patched, report) = pattern
+            .replace_with_template_with(&data, &PatchTemplate::literal(&[0x00]), &options)
+            .unwrap();
+        check(&patched, &report);
+        let mut patched = data.clone();
+        let report = pattern
+            .patch_in_place_with(&mut patched, &[0x00], &options)
+            .unwrap();
+        check(&patched, &report);
+        let mut patched = data.clone();
+        let report = pattern.fill_matches_with(&mut patched, 0x00, &options);
+        check(&patched, &report);
+
+        // A limit of 0 patches nothing, but there was something left to patch
+        let (patched, report) = pattern
+            .replace_matches_cow_with(&data, &[0x00], &PatchOptions::new().max_patches(0))
+            .unwrap();
+        assert_eq!(*patched, *data);
+        assert!(report.is_empty() && report.limit_reached());
+    }
+
+    #[test]
+    fn empty_pattern_never_matches() {
+        let empty = Pattern::new("").unwrap();
+        let data = [0x00, 0x01, 0x02];
+        let starts = empty.find_match_starts(&data);
+        assert!(starts.is_empty());
+        let starts_with = |options: &MatchOptions| -> Vec<usize> {
+            empty
+                .find_with(&data, options)
+                .iter()
+                .map(Match::start)
+                .collect()
+        };
+        assert_eq!(starts_with(&MatchOptions::new()), starts);
+        assert_eq!(starts_with(&MatchOptions::new().non_overlapping()), starts);
+        assert_eq!(starts_with(&MatchOptions::new().swap_bytes(2)), starts);
+        assert_eq!(empty.match_bitmap(&data).count_ones(), 0);
+        assert_eq!(empty.find_last(&data), None);
+        assert_eq!(empty.match_at_many(&data, &[0, 3]), vec![None, None]);
+        assert!(empty.find_with_stats(&data).0.is_empty());
+        assert!(empty
+            .compile_dfa()
+            .unwrap()
+            .find_match_starts(&data)
+            .is_empty());
+    }
+
+    #[test]
+    fn empty_pattern_patches_nothing() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let empty = Pattern::new("").unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let options = PatchOptions::new().after_patch(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let mut data = vec![0x00, 0x01, 0x02];
+        let report = empty
+            .patch(&mut data, &PatchTemplate::literal(&[]), &options)
+            .unwrap();
+        assert!(report.is_empty() && !report.limit_reached());
+        assert_eq!(empty.fill_matches(&mut data, 0xCC), 0);
+        assert_eq!(empty.patch_in_place(&mut data, &[]).unwrap(), 0);
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+        assert_eq!(data, vec![0x00, 0x01, 0x02]);
+
+        let set = PatternSet::new(vec![empty, Pattern::new("01").unwrap()]);
+        assert_eq!(set.find_matches(&data), set.find_matches_naive(&data));
+        let found = set.find_matches(&data);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 1);
     }
 }
 
 #[cfg(not(feature = "std"))]
+#[allow(deprecated)]
 mod no_std_tests {
-    use crate::Pattern;
+    use crate::{BinmatchError, FixedPattern, Pattern};
+    use alloc::string::ToString;
     use alloc::vec::Vec;
 
+    #[test]
+    fn test_error_display() {
+        let err: &dyn core::error::Error = &BinmatchError::StrideError;
+        assert_eq!(err.to_string(), "The stride of a search must be at least 1");
+        assert_eq!(
+            BinmatchError::PatternParseError {
+                ch: 'X',
+                position: 3
+            }
+            .to_string(),
+            "invalid character 'X' at position 3"
+        );
+    }
+
+    #[test]
+    fn test_stream_matcher() {
+        let pattern = Pattern::new_unchecked("AA ?? AA");
+        let mut matcher = pattern.stream_matcher();
+        let mut offsets = Vec::new();
+        for byte in [0xAA, 0x01, 0xAA, 0x02, 0xAA] {
+            offsets.extend(matcher.push(&[byte]).iter().map(|m| m.offset()));
+        }
+        assert_eq!(offsets, [0, 2].to_vec());
+    }
+
     #[test]
     fn test_pattern_chunk_matching() {
         let pattern = Pattern::new_unchecked("00 ?? 00 ??");
@@ -93,4 +3900,40 @@ mod no_std_tests {
         let matches = pattern.find_matches(data);
         assert_eq!(matches, [23].to_vec());
     }
+
+    #[test]
+    fn captures_into_arrays() {
+        let pattern = Pattern::new_unchecked("00 ?? ?? 00");
+        let data = [0x00, 0x12, 0x34, 0x00];
+        let m = pattern.match_at(&data, 0).unwrap();
+        let array: [u8; 2] = m.captures().to_array().unwrap();
+        assert_eq!(array, [0x12, 0x34]);
+        let mut buf = [0u8; 1];
+        assert!(m.captures().read_into(&mut buf).is_err());
+    }
+
+    #[test]
+    fn fixed_pattern_captures_without_alloc() {
+        let pattern = FixedPattern::<4>::new("00 ?? __ ??").unwrap();
+        let data = [0x00, 0x12, 0x34, 0x56];
+        let m = Pattern::from(&pattern).match_at(&data, 0).unwrap();
+        assert_eq!(
+            pattern.capture_array::<2>(&data),
+            m.captures().to_array::<2>()
+        );
+        assert_eq!(pattern.capture_array::<1>(&data), None);
+        assert_eq!(pattern.capture_array::<2>(&[0x01, 0x12, 0x34, 0x56]), None);
+
+        let mut buf = [0u8; 3];
+        assert_eq!(pattern.read_captures_into(&data, &mut buf), Ok(2));
+        assert_eq!(buf, [0x12, 0x56, 0x00]);
+        assert_eq!(
+            pattern.read_captures_into(&data, &mut buf[..1]),
+            Err(BinmatchError::CaptureBufferError {
+                needed: 2,
+                available: 1
+            })
+        );
+        assert_eq!(pattern.read_captures_into(&[0x01; 4], &mut buf), Ok(0));
+    }
 }
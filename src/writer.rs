@@ -0,0 +1,115 @@
+use crate::{FileMatch, Pattern, StreamMatcher};
+use std::fmt;
+use std::io::{self, Write};
+
+/// <div class="warning"> Only available using the <code>std</code> feature </div>
+///
+/// A [Write] adapter that forwards everything to an inner writer and searches the written bytes
+/// on the way, e.g. to scan a download while saving it
+///
+/// Only the bytes accepted by the inner writer are searched, so short writes are handled like
+/// everywhere else and the stream positions of the matches are those of the inner writer  
+/// Matches are collected, or handed to a callback set with [ScanWriter::on_match]
+///
+/// # Example:
+/// ```
+/// # use binmatch::{Pattern, ScanWriter};
+/// # use std::io::Write;
+/// let pattern = Pattern::new("50 4B 03 04").unwrap();
+/// let mut writer = ScanWriter::new(&pattern, Vec::new());
+/// writer.write_all(&[0x00, 0x50, 0x4B]).unwrap();
+/// writer.write_all(&[0x03, 0x04]).unwrap();
+/// assert_eq!(writer.matches()[0].offset(), 1);
+/// assert_eq!(writer.into_inner().len(), 5);
+/// ```
+pub struct ScanWriter<'p, W> {
+    inner: W,
+    matcher: StreamMatcher<'p>,
+    matches: Vec<FileMatch>,
+    callback: Option<Box<dyn FnMut(FileMatch) + 'p>>,
+}
+
+impl<'p, W: Write> ScanWriter<'p, W> {
+    pub fn new(pattern: &'p Pattern, inner: W) -> ScanWriter<'p, W> {
+        Self::new_at(pattern, inner, 0)
+    }
+
+    /// Same as [ScanWriter::new], but the stream positions start at `position`, e.g. when `inner`
+    /// appends to a file that is `position` bytes long
+    pub fn new_at(pattern: &'p Pattern, inner: W, position: u64) -> ScanWriter<'p, W> {
+        Self {
+            inner,
+            matcher: pattern.stream_matcher_at(position),
+            matches: Vec::new(),
+            callback: None,
+        }
+    }
+
+    /// Calls `callback` with every match as soon as it was written instead of collecting it
+    pub fn on_match(mut self, callback: impl FnMut(FileMatch) + 'p) -> ScanWriter<'p, W> {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// The matches found so far, in ascending order  
+    /// Always empty when a callback is set
+    #[inline(always)]
+    pub fn matches(&self) -> &[FileMatch] {
+        &self.matches
+    }
+
+    /// Takes the matches found so far, later matches are collected again
+    pub fn take_matches(&mut self) -> Vec<FileMatch> {
+        core::mem::take(&mut self.matches)
+    }
+
+    /// Number of bytes written to the inner writer so far
+    #[inline(always)]
+    pub fn position(&self) -> u64 {
+        self.matcher.position()
+    }
+
+    #[inline(always)]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writing to the inner writer directly bypasses the search, and the stream positions of later
+    /// matches no longer line up with the inner writer
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for ScanWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        for found in self.matcher.push(&buf[..written]) {
+            match &mut self.callback {
+                Some(callback) => callback(found),
+                None => self.matches.push(found),
+            }
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for ScanWriter<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScanWriter")
+            .field("inner", &self.inner)
+            .field("matcher", &self.matcher)
+            .field("matches", &self.matches)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
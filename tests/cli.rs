@@ -0,0 +1,103 @@
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// A file in the temp directory, removed on drop
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, contents: &[u8]) -> TempFile {
+        let path =
+            std::env::temp_dir().join(format!("binmatch-cli-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        TempFile(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn binmatch(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_binmatch"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn find_reports_offsets_and_exit_code() {
+    let file = TempFile::new("find", &[0x00, 0x74, 0x05, 0x90, 0x74, 0x10]);
+    let output = binmatch(&["find", "74 ??", file.path()]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        format!("{0}:0x00000001: 7405\n{0}:0x00000004: 7410\n", file.path())
+    );
+
+    let output = binmatch(&["find", "74 ??", file.path(), "--json"]);
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "[{{\"file\":\"{0}\",\"offset\":1,\"length\":2,\"captures\":\"05\"}},\
+             {{\"file\":\"{0}\",\"offset\":4,\"length\":2,\"captures\":\"10\"}}]\n",
+            file.path()
+        )
+    );
+
+    let output = binmatch(&["find", "74 ?? 90", file.path(), "--context", "1"]);
+    assert!(stdout(&output).contains("00 74 05 90 74"));
+    assert!(stdout(&output).contains("^^ ?? ^^"));
+
+    let output = binmatch(&["find", "CC", file.path()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn count_and_errors() {
+    let first = TempFile::new("count-1", &[0xCC, 0xCC, 0x00]);
+    let second = TempFile::new("count-2", &[0x00]);
+    let output = binmatch(&["count", "CC", first.path(), second.path()]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        format!("{}: 2\n{}: 0\n", first.path(), second.path())
+    );
+
+    assert_eq!(
+        binmatch(&["count", "XY", first.path()]).status.code(),
+        Some(2)
+    );
+    assert_eq!(binmatch(&["find", "CC"]).status.code(), Some(2));
+    let missing = std::env::temp_dir().join("binmatch-cli-missing");
+    let output = binmatch(&["find", "CC", missing.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn patch_file_in_place() {
+    let file = TempFile::new("patch", &[0x74, 0x05, 0x74, 0x10]);
+    let output = binmatch(&["patch", "74 ?? -> EB __", file.path(), "--dry-run"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(std::fs::read(&file.0).unwrap(), [0x74, 0x05, 0x74, 0x10]);
+
+    let output = binmatch(&["patch", "74 ?? -> EB __", file.path(), "--max", "1"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "@@ 0x00000000 @@\n-74 05\n+eb 05\n");
+    assert_eq!(std::fs::read(&file.0).unwrap(), [0xEB, 0x05, 0x74, 0x10]);
+
+    let output = binmatch(&["patch", "CC -> 90", file.path()]);
+    assert_eq!(output.status.code(), Some(1));
+}
@@ -0,0 +1,4 @@
+fn main() {
+    // `_start` is defined in main.rs, there is no libc to provide it
+    println!("cargo:rustc-link-arg-bins=-nostartfiles");
+}
@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+use binmatch::{FixedPattern, StreamScanner};
+use core::panic::PanicInfo;
+use core::ptr;
+
+extern "C" {
+    /// Never defined, so linking fails if a call to the panic handler survives optimization
+    fn binmatch_reachable_panic() -> !;
+}
+
+#[panic_handler]
+fn panic(_: &PanicInfo) -> ! {
+    unsafe { binmatch_reachable_panic() }
+}
+
+/// Hides `value` from the optimizer, so nothing is evaluated at compile time
+fn opaque<T>(value: T) -> T {
+    unsafe { ptr::read_volatile(&value) }
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let mut found = 0;
+    if let Ok(pattern) = FixedPattern::<4>::new(opaque("7E ?? __ 81")) {
+        found += u64::from(pattern.matches(&opaque([0x7E, 0x00, 0x00, 0x81])));
+
+        let window = opaque([0x7E, 0x12, 0x34, 0x81]);
+        if let Some([byte]) = pattern.capture_array::<1>(&window) {
+            found += u64::from(byte);
+        }
+        let mut buf = opaque([0u8; 1]);
+        found += pattern.read_captures_into(&window, &mut buf).unwrap_or(0) as u64;
+        found += pattern.read_captures_into(&window, &mut []).unwrap_or(0) as u64;
+
+        let mut scanner = StreamScanner::with_position(pattern, opaque(0));
+        found += scanner.push(opaque(0x7E)).unwrap_or(0);
+        scanner.push_slice(&opaque([0x7E, 0x01, 0x02, 0x81, 0x03]), |offset| {
+            found += offset
+        });
+        scanner.reset();
+        found += scanner.position();
+    }
+    opaque(found);
+    loop {}
+}